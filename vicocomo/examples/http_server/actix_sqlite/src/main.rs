@@ -10,6 +10,7 @@ vicocomo_actix::config! {
             vicocomo_sqlite::SqliteConn,
             vicocomo_sqlite::SqliteConn::new(
                 std::path::Path::new("test.sqlite"),
+                None,
             ).unwrap(),
         ),
     },
@@ -0,0 +1,147 @@
+use super::models::{rank::Rank, single_pk::SinglePk};
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, Error, QueryBld};
+
+pub fn test_aggregate(db: DatabaseIf) {
+    let _ = db.clone().exec("DELETE FROM single_pks", &[]);
+    let mut data = vec![
+        SinglePk {
+            id: None,
+            name: Some(String::from("a")),
+            data: Some(1.5),
+            opt: Some(1),
+            un2: 1,
+        },
+        SinglePk {
+            id: None,
+            name: Some(String::from("b")),
+            data: None,
+            opt: Some(2),
+            un2: 1,
+        },
+        SinglePk {
+            id: None,
+            name: Some(String::from("c")),
+            data: Some(2.5),
+            opt: Some(3),
+            un2: 2,
+        },
+    ];
+    assert!(SinglePk::insert_batch(db.clone(), &mut data[..]).is_ok());
+    let all = QueryBld::new().query().unwrap();
+    let un2_1 = QueryBld::new()
+        .col("un2")
+        .eq(Some(&DbValue::Int(1)))
+        .query()
+        .unwrap();
+    let none = QueryBld::new()
+        .col("un2")
+        .eq(Some(&DbValue::Int(3)))
+        .query()
+        .unwrap();
+
+    println!("\naggregate queries ---------------------------------------\n");
+
+    println!("count() ..");
+    assert_eq!(SinglePk::count(db.clone(), &all), Ok(3));
+    assert_eq!(SinglePk::count(db.clone(), &un2_1), Ok(2));
+    assert_eq!(SinglePk::count(db.clone(), &none), Ok(0));
+    let mut paged = all.clone();
+    paged.set_limit(Some(2));
+    assert_eq!(SinglePk::count(db.clone(), &paged), Ok(2));
+    paged.set_offset(Some(2));
+    assert_eq!(SinglePk::count(db.clone(), &paged), Ok(1));
+    paged.set_offset(Some(5));
+    assert_eq!(SinglePk::count(db.clone(), &paged), Ok(0));
+    let unset = QueryBld::new().col("un2").eq(None).query().unwrap();
+    assert!(SinglePk::count(db.clone(), &unset).is_err());
+    println!("    OK");
+    println!("exists() and is_empty() ..");
+    assert_eq!(SinglePk::exists(db.clone(), &un2_1), Ok(true));
+    assert_eq!(SinglePk::exists(db.clone(), &none), Ok(false));
+    assert_eq!(SinglePk::exists(db.clone(), &paged), Ok(false));
+    assert_eq!(SinglePk::is_empty(db.clone()), Ok(false));
+    println!("    OK");
+    println!("sum(), avg(), min(), and max() ..");
+    assert_eq!(
+        format!("{:?}", SinglePk::sum(db.clone(), "opt", &all)),
+        "Ok(Some(Int(6)))",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::sum(db.clone(), "data", &all)),
+        "Ok(Some(Float(4.0)))",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::avg(db.clone(), "opt", &un2_1)),
+        "Ok(Some(Float(1.5)))",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::min(db.clone(), "name", &all)),
+        "Ok(Some(Text(\"a\")))",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::max(db.clone(), "data", &un2_1)),
+        "Ok(Some(Float(1.5)))",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::sum(db.clone(), "opt", &none)),
+        "Ok(None)",
+    );
+    println!("    OK");
+    println!("aggregates respect limit and order ..");
+    let mut first_two = all.clone();
+    first_two.set_limit(Some(2));
+    // default order is un2 DESC, name ASC, i.e. c, a, b
+    assert_eq!(
+        format!("{:?}", SinglePk::sum(db.clone(), "opt", &first_two)),
+        "Ok(Some(Int(4)))",
+    );
+    let by_opt = QueryBld::new().order("opt DESC").limit(2).query().unwrap();
+    assert_eq!(
+        format!("{:?}", SinglePk::sum(db.clone(), "opt", &by_opt)),
+        "Ok(Some(Int(5)))",
+    );
+    println!("    OK");
+    println!("group_by() ..");
+    assert_eq!(
+        format!("{:?}", SinglePk::group_by(db.clone(), "un2", &all)),
+        "Ok([(Int(1), 2), (Int(2), 1)])",
+    );
+    assert_eq!(
+        format!("{:?}", SinglePk::group_by(db.clone(), "opt", &un2_1)),
+        "Ok([(Int(1), 1), (Int(2), 1)])",
+    );
+    let not_null = QueryBld::new()
+        .filter("data IS NOT NULL", &[])
+        .query()
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", SinglePk::group_by(db.clone(), "data", &not_null)),
+        "Ok([(NulFloat(Some(1.5)), 1), (NulFloat(Some(2.5)), 1)])",
+    );
+    println!("    OK");
+    println!("renamed columns ..");
+    assert_eq!(
+        format!("{:?}", Rank::sum(db.clone(), "rank", &all)),
+        "Ok(Some(Int(4)))",
+    );
+    assert_eq!(
+        format!("{:?}", Rank::max(db.clone(), "label", &all)),
+        "Ok(Some(Text(\"c\")))",
+    );
+    assert!(Rank::sum(db.clone(), "un2", &all).is_err());
+    println!("    OK");
+    println!("invalid fields ..");
+    assert!(matches!(
+        SinglePk::sum(db.clone(), "name", &all),
+        Err(Error::InvalidInput(_)),
+    ));
+    assert!(matches!(
+        SinglePk::avg(db.clone(), "no_such_field", &all),
+        Err(Error::InvalidInput(_)),
+    ));
+    assert!(matches!(
+        SinglePk::group_by(db.clone(), "no_such_field", &all),
+        Err(Error::InvalidInput(_)),
+    ));
+    println!("    OK");
+}
@@ -0,0 +1,89 @@
+use vicocomo::DatabaseIf;
+
+pub fn test_blob(db: DatabaseIf) {
+    use super::models::attachment::Attachment;
+    use vicocomo::{is_error, ActiveRecord};
+
+    let _ = db.clone().exec("DELETE FROM attachments", &[]);
+
+    println!("\nbinary values -------------------------------------------\n");
+
+    let mut a1 = Attachment {
+        id: 1,
+        data: vec![0, 1, 2, 255],
+        thumbnail: None,
+    };
+    println!("inserting {a1:?} ..");
+    let res = a1.insert(db.clone());
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(Attachment::find(db.clone(), &1), Some(a1.clone()));
+    println!("    OK");
+    println!("updating {a1:?} ..");
+    a1.thumbnail = Some(vec![0xca, 0xfe]);
+    assert!(a1.update(db.clone()).is_ok());
+    assert_eq!(Attachment::find(db.clone(), &1), Some(a1.clone()));
+    println!("    OK");
+    let mut a2 = Attachment {
+        id: 2,
+        data: Vec::new(),
+        thumbnail: Some(Vec::new()),
+    };
+    println!("error inserting empty required binary value ..");
+    let res = a2.insert(db.clone());
+    assert!(is_error!(
+        &res.err().unwrap(),
+        Model(CannotSave, "Attachment", None, "data", ["required"]),
+    ));
+    println!("    OK");
+    println!("inserting empty non-required binary value ..");
+    a2.data = vec![0x41];
+    assert!(a2.insert(db.clone()).is_ok());
+    assert_eq!(Attachment::find(db.clone(), &2), Some(a2.clone()));
+    println!("    OK");
+
+    println!("binary values to CSV ..");
+    let csv = "id,data,thumbnail\r\n\
+        1,\"000102ff\",\"cafe\"\r\n\
+        2,\"41\",\"\"\r\n";
+    let res = Attachment::try_to_csv(db.clone(), None);
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(res.unwrap(), ("attachments".to_string(), csv.to_string()));
+    println!("    OK");
+    println!("binary values from CSV ..");
+    let res =
+        Attachment::try_from_csv(db.clone(), "id,data\r\n3,00\r\n", None);
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        Attachment::load(db.clone()).unwrap(),
+        vec![Attachment {
+            id: 3,
+            data: vec![0],
+            thumbnail: None,
+        }],
+    );
+    let res = Attachment::try_from_csv(db.clone(), csv, None);
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        Attachment::load(db.clone()).unwrap(),
+        vec![a1.clone(), a2.clone()],
+    );
+    println!("    OK");
+    println!("error reading invalid hexadecimal from CSV ..");
+    assert!(Attachment::try_from_csv(
+        db.clone(),
+        "id,data\r\n3,0g\r\n",
+        None
+    )
+    .is_err());
+    assert!(Attachment::try_from_csv(
+        db.clone(),
+        "id,data\r\n3,012\r\n",
+        None
+    )
+    .is_err());
+    assert_eq!(
+        Attachment::load(db.clone()).unwrap(),
+        vec![a1.clone(), a2.clone()],
+    );
+    println!("    OK");
+}
@@ -0,0 +1,82 @@
+use super::models::{article::Article, empty_tables};
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, Original};
+
+pub fn test_change_tracking(db: DatabaseIf) {
+    empty_tables(db.clone(), &["articles"]);
+    let all_cols = |cols: &[&str]| {
+        cols.iter().map(|c| c.to_string()).collect::<Vec<_>>()
+    };
+
+    println!("\nchange tracking -----------------------------------------\n");
+
+    println!("all columns of a new object are changed ..");
+    let mut art = Article {
+        id: 1,
+        title: "title".to_string(),
+        body: "body".to_string(),
+        views: None,
+        lock_version: 0,
+        original: Original::default(),
+    };
+    assert!(!art.original.is_loaded());
+    assert_eq!(
+        art.changed_columns(),
+        all_cols(&["id", "title", "body", "lock_version"]),
+    );
+    println!("    OK");
+    println!("a saved or loaded object has none ..");
+    assert!(art.insert(db.clone()).is_ok());
+    assert!(art.original.is_loaded());
+    assert_eq!(art.views, Some(0));
+    assert!(art.changed_columns().is_empty());
+    let found = Article::find(db.clone(), &1).unwrap();
+    assert!(found.original.is_loaded());
+    assert!(found.changed_columns().is_empty());
+    assert_eq!(found, art);
+    println!("    OK");
+    println!("changed_columns() reports the differences ..");
+    art.title = "new title".to_string();
+    art.views = Some(1);
+    assert_eq!(art.changed_columns(), all_cols(&["title", "views"]));
+    art.views = None;
+    assert_eq!(art.changed_columns(), all_cols(&["title"]));
+    println!("    OK");
+    println!("update() sends only the changed columns ..");
+    let mut other = Article::find(db.clone(), &1).unwrap();
+    other.body = "other body".to_string();
+    assert!(other.update(db.clone()).is_ok());
+    assert_eq!(other.lock_version, 1);
+    assert!(other.changed_columns().is_empty());
+    // optimistic locking would otherwise prevent the update
+    art.lock_version = 1;
+    assert!(art.update(db.clone()).is_ok());
+    assert!(art.changed_columns().is_empty());
+    assert_eq!(art.body, "other body");
+    assert_eq!(art.lock_version, 2);
+    assert_eq!(Article::find(db.clone(), &1), Some(art.clone()));
+    println!("    OK");
+    println!("save() with no changes writes nothing ..");
+    assert!(db
+        .clone()
+        .exec(
+            "UPDATE articles SET title = $1 WHERE id = 1",
+            &[DbValue::Text("behind the back".to_string())],
+        )
+        .is_ok());
+    assert!(art.save(db.clone()).is_ok());
+    assert_eq!(art.title, "new title");
+    assert_eq!(art.lock_version, 2);
+    let found = Article::find(db.clone(), &1).unwrap();
+    assert_eq!(found.title, "behind the back");
+    assert_eq!(found.lock_version, 2);
+    println!("    OK");
+    println!("update_columns() remembers the updated values ..");
+    assert!(art
+        .update_columns(db.clone(), &[("views", DbValue::Int(7))])
+        .is_ok());
+    assert_eq!(art.title, "behind the back");
+    assert_eq!(art.views, Some(7));
+    assert!(art.changed_columns().is_empty());
+    println!("    OK");
+    empty_tables(db.clone(), &["articles"]);
+}
@@ -0,0 +1,126 @@
+use super::models::Score;
+use vicocomo::{is_error, ActiveRecord, DatabaseIf, Error};
+
+pub fn test_constraint(db: DatabaseIf) {
+    let _ = db.clone().exec("DELETE FROM scores", &[]);
+
+    println!("\nconstraint violations -----------------------------------\n");
+
+    println!("NOT NULL violation SQLSTATE ..");
+    let res = db.clone().exec(
+        "INSERT INTO scores (id, name, points) VALUES (1, NULL, 1)",
+        &[],
+    );
+    assert!(res.is_err());
+    let err = res.unwrap_err();
+    assert!(err.is_not_null_violation());
+    assert!(
+        matches!(
+            &err,
+            Error::Database(de) if de.column.as_deref() == Some("name"),
+        ),
+        "{:?}",
+        err,
+    );
+    println!("    OK");
+    println!("CHECK violation SQLSTATE ..");
+    let res = db.clone().exec(
+        "INSERT INTO scores (id, name, points) VALUES (1, 'one', -1)",
+        &[],
+    );
+    assert!(res.is_err());
+    let err = res.unwrap_err();
+    assert!(err.is_check_violation());
+    assert!(
+        matches!(
+            &err,
+            Error::Database(de)
+                if de.constraint.as_deref() == Some("scores_points_check"),
+        ),
+        "{:?}",
+        err,
+    );
+    println!("    OK");
+    println!("PRIMARY KEY violation SQLSTATE ..");
+    assert!(db
+        .clone()
+        .exec(
+            "INSERT INTO scores (id, name, points) VALUES (1, 'one', 1)",
+            &[],
+        )
+        .is_ok());
+    let res = db.clone().exec(
+        "INSERT INTO scores (id, name, points) VALUES (1, 'two', 2)",
+        &[],
+    );
+    assert!(res.is_err());
+    assert!(res.unwrap_err().is_unique_violation());
+    println!("    OK");
+
+    println!("NOT NULL violation to field error ..");
+    let mut score = Score {
+        id: 2,
+        name: None,
+        points: 2,
+    };
+    let res = score.insert(db.clone());
+    assert!(res.is_err());
+    let err = res.unwrap_err();
+    assert!(
+        is_error!(
+            &err,
+            Model(
+                NotNull,
+                "Score",
+                Some("not-null-violation".to_string()),
+                "name",
+                [],
+            )
+        ),
+        "{:?}",
+        err,
+    );
+    assert!(matches!(&err, Error::Model(me) if me.field_errors.len() == 1));
+    println!("    OK");
+    println!("CHECK violation to field error ..");
+    score.name = Some("two".to_string());
+    score.points = -2;
+    let res = score.insert(db.clone());
+    assert!(res.is_err());
+    let err = res.unwrap_err();
+    assert!(
+        is_error!(
+            &err,
+            Model(
+                CheckFailed,
+                "Score",
+                Some("check-violation".to_string()),
+                "points",
+                [],
+            )
+        ),
+        "{:?}",
+        err,
+    );
+    assert!(matches!(&err, Error::Model(me) if me.field_errors.len() == 1));
+    println!("    OK");
+    println!("CHECK violation on update ..");
+    let mut score = Score::find(db.clone(), &1).unwrap();
+    score.points = -1;
+    let res = score.update(db.clone());
+    assert!(res.is_err());
+    assert!(is_error!(
+        &res.unwrap_err(),
+        Model(
+            CheckFailed,
+            "Score",
+            Some("check-violation".to_string()),
+            "points",
+            [],
+        )
+    ));
+    assert_eq!(Score::find(db.clone(), &1).unwrap().points, 1);
+    println!("    OK");
+
+    let _ = db.clone().exec("DELETE FROM scores", &[]);
+}
@@ -1,8 +1,9 @@
 use vicocomo::DatabaseIf;
 pub fn test_csv(db: DatabaseIf) {
     use super::models::{
-        backup, find_or_insert_default_parent, restore, DefaultParent, Join,
-        MultiPk, NoPk, NonstandardParent, Random, Serialize, SinglePk,
+        backup, backup_to, find_or_insert_default_parent, restore,
+        Attachment, DefaultParent, Join, MultiPk, Native, NoPk,
+        NonstandardParent, Random, Serialize, SinglePk,
     };
     use std::str::from_utf8;
     use vicocomo::{check_backup, ActiveRecord, Error};
@@ -150,6 +151,9 @@ pub fn test_csv(db: DatabaseIf) {
     let bkp = backup(db.clone());
     assert!(bkp.is_ok(), "{bkp:?}");
     let bkp = bkp.unwrap();
+    let mut streamed = Vec::new();
+    assert!(backup_to(db.clone(), &mut streamed).is_ok());
+    assert_eq!(streamed, bkp);
     let contents = check_backup(&bkp);
     assert!(contents.is_ok(), "{:?}", contents);
     let contents = &contents.unwrap();
@@ -165,6 +169,10 @@ pub fn test_csv(db: DatabaseIf) {
         format!(
             "--- joins ---\r\n\
             \r\n\
+            --- attachments ---\r\n\
+            id,data,thumbnail\r\n\
+            1,\"000102ff\",\"cafe\"\r\n\
+            2,\"41\",\"\"\r\n\
             --- default_parents ---\r\n\
             id,name\r\n\
             {default_filler_id},\"default filler\"\r\n\
@@ -178,6 +186,12 @@ pub fn test_csv(db: DatabaseIf) {
             \"\",0,0,0\r\n\
             1,2,0,,0,1,0,1,0,1,{used_default_id},,\"bonus nonstandard\",0,0,\
             \"\",0,0,0\r\n\
+            --- natives ---\r\n\
+            id,flag,flag_nul,date,date_time,time_nul,date_opt\r\n\
+            1,true,,\"2024-02-29\",\"2024-02-29 12:34:56.789\",\
+            \"23:59:59\",\"2000-01-01\"\r\n\
+            2,false,true,\"1999-12-31\",\"2000-01-01 00:00:00\",,\
+            \"2024-03-01\"\r\n\
             --- no_pks ---\r\n\
             data\r\n\
             4713\r\n\
@@ -213,8 +227,10 @@ pub fn test_csv(db: DatabaseIf) {
 
     println!("restore from CSV ..");
     let joins = Join::load(db.clone()).unwrap();
+    let attachments = Attachment::load(db.clone()).unwrap();
     let default_parents = DefaultParent::load(db.clone()).unwrap();
     let multi_pks = MultiPk::load(db.clone()).unwrap();
+    let natives = Native::load(db.clone()).unwrap();
     let no_pks = NoPk::load(db.clone()).unwrap();
     let nonstandard_parents = NonstandardParent::load(db.clone()).unwrap();
     let randoms = Random::load(db.clone()).unwrap();
@@ -222,8 +238,10 @@ pub fn test_csv(db: DatabaseIf) {
     let res = restore(db.clone(), &bkp);
     assert!(res.is_ok(), "{res:?}");
     assert_eq!(Join::load(db.clone()).unwrap(), joins);
+    assert_eq!(Attachment::load(db.clone()).unwrap(), attachments);
     assert_eq!(DefaultParent::load(db.clone()).unwrap(), default_parents);
     assert_eq!(MultiPk::load(db.clone()).unwrap(), multi_pks);
+    assert_eq!(Native::load(db.clone()).unwrap(), natives);
     assert_eq!(NoPk::load(db.clone()).unwrap(), no_pks);
     assert_eq!(
         NonstandardParent::load(db.clone()).unwrap(),
@@ -245,6 +263,12 @@ pub fn test_csv(db: DatabaseIf) {
             \"\",0,0,0\r\n\
             1,2,0,,0,1,0,1,0,1,{used_default_id},,\"bonus nonstandard\",0,0,\
             \"\",0,0,0\r\n\
+            --- natives ---\r\n\
+            id,flag,flag_nul,date,date_time,time_nul,date_opt\r\n\
+            1,true,,\"2024-02-29\",\"2024-02-29 12:34:56.789\",\
+            \"23:59:59\",\"2000-01-01\"\r\n\
+            2,false,true,\"1999-12-31\",\"2000-01-01 00:00:00\",,\
+            \"2024-03-01\"\r\n\
             --- serializes ---\r\n\
             id,not_null,nullable,optional,opt_null\r\n\
             1,\"{{\"\"pair\"\":[1,43],\"\"text\"\":\"\"not-null\"\"}}\",\
@@ -273,6 +297,10 @@ pub fn test_csv(db: DatabaseIf) {
             {used_default_id},\"used default\"\r\n\
             --- joins ---\r\n\
             \r\n\
+            --- attachments ---\r\n\
+            id,data,thumbnail\r\n\
+            1,\"000102ff\",\"cafe\"\r\n\
+            2,\"41\",\"\"\r\n\
             --- no_pks ---\r\n\
             data\r\n\
             4713\r\n\
@@ -286,8 +314,10 @@ pub fn test_csv(db: DatabaseIf) {
     let res = restore(db.clone(), &shuffled);
     assert!(res.is_ok(), "{res:?}");
     assert_eq!(Join::load(db.clone()).unwrap(), joins);
+    assert_eq!(Attachment::load(db.clone()).unwrap(), attachments);
     assert_eq!(DefaultParent::load(db.clone()).unwrap(), default_parents);
     assert_eq!(MultiPk::load(db.clone()).unwrap(), multi_pks);
+    assert_eq!(Native::load(db.clone()).unwrap(), natives);
     assert_eq!(NoPk::load(db.clone()).unwrap(), no_pks);
     assert_eq!(
         NonstandardParent::load(db.clone()).unwrap(),
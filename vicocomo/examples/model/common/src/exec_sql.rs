@@ -0,0 +1,118 @@
+use vicocomo::DatabaseIf;
+
+pub fn test_exec_sql(db: DatabaseIf) {
+    use vicocomo::{try_exec_sql, DbType, Error};
+
+    let drop_all = |db: DatabaseIf| {
+        let _ = db.clone().exec("DROP TABLE IF EXISTS exec_notes", &[]);
+        let _ = db.clone().exec("DROP TABLE IF EXISTS exec_log", &[]);
+        let _ = db.exec("DROP FUNCTION IF EXISTS exec_notes_log()", &[]);
+    };
+    drop_all(db.clone());
+    let sqlite = db
+        .clone()
+        .query("SELECT sqlite_version()", &[], &[DbType::Text])
+        .is_ok();
+
+    println!("\nexecuting SQL source ------------------------------------\n");
+
+    println!("semicolons in strings and comments ..");
+    let res = try_exec_sql(
+        db.clone(),
+        "-- a comment; with a semicolon\n\
+        CREATE TABLE exec_notes (id BIGINT PRIMARY KEY, note TEXT);\n\
+        /* a block comment;\n   with a semicolon */\n\
+        CREATE TABLE \"exec_log\" (note TEXT);\n\
+        -- the end;\n",
+        None,
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    println!("    OK");
+    println!("trigger bodies ..");
+    let res = try_exec_sql(
+        db.clone(),
+        if sqlite {
+            "CREATE TRIGGER exec_notes_log AFTER INSERT ON exec_notes\n\
+            BEGIN\n\
+                INSERT INTO exec_log (note) VALUES (\n\
+                    CASE WHEN NEW.id = 1 THEN 'one;' ELSE NEW.note END\n\
+                );\n\
+                INSERT INTO exec_log (note) VALUES ('again');\n\
+            END;\n"
+        } else {
+            "CREATE FUNCTION exec_notes_log() RETURNS TRIGGER AS $body$\n\
+            BEGIN\n\
+                INSERT INTO exec_log (note) VALUES (\n\
+                    CASE WHEN NEW.id = 1 THEN 'one;' ELSE NEW.note END\n\
+                );\n\
+                INSERT INTO exec_log (note) VALUES ('again');\n\
+                RETURN NEW;\n\
+            END;\n\
+            $body$ LANGUAGE plpgsql;\n\
+            CREATE TRIGGER exec_notes_log AFTER INSERT ON exec_notes\n\
+            FOR EACH ROW EXECUTE FUNCTION exec_notes_log();\n"
+        },
+        None,
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    let res = try_exec_sql(
+        db.clone(),
+        "INSERT INTO exec_notes (id, note) VALUES (1, 'semi;colon');\n\
+        INSERT INTO exec_notes (id, note) VALUES (2, 'it''s; quoted')",
+        None,
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    let res = db.clone().query(
+        "SELECT note FROM exec_notes ORDER BY id",
+        &[],
+        &[DbType::Text],
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        res.unwrap()
+            .iter()
+            .map(|row| row[0].to_string())
+            .collect::<Vec<_>>(),
+        vec!["semi;colon", "it's; quoted"],
+    );
+    let res = db.clone().query(
+        "SELECT note FROM exec_log ORDER BY note",
+        &[],
+        &[DbType::Text],
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        res.unwrap()
+            .iter()
+            .map(|row| row[0].to_string())
+            .collect::<Vec<_>>(),
+        vec!["again", "again", "it's; quoted", "one;"],
+    );
+    println!("    OK");
+    println!("error reports the failing statement ..");
+    let res = try_exec_sql(
+        db.clone(),
+        "INSERT INTO exec_notes (id, note) VALUES (3, 'three');\n\
+        INSERT INTO no_such_table (id) VALUES (3);\n",
+        None,
+    );
+    match res {
+        Err(Error::Database(ref e)) => assert!(
+            e.text.starts_with(
+                "statement 2 `INSERT INTO no_such_table (id) VALUES (3)`: "
+            ),
+            "{:?}",
+            res,
+        ),
+        _ => panic!("expected Error::Database, got {:?}", res),
+    }
+    let res = try_exec_sql(
+        db.clone(),
+        "INSERT INTO no_such_table (id) VALUES (4)",
+        Some(Error::other("original")),
+    );
+    assert_eq!(res, Err(Error::other("original")));
+    println!("    OK");
+
+    drop_all(db);
+}
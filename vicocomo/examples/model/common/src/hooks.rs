@@ -0,0 +1,170 @@
+use super::models::{account::Account, audit::Audit, empty_tables};
+use vicocomo::{is_error, ActiveRecord, DatabaseIf, DbValue};
+
+pub fn test_hooks(db: DatabaseIf) {
+    empty_tables(db.clone(), &["accounts", "audits"]);
+    let audits = || {
+        Audit::load(db.clone())
+            .unwrap()
+            .drain(..)
+            .map(|a| (a.account_id, a.what))
+            .collect::<Vec<_>>()
+    };
+
+    println!("\nhooks ---------------------------------------------------\n");
+
+    println!("validate() collects all errors ..");
+    let mut a1 = Account {
+        id: 1,
+        name: " ".to_string(),
+        balance: -1,
+    };
+    assert!(is_error!(
+        a1.insert(db.clone()).unwrap_err(),
+        Model(
+            CannotSave,
+            "Account",
+            None,
+            "name",
+            ["required"],
+            "balance",
+            ["negative"],
+        ),
+    ));
+    assert_eq!(Account::find(db.clone(), &1), None);
+    a1.name = "a1".to_string();
+    a1.balance = 10;
+    assert!(a1.insert(db.clone()).is_ok());
+    a1.name = "much too long".to_string();
+    a1.balance = -10;
+    assert!(is_error!(
+        a1.update(db.clone()).unwrap_err(),
+        Model(
+            CannotSave,
+            "Account",
+            None,
+            "name",
+            ["too-long"],
+            "balance",
+            ["negative"],
+        ),
+    ));
+    assert_eq!(Account::find(db.clone(), &1).unwrap().name, "a1");
+    println!("    OK");
+    println!("after_save() is called by insert() and update() ..");
+    a1.name = "a1".to_string();
+    a1.balance = 20;
+    assert!(a1.update(db.clone()).is_ok());
+    assert_eq!(
+        audits(),
+        vec![(1, "saved 10".to_string()), (1, "saved 20".to_string())],
+    );
+    println!("    OK");
+    println!("after_save() is called by insert_batch() ..");
+    let mut batch = [
+        Account {
+            id: 2,
+            name: "a2".to_string(),
+            balance: 0,
+        },
+        Account {
+            id: 3,
+            name: "a3".to_string(),
+            balance: 30,
+        },
+    ];
+    assert!(Account::insert_batch(db.clone(), &mut batch).is_ok());
+    assert_eq!(audits().len(), 4);
+    assert!(audits().contains(&(2, "saved 0".to_string())));
+    assert!(audits().contains(&(3, "saved 30".to_string())));
+    println!("    OK");
+    println!("an after_save() error rolls back the save ..");
+    let mut unaudited = Account {
+        id: 4,
+        name: "unaudited".to_string(),
+        balance: 0,
+    };
+    assert!(is_error!(
+        unaudited.insert(db.clone()).unwrap_err(),
+        Model(CannotSave, "Account", Some("unaudited".to_string())),
+    ));
+    assert_eq!(Account::find(db.clone(), &4), None);
+    let mut batch = [
+        Account {
+            id: 4,
+            name: "a4".to_string(),
+            balance: 0,
+        },
+        Account {
+            id: 5,
+            name: "unaudited".to_string(),
+            balance: 0,
+        },
+    ];
+    assert!(Account::insert_batch(db.clone(), &mut batch).is_err());
+    assert_eq!(Account::find(db.clone(), &4), None);
+    let mut a2 = batch[0].clone();
+    a2.id = 2;
+    a2.name = "unaudited".to_string();
+    assert!(a2.update(db.clone()).is_err());
+    assert_eq!(Account::find(db.clone(), &2).unwrap().name, "a2");
+    assert_eq!(audits().len(), 4);
+    println!("    OK");
+    println!("after_delete() is called by delete() ..");
+    assert!(is_error!(
+        a1.clone().delete(db.clone()).unwrap_err(),
+        Model(CannotDelete, "Account", None, "balance", ["not-zero"]),
+    ));
+    assert!(Account::find(db.clone(), &1).is_some());
+    a1.balance = 0;
+    assert!(a1.update(db.clone()).is_ok());
+    assert!(a1.clone().delete(db.clone()).is_ok());
+    assert_eq!(Account::find(db.clone(), &1), None);
+    assert!(audits().contains(&(1, "deleted 0".to_string())));
+    assert_eq!(audits().len(), 6);
+    println!("    OK");
+    println!("an after_delete() error rolls back the delete ..");
+    let mut a2 = Account::find(db.clone(), &2).unwrap();
+    assert!(a2
+        .update_columns(
+            db.clone(),
+            &[("name", DbValue::Text("unaudited".to_string()))],
+        )
+        .is_ok());
+    assert!(a2.clone().delete(db.clone()).is_err());
+    assert_eq!(Account::find(db.clone(), &2), Some(a2.clone()));
+    assert_eq!(audits().len(), 6);
+    println!("    OK");
+    println!("delete_batch() calls the hooks if opted in ..");
+    assert!(a2
+        .update_columns(
+            db.clone(),
+            &[("name", DbValue::Text("a2".to_string()))],
+        )
+        .is_ok());
+    assert!(is_error!(
+        Account::delete_batch(db.clone(), &[2, 3]).unwrap_err(),
+        Model(CannotDelete, "Account", None, "balance", ["not-zero"]),
+    ));
+    assert!(is_error!(
+        Account::delete_batch(db.clone(), &[2, 4711]).unwrap_err(),
+        Model(
+            CannotDelete,
+            "Account",
+            Some("not-found".to_string()),
+            "id",
+            []
+        ),
+    ));
+    assert_eq!(Account::load(db.clone()).unwrap().len(), 2);
+    let mut a3 = Account::find(db.clone(), &3).unwrap();
+    a3.balance = 0;
+    assert!(a3.update(db.clone()).is_ok());
+    assert_eq!(Account::delete_batch(db.clone(), &[2, 3]), Ok(2));
+    assert_eq!(Account::load(db.clone()), Ok(Vec::new()));
+    assert!(audits().contains(&(2, "deleted 0".to_string())));
+    assert!(audits().contains(&(3, "deleted 0".to_string())));
+    assert_eq!(audits().len(), 9);
+    println!("    OK");
+    empty_tables(db.clone(), &["accounts", "audits"]);
+}
@@ -0,0 +1,143 @@
+use super::models::{
+    reset_many_to_many, DefaultParent, MultiPk, NonstandardParent,
+};
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, Error, QueryBld};
+
+pub fn test_join(db: DatabaseIf) {
+    println!("\njoin queries --------------------------------------------\n");
+
+    let (dp, pa, pb, sa, sb) = reset_many_to_many(db.clone());
+    assert!(pa.connect_to_single_pk(db.clone(), &sa).is_ok());
+    assert!(pa.connect_to_single_pk(db.clone(), &sb).is_ok());
+    assert!(pb.connect_to_single_pk(db.clone(), &sb).is_ok());
+    let bp =
+        NonstandardParent::find(db.clone(), &"bonus nonstandard".to_string())
+            .unwrap();
+    let mut np =
+        NonstandardParent::find(db.clone(), &"nonstandard".to_string())
+            .unwrap();
+    assert!(np.set_nonstandard_parent(&bp).is_ok());
+    assert!(np.save(db.clone()).is_ok());
+    let text = |s: &str| Some(DbValue::Text(s.to_string()));
+    let parent_names = |query: &vicocomo::Query| {
+        let mut names = DefaultParent::query(db.clone(), query)
+            .unwrap()
+            .drain(..)
+            .map(|p| p.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    println!("belongs-to join ..");
+    let query = QueryBld::new()
+        .join("default_parent")
+        .filter("default_parent.name = $1", &[text("used default")])
+        .query()
+        .unwrap();
+    let found = MultiPk::query(db.clone(), &query).unwrap();
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().all(|m| m.default_parent_id == dp.id));
+    assert_eq!(MultiPk::count(db.clone(), &query), Ok(2));
+    assert_eq!(
+        format!("{:?}", MultiPk::sum(db.clone(), "id2", &query)),
+        "Ok(Some(Int(3)))",
+    );
+    let query = QueryBld::new()
+        .join("default_parent")
+        .filter("default_parent.name = $1", &[text("parent-a")])
+        .query()
+        .unwrap();
+    assert!(MultiPk::query(db.clone(), &query).unwrap().is_empty());
+    assert_eq!(MultiPk::exists(db.clone(), &query), Ok(false));
+    println!("    OK");
+    println!("self-referencing joins ..");
+    let query = QueryBld::new()
+        .join("nonstandard_parent")
+        .filter("nonstandard_parent.pk = $1", &[text(&bp.pk)])
+        .query()
+        .unwrap();
+    assert_eq!(
+        NonstandardParent::query(db.clone(), &query).unwrap(),
+        vec![np.clone()],
+    );
+    let query = QueryBld::new()
+        .join("nonstandard_parents")
+        .filter("nonstandard_parents.pk = $1", &[text(&np.pk)])
+        .query()
+        .unwrap();
+    assert_eq!(
+        NonstandardParent::query(db.clone(), &query).unwrap(),
+        vec![bp.clone()],
+    );
+    println!("    OK");
+    println!("one-to-many join ..");
+    let query = QueryBld::new()
+        .join("multi_pks")
+        .filter("multi_pks.id2 > $1", &[Some(DbValue::Int(0))])
+        .query()
+        .unwrap();
+    assert_eq!(parent_names(&query), vec!["used default"]);
+    assert_eq!(DefaultParent::count(db.clone(), &query), Ok(1));
+    println!("    OK");
+    println!("many-to-many join ..");
+    let query = QueryBld::new()
+        .join("single_pks")
+        .join("single_pks")
+        .filter("single_pks.name = $1", &[text("child-b")])
+        .query()
+        .unwrap();
+    assert_eq!(query.joins().len(), 1);
+    assert_eq!(parent_names(&query), vec!["parent-a", "parent-b"]);
+    assert_eq!(DefaultParent::count(db.clone(), &query), Ok(2));
+    let query = QueryBld::new()
+        .join("single_pks")
+        .filter(
+            "this.name = $1 AND single_pks.un2 = $2",
+            &[text("parent-a"), Some(DbValue::Int(101))],
+        )
+        .query()
+        .unwrap();
+    assert_eq!(parent_names(&query), vec!["parent-a"]);
+    println!("    OK");
+    println!("ordering by a joined column ..");
+    let ordered_names = |query: &vicocomo::Query| {
+        DefaultParent::query(db.clone(), query)
+            .unwrap()
+            .drain(..)
+            .map(|p| p.name)
+            .collect::<Vec<_>>()
+    };
+    let query = QueryBld::new()
+        .join("multi_pks")
+        .order("multi_pks.id2 DESC")
+        .query()
+        .unwrap();
+    assert_eq!(ordered_names(&query), vec!["used default"]);
+    assert_eq!(DefaultParent::count(db.clone(), &query), Ok(1));
+    let query = QueryBld::new()
+        .join("single_pks")
+        .order("single_pks.name DESC, this.name DESC")
+        .query()
+        .unwrap();
+    assert_eq!(ordered_names(&query), vec!["parent-b", "parent-a"]);
+    let query = QueryBld::new()
+        .join("single_pks")
+        .order("single_pks.name, this.name DESC")
+        .query()
+        .unwrap();
+    assert_eq!(ordered_names(&query), vec!["parent-a", "parent-b"]);
+    let query = query.builder().limit(1).offset(1).query().unwrap();
+    assert_eq!(ordered_names(&query), vec!["parent-b"]);
+    assert_eq!(DefaultParent::count(db.clone(), &query), Ok(1));
+    println!("    OK");
+    println!("unknown join ..");
+    let query = QueryBld::new().join("no_such_assoc").query().unwrap();
+    assert!(matches!(
+        DefaultParent::query(db.clone(), &query),
+        Err(Error::InvalidInput(_)),
+    ));
+    println!("    OK");
+    np.nonstandard_parent_id = None;
+    assert!(np.save(db.clone()).is_ok());
+}
@@ -1,26 +1,72 @@
 // TODO: test optional unique field without value
 
+pub mod aggregate;
+pub use aggregate::test_aggregate;
 pub mod belongs_to;
 pub use belongs_to::test_belongs_to;
+pub mod blob;
+pub use blob::test_blob;
+pub mod change_tracking;
+pub use change_tracking::test_change_tracking;
+pub mod constraint;
+pub use constraint::test_constraint;
 pub mod delete;
 pub use delete::test_delete;
+pub mod exec_sql;
+pub use exec_sql::test_exec_sql;
+pub mod hooks;
+pub use hooks::test_hooks;
+pub mod join;
+pub use join::test_join;
+pub mod lock_version;
+pub use lock_version::test_lock_version;
 pub mod many_to_many;
 pub use many_to_many::test_many_to_many;
+pub mod migration;
+pub use migration::test_migration;
 pub mod models;
 pub use models::setup;
 pub mod multi_pk;
 pub use multi_pk::test_multi_pk;
+pub mod native;
+pub use native::test_native;
 pub mod no_pk;
 pub use no_pk::test_no_pk;
 pub mod nonstandard_parent;
 pub use nonstandard_parent::test_nonstandard_parent;
 pub mod one_to_many;
 pub use one_to_many::test_one_to_many;
+pub mod preload;
+pub use preload::test_preload;
+pub mod query_bld;
+pub use query_bld::test_query_bld;
+pub mod query_each;
+pub use query_each::test_query_each;
+pub mod query_log;
+pub use query_log::test_query_log;
+pub mod query_rows;
+pub use query_rows::test_query_rows;
 pub mod random;
 pub use random::test_random;
+pub mod schema;
+pub use schema::test_schema;
 pub mod serialize;
 pub use serialize::test_serialize;
 pub mod single_pk;
 pub use single_pk::test_single_pk;
+pub mod soft_delete;
+pub use soft_delete::test_soft_delete;
+pub mod statement_cache;
+pub use statement_cache::test_statement_cache;
+pub mod timestamp;
+pub use timestamp::test_timestamp;
+pub mod transaction;
+pub use transaction::test_transaction;
+pub mod upsert;
+pub use upsert::test_upsert;
+pub mod validation;
+pub use validation::test_validation;
+pub mod verify_schema;
+pub use verify_schema::test_verify_schema;
 pub mod csv;
 pub use csv::test_csv;
@@ -0,0 +1,89 @@
+use super::models::{document::Document, empty_tables};
+use vicocomo::{is_error, ActiveRecord, DatabaseIf, DbValue};
+
+pub fn test_lock_version(db: DatabaseIf) {
+    empty_tables(db.clone(), &["documents"]);
+    let mut doc = Document {
+        id: 1,
+        title: "original".to_string(),
+        lock_version: 0,
+    };
+    assert!(doc.insert(db.clone()).is_ok());
+
+    println!("\noptimistic locking --------------------------------------\n");
+
+    println!("update() increments the version ..");
+    let mut first = Document::find(db.clone(), &1).unwrap();
+    let mut second = first.clone();
+    first.title = "first".to_string();
+    assert!(first.update(db.clone()).is_ok());
+    assert_eq!(first.lock_version, 1);
+    assert_eq!(Document::find(db.clone(), &1), Some(first.clone()));
+    println!("    OK");
+    println!("error updating stale object ..");
+    second.title = "second".to_string();
+    let res = second.update(db.clone());
+    assert!(is_error!(
+        res.as_ref().unwrap_err(),
+        Model(StaleObject, "Document", None),
+    ));
+    assert_eq!(second.lock_version, 0);
+    let res = second.update_columns(
+        db.clone(),
+        &[("title", DbValue::Text("second".to_string()))],
+    );
+    assert!(is_error!(res.unwrap_err(), Model(StaleObject)));
+    assert_eq!(Document::find(db.clone(), &1), Some(first.clone()));
+    println!("    OK");
+    println!("update_columns() increments the version ..");
+    assert!(first
+        .update_columns(
+            db.clone(),
+            &[("title", DbValue::Text("columns".to_string()))],
+        )
+        .is_ok());
+    assert_eq!(first.title, "columns");
+    assert_eq!(first.lock_version, 2);
+    println!("    OK");
+    println!("error updating non-existing object is not stale ..");
+    let mut missing = first.clone();
+    missing.id = 2;
+    assert!(is_error!(
+        missing.update(db.clone()).unwrap_err(),
+        Model(CannotSave, "Document", Some("not-found".to_string())),
+    ));
+    println!("    OK");
+    println!("error deleting stale object ..");
+    assert!(is_error!(
+        second.clone().delete(db.clone()).unwrap_err(),
+        Model(StaleObject, "Document", None),
+    ));
+    assert!(Document::find(db.clone(), &1).is_some());
+    assert!(is_error!(
+        missing.delete(db.clone()).unwrap_err(),
+        Model(CannotDelete, "Document", Some("not-found".to_string())),
+    ));
+    println!("    OK");
+    println!("error upserting stale object ..");
+    let mut upserted = Document {
+        id: 1,
+        title: "columns".to_string(),
+        lock_version: 0,
+    };
+    assert!(is_error!(
+        upserted.upsert_by_title(db.clone()).unwrap_err(),
+        Model(StaleObject, "Document", None),
+    ));
+    assert_eq!(Document::find(db.clone(), &1), Some(first.clone()));
+    println!("    OK");
+    println!("upsert increments the version ..");
+    upserted.lock_version = 2;
+    assert!(upserted.upsert_by_title(db.clone()).is_ok());
+    assert_eq!(upserted.lock_version, 3);
+    println!("    OK");
+    println!("deleting fresh object ..");
+    assert!(upserted.delete(db.clone()).is_ok());
+    assert!(Document::find(db.clone(), &1).is_none());
+    println!("    OK");
+    empty_tables(db.clone(), &["documents"]);
+}
@@ -0,0 +1,105 @@
+use vicocomo::DatabaseIf;
+
+pub fn test_migration(db: DatabaseIf) {
+    use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+    use vicocomo::migration::{
+        applied, migrate, migrate_down, pending, MIGRATIONS_TABLE,
+    };
+    use vicocomo::{DbType, Error};
+
+    let dir = std::env::temp_dir()
+        .join(format!("vicocomo_migrations_{}", std::process::id()));
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    let _ = db.clone().exec("DROP TABLE IF EXISTS mig_widgets", &[]);
+    let _ = db
+        .clone()
+        .exec(&format!("DROP TABLE IF EXISTS {MIGRATIONS_TABLE}"), &[]);
+    let widget_count = |db: DatabaseIf| {
+        db.query("SELECT COUNT(*) FROM mig_widgets", &[], &[DbType::Int])
+            .map(|rows| rows[0][0].to_string())
+    };
+
+    println!("\nmigrations ----------------------------------------------\n");
+
+    write(
+        dir.join("0001_create_widgets.sql"),
+        "CREATE TABLE mig_widgets (id BIGINT PRIMARY KEY, name TEXT);",
+    )
+    .unwrap();
+    write(
+        dir.join("0001_create_widgets.down.sql"),
+        "DROP TABLE mig_widgets;",
+    )
+    .unwrap();
+    write(
+        dir.join("0002_insert_widgets.up.sql"),
+        "INSERT INTO mig_widgets (id, name) VALUES (1, 'a');\n\
+        INSERT INTO mig_widgets (id, name) VALUES (2, 'c');\n",
+    )
+    .unwrap();
+    write(
+        dir.join("0002_insert_widgets.down.sql"),
+        "DELETE FROM mig_widgets;",
+    )
+    .unwrap();
+    write(dir.join("README"), "not a migration").unwrap();
+
+    println!("applying pending migrations ..");
+    assert_eq!(pending(db.clone(), &dir).unwrap().len(), 2);
+    assert_eq!(migrate(db.clone(), &dir), Ok(vec![1, 2]));
+    assert_eq!(applied(db.clone()), Ok(vec![1, 2]));
+    assert_eq!(widget_count(db.clone()), Ok("2".to_string()));
+    println!("    OK");
+    println!("applying again is a no-op ..");
+    assert_eq!(migrate(db.clone(), &dir), Ok(vec![]));
+    assert!(pending(db.clone(), &dir).unwrap().is_empty());
+    assert_eq!(widget_count(db.clone()), Ok("2".to_string()));
+    println!("    OK");
+    println!("a failing migration is rolled back ..");
+    write(
+        dir.join("0003_fail.sql"),
+        "INSERT INTO mig_widgets (id, name) VALUES (3, 'd');\n\
+        INSERT INTO no_such_table (id) VALUES (3);\n",
+    )
+    .unwrap();
+    let res = migrate(db.clone(), &dir);
+    match res {
+        Err(Error::Database(ref e)) => {
+            assert!(e.text.starts_with("migration 3 fail: "), "{:?}", res)
+        }
+        _ => panic!("expected Error::Database, got {:?}", res),
+    }
+    assert_eq!(applied(db.clone()), Ok(vec![1, 2]));
+    assert_eq!(widget_count(db.clone()), Ok("2".to_string()));
+    println!("    OK");
+    println!("error reverting a migration without down file ..");
+    remove_file(dir.join("0003_fail.sql")).unwrap();
+    write(dir.join("0003_noop.sql"), "").unwrap();
+    assert_eq!(migrate(db.clone(), &dir), Ok(vec![3]));
+    assert!(migrate_down(db.clone(), &dir, 1).is_err());
+    assert_eq!(applied(db.clone()), Ok(vec![1, 2, 3]));
+    write(dir.join("0003_noop.down.sql"), "").unwrap();
+    println!("    OK");
+    println!("error in migration directory ..");
+    write(dir.join("0004_orphan.down.sql"), "").unwrap();
+    assert!(migrate(db.clone(), &dir).is_err());
+    remove_file(dir.join("0004_orphan.down.sql")).unwrap();
+    write(dir.join("0003_duplicate.sql"), "").unwrap();
+    assert!(migrate(db.clone(), &dir).is_err());
+    remove_file(dir.join("0003_duplicate.sql")).unwrap();
+    println!("    OK");
+    println!("reverting migrations ..");
+    assert_eq!(migrate_down(db.clone(), &dir, 1), Ok(vec![3, 2]));
+    assert_eq!(applied(db.clone()), Ok(vec![1]));
+    assert_eq!(widget_count(db.clone()), Ok("0".to_string()));
+    assert_eq!(migrate_down(db.clone(), &dir, 0), Ok(vec![1]));
+    assert_eq!(applied(db.clone()), Ok(vec![]));
+    assert!(widget_count(db.clone()).is_err());
+    println!("    OK");
+
+    let _ = db
+        .clone()
+        .exec(&format!("DROP TABLE {MIGRATIONS_TABLE}"), &[]);
+    let _ = remove_dir_all(&dir);
+}
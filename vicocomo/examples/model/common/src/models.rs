@@ -1,10 +1,10 @@
-pub use backup_and_restore::{backup, restore, Join};
+pub use backup_and_restore::{backup, backup_to, restore, Join};
 use chrono::{DateTime, NaiveDate, Utc};
 use vicocomo::{ActiveRecord, DatabaseIf};
 pub use {
-    default_parent::DefaultParent, multi_pk::MultiPk, no_pk::NoPk,
-    other_parent::NonstandardParent, random::Random, serialize::Serialize,
-    single_pk::SinglePk,
+    attachment::Attachment, default_parent::DefaultParent, multi_pk::MultiPk,
+    native::Native, no_pk::NoPk, other_parent::NonstandardParent,
+    random::Random, score::Score, serialize::Serialize, single_pk::SinglePk,
 };
 
 // belongs-to associations:
@@ -35,8 +35,10 @@ pub mod backup_and_restore {
 
     define_backup_and_restore! {
         Join,
+        super::Attachment,
         super::DefaultParent,
         super::MultiPk,
+        super::Native,
         super::NoPk,
         super::NonstandardParent,
         super::Random,
@@ -45,6 +47,148 @@ pub mod backup_and_restore {
     }
 }
 
+pub mod account {
+    use super::audit::Audit;
+    use vicocomo::{ActiveRecord, DatabaseIf, Error, ModelError};
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    #[vicocomo_after_delete]
+    #[vicocomo_after_save]
+    #[vicocomo_before_delete]
+    #[vicocomo_delete_batch_hooks]
+    #[vicocomo_validate]
+    pub struct Account {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_required]
+        pub name: String,
+        pub balance: i64,
+    }
+
+    impl Account {
+        // Fails for the name "unaudited", rolling back the saving or
+        // deleting.
+        fn audit(&self, db: DatabaseIf, what: &str) -> Result<(), Error> {
+            if self.name == "unaudited" {
+                return Err(vicocomo::model_error!(
+                    CannotSave,
+                    "Account": "unaudited",
+                ));
+            }
+            Audit {
+                account_id: self.id,
+                what: format!("{} {}", what, self.balance),
+            }
+            .insert(db)
+        }
+    }
+
+    impl vicocomo::AfterDelete for Account {
+        fn after_delete(&mut self, db: DatabaseIf) -> Result<(), Error> {
+            self.audit(db, "deleted")
+        }
+    }
+
+    impl vicocomo::AfterSave for Account {
+        fn after_save(&mut self, db: DatabaseIf) -> Result<(), Error> {
+            self.audit(db, "saved")
+        }
+    }
+
+    impl vicocomo::BeforeDelete for Account {
+        fn before_delete(&mut self, _db: DatabaseIf) -> Result<(), Error> {
+            if self.balance == 0 {
+                Ok(())
+            } else {
+                Err(vicocomo::model_error!(
+                    CannotDelete,
+                    "Account": "",
+                    "balance": ["not-zero"],
+                ))
+            }
+        }
+    }
+
+    impl vicocomo::Validate for Account {
+        fn validate(&self, _db: DatabaseIf, errors: &mut ModelError) {
+            if self.balance < 0 {
+                errors.add_field_error("balance", "negative");
+            }
+            if self.name.len() > 10 {
+                errors.add_field_error("name", "too-long");
+            }
+        }
+    }
+}
+
+pub mod article {
+    use vicocomo::Original;
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Article {
+        #[vicocomo_primary]
+        pub id: u32,
+        pub title: String,
+        pub body: String,
+        #[vicocomo_optional]
+        pub views: Option<i64>,
+        #[vicocomo_lock_version]
+        pub lock_version: i64,
+        #[vicocomo_original]
+        pub original: Original,
+    }
+}
+
+pub mod attachment {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Attachment {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_required]
+        pub data: Vec<u8>,
+        pub thumbnail: Option<Vec<u8>>,
+    }
+}
+
+pub mod audit {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Audit {
+        #[vicocomo_order_by(0)]
+        pub account_id: u32,
+        #[vicocomo_order_by(1)]
+        pub what: String,
+    }
+}
+
+pub mod contact {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Contact {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_validate(length(min = 2, max = 10))]
+        pub name: String,
+        #[vicocomo_validate(email)]
+        pub email: Option<String>,
+        #[vicocomo_validate(regex = "^[A-Z]{3}$", one_of = ["ABC", "XYZ"])]
+        pub code: String,
+        #[vicocomo_validate(range(min = 0, max = 150))]
+        pub age: Option<i32>,
+        #[vicocomo_validate(range(min = -1, max = 1.5))]
+        pub score: f32,
+        #[vicocomo_optional]
+        #[vicocomo_validate(custom = "crate::models::contact::no_digits")]
+        pub note: Option<Option<String>>,
+    }
+
+    pub fn no_digits(note: &str) -> Result<(), String> {
+        if note.chars().any(|c| c.is_ascii_digit()) {
+            Err("has-digits".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
 pub mod default_parent {
     #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
     #[vicocomo_has_many(remote_type = "MultiPk", on_delete = "cascade")]
@@ -57,6 +201,50 @@ pub mod default_parent {
     }
 }
 
+pub mod document {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Document {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_unique = "title"]
+        pub title: String,
+        #[vicocomo_lock_version]
+        pub lock_version: i64,
+    }
+}
+
+pub mod folder {
+    use chrono::NaiveDateTime;
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    #[vicocomo_has_many(remote_type = "crate::models::label::Label")]
+    #[vicocomo_has_many(
+        on_delete = "cascade",
+        remote_type = "crate::models::note::Note"
+    )]
+    pub struct Folder {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_unique = "name"]
+        pub name: String,
+        #[vicocomo_soft_delete]
+        pub deleted_at: Option<NaiveDateTime>,
+    }
+}
+
+pub mod label {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Label {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_belongs_to(
+            remote_pk = "id mandatory",
+            remote_type = "crate::models::folder::Folder"
+        )]
+        pub folder_id: u32,
+    }
+}
+
 pub mod multi_pk {
     use chrono::{NaiveDate, NaiveDateTime};
 
@@ -115,6 +303,29 @@ pub mod multi_pk {
     }
 }
 
+pub mod native {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Native {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_native]
+        pub flag: bool,
+        #[vicocomo_native]
+        pub flag_nul: Option<bool>,
+        #[vicocomo_native]
+        pub date: NaiveDate,
+        #[vicocomo_native]
+        pub date_time: NaiveDateTime,
+        #[vicocomo_native]
+        pub time_nul: Option<NaiveTime>,
+        #[vicocomo_native]
+        #[vicocomo_optional]
+        pub date_opt: Option<NaiveDate>,
+    }
+}
+
 pub mod no_pk {
     #[derive(vicocomo::ActiveRecord, PartialEq, Clone, Debug)]
     pub struct NoPk {
@@ -123,6 +334,26 @@ pub mod no_pk {
     }
 }
 
+pub mod note {
+    use chrono::{DateTime, Utc};
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Note {
+        #[vicocomo_primary]
+        pub id: u32,
+        #[vicocomo_belongs_to(
+            remote_pk = "id mandatory",
+            remote_type = "crate::models::folder::Folder"
+        )]
+        pub folder_id: u32,
+        #[vicocomo_order_by(1)]
+        pub title: String,
+        #[vicocomo_native]
+        #[vicocomo_soft_delete]
+        pub deleted_at: Option<DateTime<Utc>>,
+    }
+}
+
 pub mod other_parent {
     #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
     #[vicocomo_has_many(
@@ -161,6 +392,16 @@ pub mod random {
     }
 }
 
+pub mod score {
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Score {
+        #[vicocomo_primary]
+        pub id: u32,
+        pub name: Option<String>,
+        pub points: i32,
+    }
+}
+
 pub mod serialize {
     use serde::{Deserialize, Serialize as SerdeSerialize};
     #[derive(Clone, Debug, Deserialize, PartialEq, SerdeSerialize)]
@@ -254,6 +495,36 @@ pub mod single_pk {
     }
 }
 
+pub mod rank {
+    // The sole purpose of this model is to query single_pks through renamed
+    // columns.
+    #[derive(vicocomo::ActiveRecord, Clone, Debug)]
+    #[vicocomo_readonly]
+    #[vicocomo_table_name = "single_pks"]
+    pub struct Rank {
+        #[vicocomo_column = "name"]
+        pub label: String,
+        #[vicocomo_column = "un2"]
+        pub rank: i32,
+    }
+}
+
+pub mod stamp {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    #[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+    pub struct Stamp {
+        #[vicocomo_primary]
+        pub id: u32,
+        pub name: String,
+        #[vicocomo_created_at]
+        pub created_at: NaiveDateTime,
+        #[vicocomo_native]
+        #[vicocomo_updated_at]
+        pub updated_at: Option<DateTime<Utc>>,
+    }
+}
+
 pub mod view {
     #[derive(vicocomo::ActiveRecord, Clone, Debug)]
     #[vicocomo_readonly]
@@ -270,6 +541,17 @@ pub fn empty_db(db: DatabaseIf) {
     let _ = db.clone().exec("DELETE FROM default_parents", &[]);
     let _ = db.clone().exec("DELETE FROM nonstandard_parents", &[]);
     let _ = db.clone().exec("DELETE FROM randoms", &[]);
+    let _ = db.clone().exec("DELETE FROM scores", &[]);
+}
+
+// Empty the tables in the given order, e.g. children before parents.
+pub fn empty_tables(db: DatabaseIf, tables: &[&str]) {
+    for table in tables {
+        assert!(db
+            .clone()
+            .exec(&format!("DELETE FROM {}", table), &[])
+            .is_ok());
+    }
 }
 
 pub fn find_or_insert_default_parent(
@@ -419,6 +701,11 @@ pub fn setup(
     NonstandardParent,
 ) {
     assert!(db.clone().exec("DROP VIEW IF EXISTS views", &[]).is_ok());
+    assert!(db
+        .clone()
+        .exec("DROP TABLE IF EXISTS attachments", &[])
+        .is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS natives", &[]).is_ok());
     assert!(db.clone().exec("DROP TABLE IF EXISTS joins", &[]).is_ok());
     assert!(db
         .clone()
@@ -438,6 +725,56 @@ pub fn setup(
         .exec("DROP TABLE IF EXISTS single_pks", &[])
         .is_ok());
     assert!(db.clone().exec("DROP TABLE IF EXISTS randoms", &[]).is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS scores", &[]).is_ok());
+    assert!(db
+        .clone()
+        .exec("DROP TABLE IF EXISTS documents", &[])
+        .is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS stamps", &[]).is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS notes", &[]).is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS labels", &[]).is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS folders", &[]).is_ok());
+    assert!(db
+        .clone()
+        .exec("DROP TABLE IF EXISTS accounts", &[])
+        .is_ok());
+    assert!(db.clone().exec("DROP TABLE IF EXISTS audits", &[]).is_ok());
+    assert!(db
+        .clone()
+        .exec("DROP TABLE IF EXISTS contacts", &[])
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec("DROP TABLE IF EXISTS articles", &[])
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE attachments
+            (   id         BIGINT  PRIMARY KEY
+            ,   data       BYTEA   NOT NULL
+            ,   thumbnail  BYTEA
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE natives
+            (   id         BIGINT     PRIMARY KEY
+            ,   flag       BOOLEAN    NOT NULL
+            ,   flag_nul   BOOLEAN
+            ,   date       DATE       NOT NULL
+            ,   date_time  TIMESTAMP  NOT NULL
+            ,   time_nul   TIME
+            ,   date_opt   DATE       NOT NULL  DEFAULT '2000-01-01'
+            )",
+            &[],
+        )
+        .is_ok());
     assert!(db
         .clone()
         .exec(
@@ -459,6 +796,134 @@ pub fn setup(
             &[],
         )
         .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE scores
+            (   id      BIGINT  PRIMARY KEY
+            ,   name    TEXT    NOT NULL -- sic!
+            ,   points  BIGINT  NOT NULL
+                CONSTRAINT scores_points_check CHECK (points >= 0)
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE documents
+            (   id            BIGINT  PRIMARY KEY
+            ,   title         TEXT    NOT NULL  UNIQUE
+            ,   lock_version  BIGINT  NOT NULL  DEFAULT 0
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE stamps
+            (   id          BIGINT     PRIMARY KEY
+            ,   name        TEXT       NOT NULL
+            ,   created_at  BIGINT     NOT NULL
+            ,   updated_at  TIMESTAMP
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE folders
+            (   id          BIGINT  PRIMARY KEY
+            ,   name        TEXT    NOT NULL  UNIQUE
+            ,   deleted_at  BIGINT
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE labels
+            (   id         BIGINT  PRIMARY KEY
+            ,   folder_id  BIGINT  NOT NULL  REFERENCES folders
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE notes
+            (   id          BIGINT     PRIMARY KEY
+            ,   folder_id   BIGINT     NOT NULL
+                    REFERENCES folders ON DELETE CASCADE
+            ,   title       TEXT       NOT NULL
+            ,   deleted_at  TIMESTAMP
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE accounts
+            (   id       BIGINT  PRIMARY KEY
+            ,   name     TEXT    NOT NULL
+            ,   balance  BIGINT  NOT NULL
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE audits
+            (   account_id  BIGINT  NOT NULL
+            ,   what        TEXT    NOT NULL
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE contacts
+            (   id     BIGINT     PRIMARY KEY
+            ,   name   TEXT       NOT NULL
+            ,   email  TEXT
+            ,   code   TEXT       NOT NULL
+            ,   age    BIGINT
+            ,   score  FLOAT(53)  NOT NULL
+            ,   note   TEXT
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE articles
+            (   id            BIGINT  PRIMARY KEY
+            ,   title         TEXT    NOT NULL
+            ,   body          TEXT    NOT NULL
+            ,   views         BIGINT  NOT NULL  DEFAULT 0
+            ,   lock_version  BIGINT  NOT NULL  DEFAULT 0
+            )",
+            &[],
+        )
+        .is_ok());
     assert!(db
         .clone()
         .exec("DROP TABLE IF EXISTS serializes", &[])
@@ -0,0 +1,113 @@
+use vicocomo::DatabaseIf;
+
+pub fn test_native(db: DatabaseIf) {
+    use super::models::native::Native;
+    use chrono::{NaiveDate, NaiveTime};
+    use vicocomo::{ActiveRecord, DbType, DbValue};
+
+    let _ = db.clone().exec("DELETE FROM natives", &[]);
+
+    println!("\nnative date, time, and boolean values ------------------\n");
+
+    let leap = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    let mut n1 = Native {
+        id: 1,
+        flag: true,
+        flag_nul: None,
+        date: leap,
+        date_time: leap.and_hms_milli_opt(12, 34, 56, 789).unwrap(),
+        time_nul: None,
+        date_opt: None,
+    };
+    println!("inserting {n1:?} ..");
+    let res = n1.insert(db.clone());
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(n1.date_opt, NaiveDate::from_ymd_opt(2000, 1, 1));
+    assert_eq!(Native::find(db.clone(), &1), Some(n1.clone()));
+    println!("    OK");
+    println!("updating {n1:?} ..");
+    n1.time_nul = NaiveTime::from_hms_opt(23, 59, 59);
+    assert!(n1.update(db.clone()).is_ok());
+    assert_eq!(Native::find(db.clone(), &1), Some(n1.clone()));
+    println!("    OK");
+    let n2 = Native {
+        id: 2,
+        flag: false,
+        flag_nul: Some(true),
+        date: NaiveDate::from_ymd_opt(1999, 12, 31).unwrap(),
+        date_time: NaiveDate::from_ymd_opt(2000, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+        time_nul: None,
+        date_opt: NaiveDate::from_ymd_opt(2024, 3, 1),
+    };
+    println!("inserting {n2:?} ..");
+    let res = n2.clone().insert(db.clone());
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(Native::find(db.clone(), &2), Some(n2.clone()));
+    println!("    OK");
+
+    println!("values are stored as native types ..");
+    let res = db.clone().query(
+        "SELECT id FROM natives WHERE flag AND date = $1 AND date_time > $2",
+        &[
+            DbValue::Date(leap),
+            DbValue::DateTime(leap.and_hms_opt(12, 0, 0).unwrap()),
+        ],
+        &[DbType::Int],
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    let rows = res.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert!(matches!(rows[0][..], [DbValue::Int(1)]), "{:?}", rows);
+    println!("    OK");
+
+    println!("native values to CSV ..");
+    let csv = "id,flag,flag_nul,date,date_time,time_nul,date_opt\r\n\
+        1,true,,\"2024-02-29\",\"2024-02-29 12:34:56.789\",\"23:59:59\",\
+        \"2000-01-01\"\r\n\
+        2,false,true,\"1999-12-31\",\"2000-01-01 00:00:00\",,\
+        \"2024-03-01\"\r\n";
+    let res = Native::try_to_csv(db.clone(), None);
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(res.unwrap(), ("natives".to_string(), csv.to_string()));
+    println!("    OK");
+    println!("native values from CSV ..");
+    let res = Native::try_from_csv(
+        db.clone(),
+        "id,flag,flag_nul,date,date_time,time_nul\r\n\
+        3,T,0,2024-02-29,2024-02-29 12:34:56.789,12:00:00\r\n",
+        None,
+    );
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        Native::load(db.clone()).unwrap(),
+        vec![Native {
+            id: 3,
+            flag: true,
+            flag_nul: Some(false),
+            time_nul: NaiveTime::from_hms_opt(12, 0, 0),
+            ..n1.clone()
+        }],
+    );
+    let res = Native::try_from_csv(db.clone(), csv, None);
+    assert!(res.is_ok(), "{:?}", res);
+    assert_eq!(
+        Native::load(db.clone()).unwrap(),
+        vec![n1.clone(), n2.clone()],
+    );
+    println!("    OK");
+    println!("error reading invalid boolean from CSV ..");
+    assert!(Native::try_from_csv(
+        db.clone(),
+        "id,flag,date,date_time\r\n3,yes,2024-02-29,2024-02-29 12:00:00\r\n",
+        None
+    )
+    .is_err());
+    assert_eq!(
+        Native::load(db.clone()).unwrap(),
+        vec![n1.clone(), n2.clone()],
+    );
+    println!("    OK");
+}
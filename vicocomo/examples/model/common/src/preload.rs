@@ -0,0 +1,99 @@
+use super::models::{
+    find_or_insert_default_parent, reset_db, reset_many_to_many,
+    DefaultParent, MultiPk, NonstandardParent,
+};
+use vicocomo::{ActiveRecord, DatabaseIf};
+
+pub fn test_preload(db: DatabaseIf) {
+    println!("\neager loading of associations ---------------------------\n");
+
+    let (m, m2, dp, bp, np) = reset_db(db.clone());
+    let filler = find_or_insert_default_parent(db.clone(), "default filler");
+    let dp_id = dp.id.unwrap();
+    let filler_id = filler.id.unwrap();
+    let mut orphan = m.clone();
+    orphan.id2 = 3;
+    orphan.default_parent_id = None;
+    assert!(orphan.save(db.clone()).is_ok());
+    println!("belongs-to preload ..");
+    let parents = MultiPk::preload_default_parent(
+        db.clone(),
+        &[m.clone(), m2.clone(), orphan.clone()],
+    )
+    .unwrap();
+    assert_eq!(parents.len(), 1);
+    assert_eq!(parents.get(&dp_id), Some(&dp));
+    assert_eq!(
+        parents.get(&m.default_parent_id.unwrap()),
+        m.default_parent(db.clone()).as_ref(),
+    );
+    let bonus =
+        MultiPk::preload_bonus_parent(db.clone(), &[m.clone(), m2.clone()])
+            .unwrap();
+    assert_eq!(bonus.len(), 1);
+    assert_eq!(bonus.get(&bp.pk), Some(&bp));
+    assert!(MultiPk::preload_default_parent(db.clone(), &[])
+        .unwrap()
+        .is_empty());
+    println!("    OK");
+    println!("belongs-to load grouped ..");
+    let children = MultiPk::load_grouped_by_default_parent(
+        db.clone(),
+        &[dp.clone(), filler.clone()],
+    )
+    .unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!(
+        children.get(&dp_id).map(|c| c.len()),
+        Some(m.clone().default_parent_siblings(db.clone()).unwrap().len()),
+    );
+    assert!(children.get(&dp_id).unwrap().contains(&m2));
+    assert!(!children.get(&dp_id).unwrap().contains(&orphan));
+    assert_eq!(children.get(&filler_id), Some(&Vec::new()));
+    let children = MultiPk::load_grouped_by_nonstandard_parent(
+        db.clone(),
+        &[np.clone()],
+    )
+    .unwrap();
+    assert_eq!(children.get(&np.pk), Some(&Vec::new()));
+    println!("    OK");
+    println!("one-to-many preload ..");
+    let children =
+        DefaultParent::preload_multi_pks(db.clone(), &[dp.clone(), filler])
+            .unwrap();
+    assert_eq!(
+        children.get(&dp_id),
+        Some(&dp.multi_pks(db.clone(), None).unwrap()),
+    );
+    assert_eq!(children.get(&filler_id), Some(&Vec::new()));
+    let children =
+        NonstandardParent::preload_bonus_childs(db.clone(), &[bp.clone()])
+            .unwrap();
+    assert_eq!(
+        children.get(&bp.pk),
+        Some(&bp.bonus_childs(db.clone(), None).unwrap()),
+    );
+    println!("    OK");
+    println!("many-to-many preload ..");
+    let (dp, pa, pb, sa, sb) = reset_many_to_many(db.clone());
+    assert!(pa.connect_to_single_pk(db.clone(), &sa).is_ok());
+    assert!(pa.connect_to_single_pk(db.clone(), &sb).is_ok());
+    assert!(pb.connect_to_single_pk(db.clone(), &sb).is_ok());
+    let children = DefaultParent::preload_single_pks(
+        db.clone(),
+        &[pa.clone(), pb.clone(), dp.clone()],
+    )
+    .unwrap();
+    assert_eq!(children.len(), 3);
+    assert_eq!(
+        children.get(&pa.id.unwrap()),
+        Some(&pa.single_pks(db.clone(), None).unwrap()),
+    );
+    assert_eq!(children.get(&pa.id.unwrap()), Some(&vec![sb.clone(), sa]));
+    assert_eq!(children.get(&pb.id.unwrap()), Some(&vec![sb]));
+    assert_eq!(children.get(&dp.id.unwrap()), Some(&Vec::new()));
+    assert!(DefaultParent::preload_single_pks(db.clone(), &[])
+        .unwrap()
+        .is_empty());
+    println!("    OK");
+}
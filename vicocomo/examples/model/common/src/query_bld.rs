@@ -0,0 +1,187 @@
+use super::models::single_pk::SinglePk;
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, QueryBld};
+
+pub fn test_query_bld(db: DatabaseIf) {
+    let _ = db.clone().exec("DELETE FROM single_pks", &[]);
+    let mut data = vec![
+        SinglePk {
+            id: None,
+            name: Some(String::from("Alpha")),
+            data: Some(1.5),
+            opt: Some(1),
+            un2: 1,
+        },
+        SinglePk {
+            id: None,
+            name: Some(String::from("beta")),
+            data: None,
+            opt: Some(2),
+            un2: 2,
+        },
+        SinglePk {
+            id: None,
+            name: Some(String::from("gamma")),
+            data: Some(2.5),
+            opt: Some(3),
+            un2: 3,
+        },
+    ];
+    assert!(SinglePk::insert_batch(db.clone(), &mut data[..]).is_ok());
+    let names = |query: &vicocomo::Query| {
+        SinglePk::query(db.clone(), query)
+            .unwrap()
+            .iter()
+            .map(|s| s.name.clone().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    println!("\nquery builder operators ---------------------------------\n");
+
+    println!("in_list() and not_in() ..");
+    let mut query = QueryBld::new()
+        .col("un2")
+        .in_list(&[DbValue::Int(1), DbValue::Int(3)])
+        .order("un2")
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("un2 IN ($1, $2)"));
+    assert_eq!(names(&query), vec!["Alpha", "gamma"]);
+    query.set_values(&[DbValue::Int(2), DbValue::Int(4)]);
+    assert_eq!(names(&query), vec!["beta"]);
+    let query = QueryBld::new()
+        .col("opt")
+        .eq(Some(&DbValue::Int(1)))
+        .or("un2")
+        .not_in(&[DbValue::Int(1), DbValue::Int(2)])
+        .order("un2")
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("opt = $1 OR un2 NOT IN ($2, $3)"));
+    assert_eq!(names(&query), vec!["Alpha", "gamma"]);
+    let query = QueryBld::new().col("un2").in_list(&[]).query().unwrap();
+    assert!(names(&query).is_empty());
+    let query = QueryBld::new().col("un2").not_in(&[]).query().unwrap();
+    assert_eq!(names(&query).len(), 3);
+    println!("    OK");
+    println!("like() and ilike() ..");
+    let query = QueryBld::new()
+        .col("name")
+        .like(Some(&DbValue::Text("%ta".to_string())))
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("name LIKE $1"));
+    assert_eq!(names(&query), vec!["beta"]);
+    let query = QueryBld::new()
+        .col("name")
+        .ilike(Some(&DbValue::Text("a%".to_string())))
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("LOWER(name) LIKE LOWER($1)"));
+    assert_eq!(names(&query), vec!["Alpha"]);
+    println!("    OK");
+    println!("is_null() and is_not_null() ..");
+    let query = QueryBld::new().col("data").is_null().query().unwrap();
+    assert_eq!(query.filter(), Some("data IS NULL"));
+    assert_eq!(names(&query), vec!["beta"]);
+    let query = QueryBld::new()
+        .col("data")
+        .is_not_null()
+        .and("un2")
+        .gt(Some(&DbValue::Int(1)))
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("data IS NOT NULL AND un2 > $1"));
+    assert_eq!(names(&query), vec!["gamma"]);
+    println!("    OK");
+    println!("between() ..");
+    let mut query = QueryBld::new()
+        .col("un2")
+        .between(None, None)
+        .order("un2")
+        .query()
+        .unwrap();
+    assert_eq!(query.filter(), Some("un2 BETWEEN $1 AND $2"));
+    query.set_values(&[DbValue::Int(2), DbValue::Int(3)]);
+    assert_eq!(names(&query), vec!["beta", "gamma"]);
+    println!("    OK");
+    println!("groups ..");
+    let query = QueryBld::new()
+        .begin_group()
+        .col("un2")
+        .eq(Some(&DbValue::Int(1)))
+        .or("data")
+        .is_null()
+        .end_group()
+        .and_group()
+        .col("opt")
+        .ge(Some(&DbValue::Int(2)))
+        .or_group()
+        .begin_group()
+        .col("un2")
+        .lt(Some(&DbValue::Int(0)))
+        .end_group()
+        .end_group()
+        .end_group()
+        .query()
+        .unwrap();
+    assert_eq!(
+        query.filter(),
+        Some("(un2 = $1 OR data IS NULL) AND (opt >= $2 OR ((un2 < $3)))"),
+    );
+    assert_eq!(names(&query), vec!["beta"]);
+    let query = query
+        .builder()
+        .filter("name <> $1", &[Some(DbValue::Text("beta".to_string()))])
+        .query()
+        .unwrap();
+    assert_eq!(
+        query.filter(),
+        Some(
+            "((un2 = $1 OR data IS NULL) AND (opt >= $2 OR ((un2 < $3)))) \
+            AND name <> $4"
+        ),
+    );
+    assert!(names(&query).is_empty());
+    println!("    OK");
+    println!("invalid sequences ..");
+    let eq_1 = Some(&DbValue::Int(1));
+    assert!(QueryBld::new().is_null().query().is_none());
+    assert!(QueryBld::new().col("un2").query().is_none());
+    assert!(QueryBld::new().col("un2").end_group().query().is_none());
+    assert!(QueryBld::new().and_group().query().is_none());
+    assert!(QueryBld::new().begin_group().end_group().query().is_none());
+    assert!(QueryBld::new()
+        .begin_group()
+        .col("un2")
+        .eq(eq_1)
+        .query()
+        .is_none());
+    assert!(QueryBld::new()
+        .col("un2")
+        .eq(eq_1)
+        .begin_group()
+        .query()
+        .is_none());
+    assert!(QueryBld::new()
+        .col("un2")
+        .eq(eq_1)
+        .end_group()
+        .query()
+        .is_none());
+    assert!(QueryBld::new()
+        .col("un2")
+        .eq(eq_1)
+        .or_group()
+        .end_group()
+        .query()
+        .is_none());
+    assert!(QueryBld::new()
+        .begin_group()
+        .col("un2")
+        .eq(eq_1)
+        .filter("opt = 1", &[])
+        .end_group()
+        .query()
+        .is_none());
+    println!("    OK");
+}
@@ -0,0 +1,92 @@
+use super::models::no_pk::NoPk;
+use vicocomo::{ActiveRecord, DatabaseIf, DbType, DbValue, Error, QueryBld};
+
+pub fn test_query_each(db: DatabaseIf) {
+    let mut saved = NoPk::load(db.clone()).unwrap();
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+    assert!(NoPk::insert_batch(
+        db.clone(),
+        &mut [NoPk { data: 1 }, NoPk { data: 2 }, NoPk { data: 3 }],
+    )
+    .is_ok());
+    let select = "SELECT data FROM no_pks ORDER BY data";
+
+    println!("\nstreaming query results ---------------------------------\n");
+
+    println!("query_each() ..");
+    let mut data = Vec::new();
+    let res = db.clone().query_each(select, &[], &[DbType::Int], |row| {
+        data.push(row[0].to_string());
+        Ok(())
+    });
+    assert_eq!(res, Ok(3));
+    assert_eq!(data, vec!["1", "2", "3"]);
+    println!("    OK");
+    println!("query_each() stopped by error ..");
+    let mut count = 0;
+    let res = db.clone().query_each(select, &[], &[DbType::Int], |_| {
+        count += 1;
+        Err(Error::other("stop"))
+    });
+    assert_eq!(res, Err(Error::other("stop")));
+    assert_eq!(count, 1);
+    println!("    OK");
+    println!("query_iter() ..");
+    let data = db
+        .clone()
+        .query_iter(select, &[], &[DbType::Int])
+        .map(|row| row.unwrap()[0].to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(data, vec!["1", "2", "3"]);
+    println!("    OK");
+    println!("query_iter() dropped before exhausted ..");
+    let mut iter = db.clone().query_iter(select, &[], &[DbType::Int]);
+    assert!(matches!(
+        iter.next(),
+        Some(Ok(ref row)) if row[0].to_string() == "1",
+    ));
+    drop(iter);
+    assert_eq!(NoPk::load(db.clone()).unwrap().len(), 3);
+    println!("    OK");
+    println!("query_iter() error ..");
+    let mut iter = db.clone().query_iter(
+        "SELECT data FROM no_such_table",
+        &[],
+        &[DbType::Int],
+    );
+    assert!(matches!(iter.next(), Some(Err(Error::Database(_)))));
+    assert!(iter.next().is_none());
+    println!("    OK");
+    println!("ActiveRecord::for_each() ..");
+    let mut models = Vec::new();
+    let res = NoPk::for_each(
+        db.clone(),
+        &QueryBld::new()
+            .col("data")
+            .gt(Some(&DbValue::Int(1)))
+            .query()
+            .unwrap(),
+        |obj| {
+            models.push(obj);
+            Ok(())
+        },
+    );
+    assert_eq!(res, Ok(2));
+    assert_eq!(models, vec![NoPk { data: 3 }, NoPk { data: 2 }]);
+    println!("    OK");
+    println!("ActiveRecord::try_write_csv() ..");
+    let mut csv = Vec::new();
+    assert_eq!(
+        NoPk::try_write_csv(db.clone(), None, &mut csv),
+        Ok("no_pks".to_string()),
+    );
+    assert_eq!(csv, b"data\r\n3\r\n2\r\n1\r\n");
+    assert_eq!(
+        NoPk::try_to_csv(db.clone(), None),
+        Ok(("no_pks".to_string(), String::from_utf8(csv).unwrap())),
+    );
+    println!("    OK");
+
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+    assert!(NoPk::insert_batch(db.clone(), &mut saved[..]).is_ok());
+}
@@ -0,0 +1,94 @@
+use super::models::no_pk::NoPk;
+use std::sync::Arc;
+use std::time::Duration;
+use vicocomo::{
+    ActiveRecord, DatabaseIf, DbConn, DbType, DbValue, Error, LoggingConn,
+    MemoryLog,
+};
+
+// Lets a LoggingConn wrap the connection behind db.
+struct Forward(DatabaseIf);
+
+impl DbConn for Forward {
+    fn exec(&self, sql: &str, values: &[DbValue]) -> Result<usize, Error> {
+        self.0.clone().exec(sql, values)
+    }
+
+    fn query(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+    ) -> Result<Vec<Vec<DbValue>>, Error> {
+        self.0.clone().query(sql, values, types)
+    }
+}
+
+pub fn test_query_log(db: DatabaseIf) {
+    let mut saved = NoPk::load(db.clone()).unwrap();
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+
+    println!("\nquery log -----------------------------------------------\n");
+
+    let sink = MemoryLog::default();
+    let logged = DatabaseIf::new(Arc::new(LoggingConn::new(
+        Forward(db.clone()),
+        Arc::new(sink.clone()),
+    )));
+    println!("logging exec ..");
+    assert!(NoPk { data: 17 }.insert(logged.clone()).is_ok());
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].sql.starts_with("INSERT INTO no_pks"));
+    assert_eq!(entries[0].values.len(), 1);
+    assert_eq!(entries[0].values[0].to_string(), "17");
+    assert_eq!(entries[0].rows, Some(1));
+    assert!(entries[0].error.is_none());
+    println!("    OK");
+    println!("logging query ..");
+    sink.clear();
+    assert_eq!(NoPk::load(logged.clone()).unwrap().len(), 1);
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].sql.starts_with("SELECT"));
+    assert_eq!(entries[0].rows, Some(1));
+    assert!(entries[0].to_string().contains("1 rows: SELECT"));
+    println!("    OK");
+    println!("logging error ..");
+    sink.clear();
+    assert!(logged
+        .clone()
+        .exec("SELECT * FROM no_such_table", &[])
+        .is_err());
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].rows.is_none());
+    assert!(entries[0].error.is_some());
+    println!("    OK");
+    println!("logging transaction ..");
+    sink.clear();
+    assert!(logged
+        .clone()
+        .transaction(|db| NoPk { data: 18 }.insert(db))
+        .is_ok());
+    assert_eq!(
+        sink.entries()
+            .iter()
+            .map(|e| e.sql.split(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>(),
+        vec!["BEGIN", "INSERT", "COMMIT"],
+    );
+    println!("    OK");
+    println!("slow query threshold ..");
+    let slow_sink = MemoryLog::default();
+    let slow = DatabaseIf::new(Arc::new(
+        LoggingConn::new(Forward(db.clone()), Arc::new(slow_sink.clone()))
+            .slow_query_threshold(Duration::from_secs(3600)),
+    ));
+    assert_eq!(NoPk::load(slow).unwrap().len(), 2);
+    assert!(slow_sink.entries().is_empty());
+    println!("    OK");
+
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+    assert!(NoPk::insert_batch(db.clone(), &mut saved[..]).is_ok());
+}
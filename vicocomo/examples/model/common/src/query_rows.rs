@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use vicocomo::{DatabaseIf, DbValue, Error, NativeField};
+
+pub fn test_query_rows(db: DatabaseIf) {
+    let _ = db.clone().exec("DROP TABLE IF EXISTS report_tests", &[]);
+    assert!(db
+        .clone()
+        .exec(
+            "
+            CREATE TABLE report_tests
+            (   id     BIGINT     PRIMARY KEY
+            ,   name   TEXT       NOT NULL
+            ,   score  FLOAT(53)
+            ,   flag   BOOLEAN    NOT NULL
+            ,   day    DATE
+            ,   note   TEXT
+            )",
+            &[],
+        )
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec(
+            "INSERT INTO report_tests (id, name, score, flag, day, note) \
+            VALUES (1, 'one', 1.5, TRUE, '2024-02-29', 'a note'), \
+            (2, 'two', NULL, FALSE, NULL, NULL)",
+            &[],
+        )
+        .is_ok());
+
+    println!("\nquery rows with column names ----------------------------\n");
+
+    println!("column names ..");
+    let rows = db.clone().query_rows(
+        "SELECT id, name AS who, score, flag, day, note, id * 2 AS twice \
+        FROM report_tests WHERE id >= $1 ORDER BY id",
+        &[DbValue::Int(1)],
+    );
+    assert!(rows.is_ok(), "{:?}", rows);
+    let rows = rows.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0].columns(),
+        &["id", "who", "score", "flag", "day", "note", "twice"],
+    );
+    println!("    OK");
+    println!("inferred values ..");
+    assert_eq!(rows[0].get::<i64>("id"), Ok(1));
+    assert_eq!(rows[0].get::<String>("who"), Ok("one".to_string()));
+    assert_eq!(rows[0].get::<f64>("score"), Ok(1.5));
+    assert_eq!(rows[0].get::<NativeField<bool>>("flag").unwrap().0, true);
+    assert_eq!(
+        rows[0].get::<NativeField<NaiveDate>>("day").unwrap().0,
+        NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+    );
+    assert_eq!(rows[0].get::<i64>("twice"), Ok(2));
+    assert_eq!(rows[1].get::<NativeField<bool>>("flag").unwrap().0, false);
+    println!("    OK");
+    println!("native values to plain types ..");
+    assert_eq!(rows[0].get::<bool>("flag"), Ok(true));
+    assert_eq!(rows[1].get::<bool>("flag"), Ok(false));
+    assert_eq!(
+        rows[0].get::<NaiveDate>("day"),
+        Ok(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+    );
+    assert_eq!(
+        rows[0].get::<Option<NaiveDate>>("day"),
+        Ok(NaiveDate::from_ymd_opt(2024, 2, 29)),
+    );
+    assert_eq!(rows[1].get::<Option<NaiveDate>>("day"), Ok(None));
+    println!("    OK");
+    println!("nullability ..");
+    assert_eq!(rows[0].get::<Option<f64>>("score"), Ok(Some(1.5)));
+    assert_eq!(
+        rows[0].get::<Option<String>>("note"),
+        Ok(Some("a note".to_string())),
+    );
+    assert_eq!(rows[1].get::<Option<f64>>("score"), Ok(None));
+    assert_eq!(rows[1].get::<Option<String>>("note"), Ok(None));
+    assert_eq!(rows[1].get::<Option<i64>>("note"), Ok(None));
+    assert_eq!(
+        rows[1]
+            .get::<NativeField<Option<NaiveDate>>>("day")
+            .unwrap()
+            .0,
+        None,
+    );
+    assert!(rows[1].get::<f64>("score").is_err());
+    println!("    OK");
+    println!("errors ..");
+    assert!(matches!(
+        rows[0].get::<i64>("no_such_column"),
+        Err(Error::InvalidInput(_)),
+    ));
+    assert!(matches!(
+        rows[0].get::<i64>("who"),
+        Err(Error::InvalidInput(_)),
+    ));
+    assert!(rows[0].value("who").is_some());
+    assert_eq!(rows[0].values().len(), 7);
+    println!("    OK");
+    println!("aggregates ..");
+    let rows = db.clone().query_rows(
+        "SELECT SUM(id) AS total, AVG(id) AS average FROM report_tests",
+        &[],
+    );
+    assert!(rows.is_ok(), "{:?}", rows);
+    let rows = rows.unwrap();
+    assert_eq!(rows.len(), 1);
+    // SQLite sums integers to an integer, PostgreSQL to a NUMERIC
+    assert!(
+        matches!(
+            rows[0].value("total"),
+            Some(DbValue::Int(3)) | Some(DbValue::Float(3.0)),
+        ),
+        "{:?}",
+        rows[0].value("total"),
+    );
+    assert_eq!(rows[0].get::<f64>("average"), Ok(1.5));
+    println!("    OK");
+
+    assert!(db.clone().exec("DROP TABLE report_tests", &[]).is_ok());
+}
@@ -0,0 +1,188 @@
+use super::models::{serialize::SerData, view::View};
+use super::models::{
+    Attachment, DefaultParent, MultiPk, Native, NoPk, NonstandardParent,
+    Random, Score, Serialize, SinglePk,
+};
+use vicocomo::{
+    ActiveRecord, DatabaseIf, Error, ForeignKeyAction, ForeignKeySchema,
+    SqlDialect,
+};
+
+vicocomo::define_schema! {
+    Attachment,
+    DefaultParent,
+    MultiPk,
+    Native,
+    NoPk,
+    NonstandardParent,
+    Random,
+    Score,
+    Serialize,
+    SinglePk,
+    View,
+}
+
+pub fn test_schema(db: DatabaseIf, dialect: SqlDialect) {
+    println!("\nschema from model attributes ----------------------------\n");
+
+    println!("columns and primary key ..");
+    assert_eq!(
+        Score::create_table_sql(dialect),
+        "CREATE TABLE scores (\n    \
+            id BIGINT NOT NULL,\n    \
+            name TEXT,\n    \
+            points BIGINT NOT NULL,\n    \
+            PRIMARY KEY(id)\n\
+        );\n",
+    );
+    println!("    OK");
+    println!("auto increment primary key ..");
+    assert_eq!(
+        DefaultParent::create_table_sql(dialect),
+        format!(
+            "CREATE TABLE default_parents (\n    {},\n    \
+                name TEXT NOT NULL\n\
+            );\n",
+            match dialect {
+                SqlDialect::Postgres => "id BIGSERIAL PRIMARY KEY",
+                SqlDialect::Sqlite => "id INTEGER PRIMARY KEY AUTOINCREMENT",
+            },
+        ),
+    );
+    println!("    OK");
+    println!("optional field default ..");
+    let multi = MultiPk::table_schema();
+    assert_eq!(
+        multi
+            .columns
+            .iter()
+            .find(|c| c.name == "f32_opt")
+            .and_then(|c| c.default.clone()),
+        Some("0".to_string()),
+    );
+    assert!(multi
+        .columns
+        .iter()
+        .find(|c| c.name == "f64_opt_nul")
+        .is_some_and(|c| c.default.is_none()));
+    println!("    OK");
+    println!("unique ..");
+    assert_eq!(
+        SinglePk::table_schema().uniques,
+        vec![vec!["name".to_string(), "un2".to_string()]],
+    );
+    println!("    OK");
+    println!("foreign keys ..");
+    let fk = |col: &str, remote: &str, remote_col: &str, on_delete| {
+        ForeignKeySchema {
+            table: "multi_pks".to_string(),
+            columns: vec![col.to_string()],
+            remote_table: remote.to_string(),
+            remote_columns: vec![remote_col.to_string()],
+            on_delete,
+        }
+    };
+    assert_eq!(
+        multi.foreign_keys,
+        vec![
+            fk(
+                "default_parent_id",
+                "default_parents",
+                "id",
+                ForeignKeyAction::Cascade,
+            ),
+            fk(
+                "other_parent_id",
+                "nonstandard_parents",
+                "pk",
+                ForeignKeyAction::SetNull,
+            ),
+            fk(
+                "bonus_parent",
+                "nonstandard_parents",
+                "pk",
+                ForeignKeyAction::NoAction,
+            ),
+        ],
+    );
+    assert_eq!(
+        NonstandardParent::create_table_sql(dialect),
+        "CREATE TABLE nonstandard_parents (\n    \
+            pk TEXT NOT NULL,\n    \
+            nonstandard_parent_id TEXT,\n    \
+            PRIMARY KEY(pk),\n    \
+            FOREIGN KEY(nonstandard_parent_id) \
+                REFERENCES nonstandard_parents(pk) \
+                DEFERRABLE INITIALLY DEFERRED\n\
+        );\n",
+    );
+    println!("    OK");
+    println!("join table ..");
+    let joins = DefaultParent::join_table_schemas();
+    assert_eq!(joins.len(), 1);
+    assert_eq!(
+        joins[0].create_table_sql(dialect),
+        "CREATE TABLE joins (\n    \
+            default_parent_id BIGINT NOT NULL,\n    \
+            single_pk_id BIGINT NOT NULL,\n    \
+            PRIMARY KEY(default_parent_id, single_pk_id),\n    \
+            FOREIGN KEY(default_parent_id) \
+                REFERENCES default_parents(id) ON DELETE CASCADE \
+                DEFERRABLE INITIALLY DEFERRED,\n    \
+            FOREIGN KEY(single_pk_id) \
+                REFERENCES single_pks(id) ON DELETE CASCADE \
+                DEFERRABLE INITIALLY DEFERRED\n\
+        );\n",
+    );
+    println!("    OK");
+    println!("schema_sql() ..");
+    let sql = schema_sql(dialect);
+    assert!(!sql.contains("CREATE TABLE views"));
+    assert_eq!(sql.matches("CREATE TABLE joins").count(), 1);
+    let pos = |table: &str| sql.find(&format!("CREATE TABLE {} ", table));
+    assert!(pos("default_parents") < pos("joins"));
+    assert!(pos("single_pks") < pos("joins"));
+    assert!(pos("nonstandard_parents") < pos("multi_pks"));
+    println!("    OK");
+    println!("schema_sql() is valid SQL ..");
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        db.clone().exec("DROP VIEW views", &[])?;
+        for table in [
+            "attachments",
+            "natives",
+            "joins",
+            "multi_pks",
+            "default_parents",
+            "nonstandard_parents",
+            "no_pks",
+            "single_pks",
+            "randoms",
+            "scores",
+            "serializes",
+        ] {
+            db.clone().exec(&format!("DROP TABLE {}", table), &[])?;
+        }
+        for stmt in sql.split(";\n").filter(|s| !s.trim().is_empty()) {
+            db.clone().exec(stmt, &[])?;
+        }
+        let mut ser = Serialize {
+            id: 1,
+            not_null: SerData {
+                pair: (1, 2),
+                text: "text".to_string(),
+            },
+            nullable: None,
+            optional: Some(SerData {
+                pair: (3, 4),
+                text: "optional".to_string(),
+            }),
+            opt_null: Some(None),
+        };
+        ser.insert(db.clone())?;
+        // roll back to the tables created by setup()
+        Err(Error::other("rollback"))
+    });
+    assert_eq!(res, Err(Error::other("rollback")));
+    assert!(db.clone().query("SELECT id FROM scores", &[], &[]).is_ok());
+    println!("    OK");
+}
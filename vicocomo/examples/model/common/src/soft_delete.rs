@@ -0,0 +1,216 @@
+use super::models::{empty_tables, folder::Folder, label::Label, note::Note};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::Arc;
+use vicocomo::{
+    is_error, ActiveRecord, DatabaseIf, DbValue, Error, FixedClock, QueryBld,
+};
+
+pub fn test_soft_delete(db: DatabaseIf) {
+    empty_tables(db.clone(), &["notes", "labels", "folders"]);
+    let t0 = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+    let clock = FixedClock::new(t0);
+    let db = db.with_clock(Arc::new(clock.clone()));
+    let all = QueryBld::new().query().unwrap();
+    let mut f1 = Folder {
+        id: 1,
+        name: "f1".to_string(),
+        deleted_at: None,
+    };
+    assert!(f1.insert(db.clone()).is_ok());
+    let mut f2 = Folder {
+        id: 2,
+        name: "f2".to_string(),
+        deleted_at: None,
+    };
+    assert!(f2.insert(db.clone()).is_ok());
+    let mut notes = [(1, 1, "a"), (2, 1, "b"), (3, 2, "c")]
+        .iter()
+        .map(|(id, folder_id, title)| Note {
+            id: *id,
+            folder_id: *folder_id,
+            title: title.to_string(),
+            deleted_at: None,
+        })
+        .collect::<Vec<_>>();
+    assert!(Note::insert_batch(db.clone(), &mut notes).is_ok());
+    let mut label = Label {
+        id: 1,
+        folder_id: 1,
+    };
+    assert!(label.insert(db.clone()).is_ok());
+
+    println!("\nsoft delete ---------------------------------------------\n");
+
+    println!("delete() marks the row ..");
+    assert!(notes[1].clone().delete(db.clone()).is_ok());
+    assert_eq!(Note::find(db.clone(), &2), None);
+    let deleted = Note::find_with_deleted(db.clone(), &2).unwrap();
+    assert_eq!(deleted.deleted_at, Some(t0));
+    assert_eq!(Note::load(db.clone()).unwrap().len(), 2);
+    assert_eq!(Note::load_with_deleted(db.clone()).unwrap().len(), 3);
+    println!("    OK");
+    println!("deleting again fails ..");
+    assert!(is_error!(
+        deleted.clone().delete(db.clone()).unwrap_err(),
+        Model(
+            CannotDelete,
+            "Note",
+            Some("not-found".to_string()),
+            "id",
+            []
+        ),
+    ));
+    assert!(Note::delete_batch(db.clone(), &[2]).is_err());
+    println!("    OK");
+    println!("query() excludes deleted unless with_deleted() ..");
+    assert_eq!(Note::count(db.clone(), &all), Ok(2));
+    let with_deleted = QueryBld::new().with_deleted().query().unwrap();
+    assert_eq!(Note::count(db.clone(), &with_deleted), Ok(3));
+    assert_eq!(
+        Note::query(
+            db.clone(),
+            &QueryBld::new()
+                .col("title")
+                .eq(Some(&DbValue::Text("b".to_string())))
+                .or("folder_id")
+                .eq(Some(&DbValue::Int(2)))
+                .query()
+                .unwrap(),
+        )
+        .unwrap()
+        .iter()
+        .map(|n| n.id)
+        .collect::<Vec<_>>(),
+        vec![3],
+    );
+    println!("    OK");
+    println!("association getters exclude deleted ..");
+    assert_eq!(
+        f1.notes(db.clone(), None)
+            .unwrap()
+            .iter()
+            .map(|n| n.id)
+            .collect::<Vec<_>>(),
+        vec![1],
+    );
+    assert_eq!(deleted.folder(db.clone()), Some(f1.clone()));
+    println!("    OK");
+    println!("joins exclude deleted ..");
+    let join_b = |with_deleted: bool| {
+        let mut bld = QueryBld::new()
+            .join("notes")
+            .col("notes.title")
+            .eq(Some(&DbValue::Text("b".to_string())));
+        if with_deleted {
+            bld = bld.with_deleted();
+        }
+        Folder::query(db.clone(), &bld.query().unwrap()).unwrap()
+    };
+    assert!(join_b(false).is_empty());
+    assert_eq!(join_b(true), vec![f1.clone()]);
+    println!("    OK");
+    println!("restricting children prevent delete ..");
+    assert!(is_error!(
+        f1.clone().delete(db.clone()).unwrap_err(),
+        Model(
+            CannotDelete,
+            "Folder",
+            Some("foreign-key-violation".to_string()),
+            "Label",
+            ["restricted"],
+        ),
+    ));
+    assert!(Folder::find(db.clone(), &1).is_some());
+    println!("    OK");
+    println!("cascading children are deleted ..");
+    assert!(label.delete(db.clone()).is_ok());
+    // a child deleted separately in the same second as the parent
+    let t1 = t0 + TimeDelta::try_minutes(1).unwrap();
+    clock.set(t1);
+    let mut separate = Note {
+        id: 4,
+        folder_id: 1,
+        title: "d".to_string(),
+        deleted_at: None,
+    };
+    assert!(separate.insert(db.clone()).is_ok());
+    assert!(separate.delete(db.clone()).is_ok());
+    // the cascade truncates the time to whole seconds, moving it past the
+    // separately deleted child
+    let t2 = t1 + TimeDelta::try_seconds(1).unwrap();
+    clock.set(t1 + TimeDelta::try_milliseconds(500).unwrap());
+    assert!(f1.clone().delete(db.clone()).is_ok());
+    assert_eq!(Folder::find(db.clone(), &1), None);
+    assert_eq!(Folder::find_by_name(db.clone(), &"f1".to_string()), None);
+    assert_eq!(Note::find(db.clone(), &1), None);
+    assert_eq!(
+        Folder::find_with_deleted(db.clone(), &1)
+            .unwrap()
+            .deleted_at,
+        Some(t2.naive_utc()),
+    );
+    assert_eq!(
+        Note::find_with_deleted(db.clone(), &1).unwrap().deleted_at,
+        Some(t2),
+    );
+    assert_eq!(Note::find(db.clone(), &3), Some(notes[2].clone()));
+    assert_eq!(deleted.folder(db.clone()), None);
+    assert_eq!(Folder::is_empty(db.clone()), Ok(false));
+    assert_eq!(Folder::count(db.clone(), &all), Ok(1));
+    println!("    OK");
+    println!("restore() ..");
+    let mut f1 = Folder::find_with_deleted(db.clone(), &1).unwrap();
+    assert!(f1.restore(db.clone()).is_ok());
+    assert_eq!(f1.deleted_at, None);
+    assert_eq!(Folder::find(db.clone(), &1), Some(f1.clone()));
+    println!("    OK");
+    println!("restore() restores children deleted by the cascade ..");
+    assert_eq!(
+        f1.notes(db.clone(), None)
+            .unwrap()
+            .iter()
+            .map(|n| n.id)
+            .collect::<Vec<_>>(),
+        vec![1],
+    );
+    assert_eq!(
+        Note::find_with_deleted(db.clone(), &2).unwrap().deleted_at,
+        Some(t0),
+    );
+    assert_eq!(
+        Note::find_with_deleted(db.clone(), &4).unwrap().deleted_at,
+        Some(t1),
+    );
+    let mut missing = f1.clone();
+    missing.id = 4711;
+    assert!(is_error!(
+        missing.restore(db.clone()).unwrap_err(),
+        Model(
+            CannotSave,
+            "Folder",
+            Some("not-found".to_string()),
+            "id",
+            []
+        ),
+    ));
+    println!("    OK");
+    println!("purge() ..");
+    assert!(matches!(
+        Note::find_with_deleted(db.clone(), &2)
+            .unwrap()
+            .purge(db.clone()),
+        Ok(()),
+    ));
+    assert_eq!(Note::find_with_deleted(db.clone(), &2), None);
+    assert!(f1.purge(db.clone()).is_ok());
+    assert_eq!(Folder::find_with_deleted(db.clone(), &1), None);
+    assert_eq!(Note::find_with_deleted(db.clone(), &1), None);
+    assert!(matches!(
+        Folder::find(db.clone(), &2).unwrap().purge(db.clone()),
+        Ok(()),
+    ));
+    assert_eq!(Note::load_with_deleted(db.clone()), Ok(Vec::new()));
+    assert!(matches!(f2.restore(db.clone()), Err(Error::Model(_))));
+    println!("    OK");
+    empty_tables(db.clone(), &["notes", "labels", "folders"]);
+}
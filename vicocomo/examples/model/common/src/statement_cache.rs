@@ -0,0 +1,60 @@
+use super::models::no_pk::NoPk;
+use vicocomo::{ActiveRecord, DatabaseIf, DbType, DbValue};
+
+pub fn test_statement_cache(db: DatabaseIf) {
+    println!("\nstatement cache -----------------------------------------\n");
+
+    println!("repeated query is a cache hit ..");
+    let before = db.statement_cache_stats().unwrap();
+    assert!(before.capacity > 0);
+    assert!(NoPk::load(db.clone()).is_ok());
+    let once = db.statement_cache_stats().unwrap();
+    assert!(once.size > 0);
+    assert!(NoPk::load(db.clone()).is_ok());
+    let twice = db.statement_cache_stats().unwrap();
+    assert_eq!(twice.hits, once.hits + 1);
+    assert_eq!(twice.misses, once.misses);
+    println!("    OK");
+    println!("repeated exec is a cache hit ..");
+    let update = "UPDATE no_pks SET data = data WHERE data = $1";
+    assert!(db.clone().exec(update, &[DbValue::Int(0)]).is_ok());
+    let once = db.statement_cache_stats().unwrap();
+    assert!(db.clone().exec(update, &[DbValue::Int(1)]).is_ok());
+    let twice = db.statement_cache_stats().unwrap();
+    assert_eq!(twice.hits, once.hits + 1);
+    assert_eq!(twice.misses, once.misses);
+    println!("    OK");
+    println!("cached statement survives a schema change ..");
+    let _ = db.clone().exec("DROP TABLE IF EXISTS cache_tests", &[]);
+    assert!(db
+        .clone()
+        .exec("CREATE TABLE cache_tests (a BIGINT)", &[])
+        .is_ok());
+    assert!(db
+        .clone()
+        .exec("INSERT INTO cache_tests (a) VALUES (1)", &[])
+        .is_ok());
+    let select = "SELECT * FROM cache_tests";
+    assert_eq!(
+        format!(
+            "{:?}",
+            db.clone().query(select, &[], &[DbType::Int]).unwrap(),
+        ),
+        format!("{:?}", vec![vec![DbValue::Int(1)]]),
+    );
+    assert!(db
+        .clone()
+        .exec("ALTER TABLE cache_tests ADD COLUMN b BIGINT", &[])
+        .is_ok());
+    assert_eq!(
+        format!(
+            "{:?}",
+            db.clone()
+                .query(select, &[], &[DbType::Int, DbType::NulInt])
+                .unwrap(),
+        ),
+        format!("{:?}", vec![vec![DbValue::Int(1), DbValue::NulInt(None)]]),
+    );
+    assert!(db.clone().exec("DROP TABLE cache_tests", &[]).is_ok());
+    println!("    OK");
+}
@@ -0,0 +1,95 @@
+use super::models::{empty_tables, stamp::Stamp};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::Arc;
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, FixedClock};
+
+pub fn test_timestamp(db: DatabaseIf) {
+    empty_tables(db.clone(), &["stamps"]);
+    let t0 = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+    let epoch = DateTime::<Utc>::from_timestamp(0, 0).unwrap();
+    let clock = FixedClock::new(t0);
+    let db = db.with_clock(Arc::new(clock.clone()));
+    let minute = TimeDelta::try_minutes(1).unwrap();
+
+    println!("\ntimestamps ----------------------------------------------\n");
+
+    println!("insert() sets both ..");
+    let mut s = Stamp {
+        id: 1,
+        name: "stamp".to_string(),
+        created_at: epoch.naive_utc(),
+        updated_at: None,
+    };
+    let fresh = s.clone();
+    assert!(s.insert(db.clone()).is_ok());
+    assert_eq!(s.created_at, t0.naive_utc());
+    assert_eq!(s.updated_at, Some(t0));
+    assert_eq!(Stamp::find(db.clone(), &1), Some(s.clone()));
+    println!("    OK");
+    println!("update() sets updated_at ..");
+    clock.advance(minute);
+    s.name = "updated".to_string();
+    assert!(s.update(db.clone()).is_ok());
+    assert_eq!(s.created_at, t0.naive_utc());
+    assert_eq!(s.updated_at, Some(t0 + minute));
+    assert_eq!(Stamp::find(db.clone(), &1), Some(s.clone()));
+    println!("    OK");
+    println!("update_columns() sets updated_at unless given ..");
+    clock.advance(minute);
+    assert!(s
+        .update_columns(
+            db.clone(),
+            &[("name", DbValue::Text("columns".to_string()))],
+        )
+        .is_ok());
+    assert_eq!(s.name, "columns");
+    assert_eq!(s.updated_at, Some(t0 + minute + minute));
+    assert!(s
+        .update_columns(
+            db.clone(),
+            &[("updated_at", DbValue::DateTime(epoch.naive_utc()))],
+        )
+        .is_ok());
+    assert_eq!(s.updated_at, Some(epoch));
+    assert_eq!(Stamp::find(db.clone(), &1), Some(s.clone()));
+    println!("    OK");
+    println!("insert_batch() sets both ..");
+    let mut batch = [
+        Stamp {
+            id: 2,
+            ..fresh.clone()
+        },
+        Stamp {
+            id: 3,
+            ..fresh.clone()
+        },
+    ];
+    let inserted = Stamp::insert_batch(db.clone(), &mut batch).unwrap();
+    let t2 = t0 + minute + minute;
+    assert_eq!(inserted.len(), 2);
+    assert!(inserted
+        .iter()
+        .all(|s| s.created_at == t2.naive_utc() && s.updated_at == Some(t2)));
+    println!("    OK");
+    println!("without_timestamps() keeps given values ..");
+    let mut kept = Stamp {
+        id: 4,
+        updated_at: Some(epoch),
+        ..fresh
+    };
+    assert!(kept.insert(db.clone().without_timestamps()).is_ok());
+    assert_eq!(kept.created_at, epoch.naive_utc());
+    assert_eq!(kept.updated_at, Some(epoch));
+    kept.name = "kept".to_string();
+    assert!(kept.update(db.clone().without_timestamps()).is_ok());
+    assert_eq!(kept.updated_at, Some(epoch));
+    println!("    OK");
+    println!("try_from_csv() keeps original values ..");
+    let before = Stamp::load(db.clone()).unwrap();
+    let (_, csv) = Stamp::try_to_csv(db.clone(), None).unwrap();
+    clock.advance(minute);
+    assert!(Stamp::try_from_csv(db.clone(), &csv, None).is_ok());
+    assert_eq!(Stamp::load(db.clone()).unwrap(), before);
+    println!("    OK");
+    empty_tables(db.clone(), &["stamps"]);
+}
@@ -0,0 +1,160 @@
+use super::models::no_pk::NoPk;
+use std::sync::Arc;
+use std::time::Duration;
+use vicocomo::{
+    ActiveRecord, DatabaseIf, DbConn, Error, RetryPolicy,
+    SQLSTATE_SERIALIZATION_FAILURE,
+};
+
+// conn should be the connection behind db.
+pub fn test_transaction(db: DatabaseIf, conn: Arc<dyn DbConn + Send + Sync>) {
+    let mut saved = NoPk::load(db.clone()).unwrap();
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+
+    println!("\ntransactions --------------------------------------------\n");
+
+    println!("committing transaction ..");
+    let res = db.clone().transaction(|db| {
+        assert_eq!(db.transaction_depth(), 1);
+        NoPk { data: 1 }.insert(db)
+    });
+    assert!(res.is_ok());
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(NoPk::load(db.clone()).unwrap().len(), 1);
+    println!("    OK");
+    println!("rolling back transaction ..");
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        NoPk { data: 2 }.insert(db)?;
+        Err(Error::other("roll back"))
+    });
+    assert!(res.is_err());
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(NoPk::load(db.clone()).unwrap().len(), 1);
+    println!("    OK");
+    println!("rolling back inner transaction only ..");
+    let res = db.clone().transaction(|db| {
+        NoPk { data: 3 }.insert(db.clone())?;
+        let inner: Result<(), Error> = db.clone().transaction(|db| {
+            assert_eq!(db.transaction_depth(), 2);
+            NoPk { data: 4 }.insert(db)?;
+            Err(Error::other("roll back inner"))
+        });
+        assert!(inner.is_err());
+        assert_eq!(db.transaction_depth(), 1);
+        db.clone().transaction(|db| NoPk { data: 5 }.insert(db))
+    });
+    assert!(res.is_ok());
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(
+        NoPk::load(db.clone())
+            .unwrap()
+            .iter()
+            .map(|n| n.data)
+            .collect::<Vec<_>>(),
+        vec![5, 3, 1],
+    );
+    println!("    OK");
+    println!("rolling back outer transaction including inner ..");
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        db.clone().transaction(|db| NoPk { data: 6 }.insert(db))?;
+        Err(Error::other("roll back outer"))
+    });
+    assert!(res.is_err());
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(NoPk::load(db.clone()).unwrap().len(), 3);
+    println!("    OK");
+    println!("sharing transaction depth with another interface ..");
+    let other = DatabaseIf::new(conn);
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        NoPk { data: 7 }.insert(db.clone())?;
+        assert_eq!(other.transaction_depth(), 1);
+        let inner: Result<(), Error> = other.clone().transaction(|other| {
+            assert_eq!(db.transaction_depth(), 2);
+            NoPk { data: 8 }.insert(other)?;
+            Err(Error::other("roll back inner"))
+        });
+        assert!(inner.is_err());
+        Ok(())
+    });
+    assert!(res.is_ok());
+    assert_eq!(other.transaction_depth(), 0);
+    assert_eq!(
+        NoPk::load(db.clone())
+            .unwrap()
+            .iter()
+            .map(|n| n.data)
+            .collect::<Vec<_>>(),
+        vec![7, 5, 3, 1],
+    );
+    let _ = db.clone().exec("DELETE FROM no_pks WHERE data = 7", &[]);
+    println!("    OK");
+    println!("retrying transaction on retryable error ..");
+    let policy = RetryPolicy {
+        attempts: 3,
+        backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(2),
+    };
+    let retryable =
+        Error::database(Some(SQLSTATE_SERIALIZATION_FAILURE), "conflict");
+    assert!(retryable.is_retryable());
+    let mut attempts = 0;
+    let res = db.clone().transaction_with_retry(&policy, |db| {
+        attempts += 1;
+        NoPk {
+            data: 10 + attempts,
+        }
+        .insert(db)?;
+        if attempts < 3 {
+            Err(retryable.clone())
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(res, Ok(3));
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(
+        NoPk::load(db.clone())
+            .unwrap()
+            .iter()
+            .map(|n| n.data)
+            .collect::<Vec<_>>(),
+        vec![13, 5, 3, 1],
+    );
+    println!("    OK");
+    println!("giving up after policy.attempts ..");
+    let mut attempts = 0;
+    let res: Result<(), Error> =
+        db.clone().transaction_with_retry(&policy, |_| {
+            attempts += 1;
+            Err(retryable.clone())
+        });
+    assert_eq!(res, Err(retryable.clone()));
+    assert_eq!(attempts, 3);
+    println!("    OK");
+    println!("not retrying other errors ..");
+    let mut attempts = 0;
+    let res: Result<(), Error> =
+        db.clone().transaction_with_retry(&policy, |_| {
+            attempts += 1;
+            Err(Error::other("not retryable"))
+        });
+    assert_eq!(res, Err(Error::other("not retryable")));
+    assert_eq!(attempts, 1);
+    println!("    OK");
+    println!("not retrying nested transaction ..");
+    let mut attempts = 0;
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        db.transaction_with_retry(&policy, |_| {
+            attempts += 1;
+            Err(retryable.clone())
+        })
+    });
+    assert_eq!(res, Err(retryable));
+    assert_eq!(attempts, 1);
+    assert_eq!(db.transaction_depth(), 0);
+    assert_eq!(NoPk::load(db.clone()).unwrap().len(), 4);
+    println!("    OK");
+
+    let _ = db.clone().exec("DELETE FROM no_pks", &[]);
+    assert!(NoPk::insert_batch(db.clone(), &mut saved[..]).is_ok());
+}
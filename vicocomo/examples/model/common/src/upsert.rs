@@ -0,0 +1,109 @@
+use super::models::{empty_tables, single_pk::SinglePk};
+use vicocomo::{ActiveRecord, DatabaseIf, Error, QueryBld};
+
+pub fn test_upsert(db: DatabaseIf) {
+    empty_tables(db.clone(), &["single_pks"]);
+    let all = QueryBld::new().query().unwrap();
+
+    println!("\nupsert by unique fields ---------------------------------\n");
+
+    println!("upsert inserting ..");
+    let mut s1 = SinglePk {
+        id: None,
+        name: Some("x".to_string()),
+        data: Some(1.0),
+        opt: Some(5),
+        un2: 1,
+    };
+    assert!(s1.upsert_by_name_and_un2(db.clone()).is_ok());
+    assert!(s1.id.is_some());
+    assert_eq!(
+        SinglePk::find(db.clone(), &s1.id.unwrap()),
+        Some(s1.clone())
+    );
+    println!("    OK");
+    println!("upsert updating ..");
+    let mut s2 = SinglePk {
+        id: None,
+        data: Some(2.0),
+        opt: None,
+        ..s1.clone()
+    };
+    assert!(s2.upsert_by_name_and_un2(db.clone()).is_ok());
+    assert_eq!(s2.id, s1.id);
+    assert_eq!(s2.data, Some(2.0));
+    assert_eq!(s2.opt, Some(5));
+    assert_eq!(SinglePk::count(db.clone(), &all), Ok(1));
+    assert_eq!(
+        SinglePk::find(db.clone(), &s1.id.unwrap()),
+        Some(s2.clone())
+    );
+    println!("    OK");
+    println!("upsert batch ..");
+    let mut batch = [
+        SinglePk {
+            id: None,
+            data: None,
+            opt: Some(6),
+            ..s1.clone()
+        },
+        SinglePk {
+            id: None,
+            name: Some("y".to_string()),
+            data: Some(3.0),
+            opt: None,
+            un2: 1,
+        },
+    ];
+    assert!(
+        SinglePk::upsert_batch_by_name_and_un2(db.clone(), &mut batch)
+            .is_ok()
+    );
+    assert_eq!(batch[0].id, s1.id);
+    assert_eq!(batch[0].data, None);
+    assert_eq!(batch[0].opt, Some(6));
+    assert!(batch[1].id.is_some());
+    assert_ne!(batch[1].id, s1.id);
+    assert_eq!(batch[1].opt, Some(4711));
+    assert_eq!(SinglePk::count(db.clone(), &all), Ok(2));
+    let mut loaded = SinglePk::load(db.clone()).unwrap();
+    loaded.sort_by_key(|s| s.id);
+    assert_eq!(loaded, batch.to_vec());
+    println!("    OK");
+    println!("error upserting duplicate keys ..");
+    let z = SinglePk {
+        id: None,
+        name: Some("z".to_string()),
+        data: None,
+        opt: None,
+        un2: 1,
+    };
+    let mut batch = [z.clone(), z.clone()];
+    assert!(matches!(
+        SinglePk::upsert_batch_by_name_and_un2(db.clone(), &mut batch),
+        Err(Error::InvalidInput(_)),
+    ));
+    assert_eq!(SinglePk::count(db.clone(), &all), Ok(2));
+    println!("    OK");
+    println!("error upserting without unique field ..");
+    let mut s = SinglePk {
+        name: None,
+        ..z.clone()
+    };
+    assert!(matches!(
+        s.upsert_by_name_and_un2(db.clone()),
+        Err(Error::Model(_)),
+    ));
+    println!("    OK");
+    println!("error from before_save() ..");
+    let mut s = SinglePk {
+        name: Some(String::new()),
+        ..z
+    };
+    assert!(matches!(
+        s.upsert_by_name_and_un2(db.clone()),
+        Err(Error::Model(_)),
+    ));
+    assert_eq!(SinglePk::count(db.clone(), &all), Ok(2));
+    println!("    OK");
+}
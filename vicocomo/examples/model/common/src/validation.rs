@@ -0,0 +1,102 @@
+use super::models::{contact::Contact, empty_tables};
+use vicocomo::{is_error, ActiveRecord, DatabaseIf};
+
+pub fn test_validation(db: DatabaseIf) {
+    empty_tables(db.clone(), &["contacts"]);
+
+    println!("\nvalidation attributes -----------------------------------\n");
+
+    println!("valid values are saved ..");
+    let mut c1 = Contact {
+        id: 1,
+        name: "Åsa".to_string(),
+        email: Some("asa@example.com".to_string()),
+        code: "ABC".to_string(),
+        age: Some(42),
+        score: 1.0,
+        note: Some(Some("note".to_string())),
+    };
+    let valid = c1.clone();
+    assert!(c1.insert(db.clone()).is_ok());
+    let mut c2 = Contact {
+        id: 2,
+        email: None,
+        age: None,
+        score: -1.0,
+        note: Some(None),
+        ..valid.clone()
+    };
+    assert!(c2.insert(db.clone()).is_ok());
+    assert_eq!(Contact::find(db.clone(), &2), Some(c2.clone()));
+    println!("    OK");
+    println!("all violations are reported together ..");
+    let mut c3 = Contact {
+        id: 3,
+        name: "Ö".to_string(),
+        email: Some("not an email".to_string()),
+        code: "abc".to_string(),
+        age: Some(-1),
+        score: 1.6,
+        note: Some(Some("4711".to_string())),
+    };
+    let err = c3.insert(db.clone()).unwrap_err();
+    assert!(is_error!(
+        &err,
+        Model(
+            CannotSave,
+            "Contact",
+            None,
+            "name",
+            ["too-short"],
+            "email",
+            ["invalid-email"],
+            "code",
+            ["invalid-format", "not-one-of"],
+            "age",
+            ["too-small"],
+            "score",
+            ["too-large"],
+            "note",
+            ["has-digits"],
+        ),
+    ));
+    assert!(err.to_strings().contains(
+        &"error--Model-CannotSave--Contact--name--too-short".into()
+    ));
+    assert_eq!(Contact::find(db.clone(), &3), None);
+    println!("    OK");
+    println!("insert_batch() and update() validate ..");
+    let mut batch = [
+        Contact {
+            id: 3,
+            ..valid.clone()
+        },
+        Contact {
+            id: 4,
+            code: "XXX".to_string(),
+            ..valid
+        },
+    ];
+    assert!(is_error!(
+        Contact::insert_batch(db.clone(), &mut batch).unwrap_err(),
+        Model(CannotSave, "Contact", None, "code", ["not-one-of"]),
+    ));
+    assert_eq!(Contact::find(db.clone(), &3), None);
+    c1.name = "Much too long".to_string();
+    c1.age = Some(151);
+    assert!(is_error!(
+        c1.update(db.clone()).unwrap_err(),
+        Model(
+            CannotSave,
+            "Contact",
+            None,
+            "name",
+            ["too-long"],
+            "age",
+            ["too-large"],
+        ),
+    ));
+    assert_eq!(Contact::find(db.clone(), &1).unwrap().name, "Åsa");
+    println!("    OK");
+    empty_tables(db.clone(), &["contacts"]);
+}
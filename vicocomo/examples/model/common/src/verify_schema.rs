@@ -0,0 +1,153 @@
+use super::models::{
+    view::View, Attachment, DefaultParent, MultiPk, Native, NoPk,
+    NonstandardParent, Random, Score, Serialize, SinglePk,
+};
+use super::schema::schema_sql;
+use vicocomo::{ActiveRecord, DatabaseIf, Error, SchemaMismatch, SqlDialect};
+
+macro_rules! verify_all_models {
+    ($db:expr) => {
+        vicocomo::verify_all!(
+            $db,
+            Attachment,
+            DefaultParent,
+            MultiPk,
+            Native,
+            NoPk,
+            NonstandardParent,
+            Random,
+            Score,
+            Serialize,
+            SinglePk,
+            View,
+        )
+    };
+}
+
+pub fn test_verify_schema(db: DatabaseIf, dialect: SqlDialect) {
+    println!("\nverify schema -------------------------------------------\n");
+
+    println!("table created by hand ..");
+    assert!(SinglePk::verify_schema(db.clone()).is_ok());
+    assert!(DefaultParent::verify_schema(db.clone()).is_ok());
+    assert!(View::verify_schema(db.clone()).is_ok());
+    let name_nullable = SchemaMismatch::Nullability {
+        table: "scores".to_string(),
+        column: "name".to_string(),
+        nullable: true,
+    };
+    assert_eq!(
+        Score::verify_schema(db.clone()),
+        Err(vec![name_nullable.clone()]),
+    );
+    assert_eq!(
+        name_nullable.to_string(),
+        "scores: column name should be nullable",
+    );
+    println!("    OK");
+    println!("verify_all!() ..");
+    assert_eq!(verify_all_models!(db.clone()), Err(vec![name_nullable]));
+    println!("    OK");
+    println!("drift ..");
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        db.clone().exec("DROP VIEW views", &[])?;
+        db.clone().exec("DROP TABLE joins", &[])?;
+        db.clone().exec("DROP TABLE scores", &[])?;
+        db.clone().exec(
+            "CREATE TABLE scores (id BIGINT NOT NULL, name TEXT)",
+            &[],
+        )?;
+        assert_eq!(
+            Score::verify_schema(db.clone()),
+            Err(vec![
+                SchemaMismatch::MissingColumn {
+                    table: "scores".to_string(),
+                    column: "points".to_string(),
+                },
+                SchemaMismatch::MissingPrimaryKey {
+                    table: "scores".to_string(),
+                    columns: vec!["id".to_string()],
+                },
+            ]),
+        );
+        assert_eq!(
+            View::verify_schema(db.clone()),
+            Err(vec![SchemaMismatch::MissingTable {
+                table: "views".to_string(),
+            }]),
+        );
+        db.clone().exec(
+            "CREATE TABLE joins
+            (   default_parent_id  BIGINT NOT NULL
+                    REFERENCES default_parents(id)
+            ,   single_pk_id       BIGINT NOT NULL
+            ,   PRIMARY KEY(default_parent_id, single_pk_id)
+            )",
+            &[],
+        )?;
+        assert_eq!(
+            DefaultParent::verify_schema(db.clone()),
+            Err(vec![SchemaMismatch::MissingForeignKey {
+                table: "joins".to_string(),
+                columns: vec!["single_pk_id".to_string()],
+                remote_table: "single_pks".to_string(),
+            }]),
+        );
+        db.clone().exec("DROP TABLE single_pks", &[])?;
+        db.clone().exec(
+            "CREATE TABLE single_pks
+            (   id    BIGINT     PRIMARY KEY
+            ,   name  TEXT       NOT NULL DEFAULT 'default'
+            ,   data  FLOAT(53)
+            ,   opt   BIGINT     NOT NULL DEFAULT 4711
+            ,   un2   BIGINT     NOT NULL
+            )",
+            &[],
+        )?;
+        assert_eq!(
+            SinglePk::verify_schema(db.clone()),
+            Err(vec![SchemaMismatch::MissingUnique {
+                table: "single_pks".to_string(),
+                columns: vec!["name".to_string(), "un2".to_string()],
+            }]),
+        );
+        // roll back to the tables created by setup()
+        Err(Error::other("rollback"))
+    });
+    assert_eq!(res, Err(Error::other("rollback")));
+    println!("    OK");
+    println!("tables created by schema_sql() ..");
+    let res: Result<(), Error> = db.clone().transaction(|db| {
+        db.clone().exec("DROP VIEW views", &[])?;
+        for table in [
+            "attachments",
+            "natives",
+            "joins",
+            "multi_pks",
+            "default_parents",
+            "nonstandard_parents",
+            "no_pks",
+            "single_pks",
+            "randoms",
+            "scores",
+            "serializes",
+        ] {
+            db.clone().exec(&format!("DROP TABLE {}", table), &[])?;
+        }
+        for stmt in schema_sql(dialect)
+            .split(";\n")
+            .filter(|s| !s.trim().is_empty())
+        {
+            db.clone().exec(stmt, &[])?;
+        }
+        assert_eq!(
+            verify_all_models!(db.clone()),
+            Err(vec![SchemaMismatch::MissingTable {
+                table: "views".to_string(),
+            }]),
+        );
+        Err(Error::other("rollback"))
+    });
+    assert_eq!(res, Err(Error::other("rollback")));
+    println!("    OK");
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use vicocomo::{ActiveRecord, DatabaseIf, DbValue, Error, ModelErrorKind};
+use vicocomo_stubs::MockConn;
+
+#[derive(vicocomo::ActiveRecord, Clone, Debug, PartialEq)]
+pub struct User {
+    #[vicocomo_optional]
+    #[vicocomo_primary]
+    pub id: Option<i64>,
+    #[vicocomo_unique = "email"]
+    pub email: String,
+}
+
+fn main() {
+    let conn = MockConn::new();
+    let db = DatabaseIf::new(Arc::new(conn.clone()));
+
+    println!("\nmock database connection --------------------------------\n");
+
+    println!("programmed query result ..");
+    conn.expect_query(
+        r"^SELECT .* FROM users",
+        Ok(vec![vec![
+            DbValue::Int(1),
+            DbValue::Text("a@b.c".to_string()),
+        ]]),
+    );
+    assert_eq!(
+        User::load(db.clone()).unwrap(),
+        vec![User {
+            id: Some(1),
+            email: "a@b.c".to_string(),
+        }],
+    );
+    conn.verify();
+    println!("    OK");
+    println!("unique violation becomes a model error ..");
+    conn.clear();
+    conn.expect_query(
+        r"^INSERT INTO users",
+        Err(MockConn::unique_violation()),
+    );
+    conn.expect_query(
+        r"^SELECT .* FROM users WHERE email = \$1",
+        Ok(vec![vec![
+            DbValue::Int(1),
+            DbValue::Text("a@b.c".to_string()),
+        ]]),
+    );
+    let res = User {
+        id: None,
+        email: "a@b.c".to_string(),
+    }
+    .insert(db.clone());
+    match res {
+        Err(Error::Model(ref me)) => {
+            assert_eq!(me.error, ModelErrorKind::CannotSave);
+            assert_eq!(me.general, Some("unique-violation".to_string()));
+            assert_eq!(me.field_errors[0].0, "email");
+        }
+        _ => panic!("expected Error::Model, got {:?}", res),
+    }
+    conn.verify();
+    println!("    OK");
+    println!("recording calls ..");
+    let calls = conn.calls();
+    assert_eq!(calls.len(), 2);
+    assert!(calls[0].sql.starts_with("INSERT INTO users"));
+    assert_eq!(calls[0].values.len(), 1);
+    assert_eq!(calls[0].values[0].to_string(), "a@b.c");
+    assert!(conn.called(r"^SELECT .* WHERE email"));
+    conn.expect_exec(r"^DELETE", Err(MockConn::foreign_key_violation()));
+    let res = db
+        .clone()
+        .transaction(|db| db.exec("DELETE FROM users", &[]));
+    assert!(res.is_err());
+    assert!(conn.called("^BEGIN$"));
+    assert!(conn.called("^ROLLBACK$"));
+    println!("    OK");
+    println!("repeated and foreign key violation results ..");
+    conn.clear();
+    conn.always_exec(r"^DELETE", Err(MockConn::foreign_key_violation()));
+    for _ in 0..2 {
+        assert!(db
+            .clone()
+            .exec("DELETE FROM users", &[])
+            .unwrap_err()
+            .is_foreign_key_violation());
+    }
+    conn.verify();
+    println!("    OK");
+    println!("unexpected statement panics ..");
+    conn.clear();
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = db.clone().exec("UPDATE users SET email = 'x'", &[]);
+    }));
+    assert!(res.is_err());
+    println!("    OK");
+    println!("unused expectation panics ..");
+    conn.clear();
+    conn.expect_exec(r"^UPDATE", Ok(1));
+    let res = std::panic::catch_unwind(|| conn.verify());
+    assert!(res.is_err());
+    // the mutex is not poisoned
+    assert!(conn.calls().is_empty());
+    conn.clear();
+    println!("    OK");
+
+    println!("\ntest completed successfully -----------------------------\n");
+}
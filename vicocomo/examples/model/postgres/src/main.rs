@@ -17,20 +17,44 @@ async fn main() {
         }
     });
     let pg_conn = ::vicocomo_postgres::PgConn::new(pg_client);
-    let db = ::vicocomo::DatabaseIf::new(Arc::new(pg_conn));
+    let conn = Arc::new(pg_conn);
+    let db = ::vicocomo::DatabaseIf::new(conn.clone());
     setup(db.clone(), "BIGSERIAL PRIMARY KEY");
 
     test_belongs_to(db.clone());
+    test_blob(db.clone());
+    test_constraint(db.clone());
     test_delete(db.clone());
+    test_exec_sql(db.clone());
     test_many_to_many(db.clone());
+    test_migration(db.clone());
     test_multi_pk(db.clone());
+    test_native(db.clone());
     test_no_pk(db.clone());
     test_nonstandard_parent(db.clone());
     test_one_to_many(db.clone());
+    test_preload(db.clone());
+    test_query_each(db.clone());
+    test_query_log(db.clone());
+    test_query_rows(db.clone());
     test_random(db.clone());
+    test_schema(db.clone(), vicocomo::SqlDialect::Postgres);
     test_serialize(db.clone());
     test_single_pk(db.clone());
+    test_statement_cache(db.clone());
+    test_transaction(db.clone(), conn.clone());
+    test_verify_schema(db.clone(), vicocomo::SqlDialect::Postgres);
     test_csv(db.clone());
+    test_aggregate(db.clone());
+    test_query_bld(db.clone());
+    test_join(db.clone());
+    test_upsert(db.clone());
+    test_lock_version(db.clone());
+    test_timestamp(db.clone());
+    test_soft_delete(db.clone());
+    test_hooks(db.clone());
+    test_validation(db.clone());
+    test_change_tracking(db.clone());
 
     println!("\ntest completed successfully -----------------------------\n");
 }
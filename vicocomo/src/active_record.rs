@@ -5,10 +5,14 @@
 //! ](../../vicocomo_active_record/derive.ActiveRecord.html) with the same name.
 
 use crate::error::{ModelError, ModelErrorKind};
+use crate::schema::{
+    ForeignKeySchema, SchemaMismatch, SqlDialect, TableSchema,
+};
 use crate::{map_error, t, DatabaseIf, DbType, DbValue, Error};
 use ljumvall_utils::first_n_chars_ellipsis;
 use regex::Regex;
 use std::cmp::min;
+use std::io::Write;
 use std::str::from_utf8;
 
 /// The (major, minor) Vicocomo version where the current backup format was
@@ -67,6 +71,18 @@ pub trait ActiveRecord: Sized {
     ///
     fn pk_value(&self) -> Option<Self::PkType>;
 
+    //- Provided - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -//
+
+    /// The column marking a row as deleted if the model is [soft deleted
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete), else `None`.
+    ///
+    /// Used e.g. to exclude deleted rows when [joining
+    /// ](struct.QueryBld.html#method.join) the model's table.
+    ///
+    fn soft_delete_column() -> Option<String> {
+        None
+    }
+
     //- Functions for deleting models from the database --------------------//
 
     //- Required - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -//
@@ -82,7 +98,10 @@ pub trait ActiveRecord: Sized {
     /// if
     /// - self has no primary key, or
     /// - the model implements [`BeforeDelete`](trait.BeforeDelete.html) and
-    ///   `before_delete()` returns an error.
+    ///   `before_delete()` returns an error, or
+    /// - the model implements [`AfterDelete`](trait.AfterDelete.html) and
+    ///   `after_delete()` returns an error, in which case the deletion
+    ///   should be rolled back.
     ///
     /// The method generated by the derive macro always returns
     /// [`Err(Error::Other("not-available")`
@@ -93,6 +112,11 @@ pub trait ActiveRecord: Sized {
     /// For referential integrity and other error handling, see
     /// [`delete_batch()`](#tymethod.delete_batch).
     ///
+    /// If the model has a [`vicocomo_soft_delete`
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete) field, the
+    /// methods generated by the derive macro mark the row as deleted
+    /// instead.
+    ///
     fn delete(self, db: DatabaseIf) -> Result<(), Error>;
 
     /// Returns `Ok(batch.len())` iff each key in `batch` identifies a database
@@ -102,8 +126,12 @@ pub trait ActiveRecord: Sized {
     /// ](#associatedtype.PkType).
     ///
     /// The implementation should not call [`before_delete()`
-    /// ](trait.BeforeDelete.html#tymethod.before_delete). You have to use
-    /// [`delete()`](#tymethod.delete) for that.
+    /// ](trait.BeforeDelete.html#tymethod.before_delete) or
+    /// [`after_delete()`](trait.AfterDelete.html#tymethod.after_delete)
+    /// unless the model opts in, e.g. by the derive macro's attribute
+    /// [`vicocomo_delete_batch_hooks`
+    /// ](../derive.ActiveRecord.html#vicocomo_delete_batch_hooks).
+    /// Otherwise you have to use [`delete()`](#tymethod.delete) for that.
     ///
     /// <b>Errors</b>
     ///
@@ -174,19 +202,246 @@ pub trait ActiveRecord: Sized {
     ///
     fn query(db: DatabaseIf, query: &Query) -> Result<Vec<Self>, Error>;
 
+    /// Returns the average of the values in a column for the records that
+    /// satisfy `query`, or `None` if there are no such records or all the
+    /// values are NULL.
+    ///
+    /// `field` is the name of a numeric model struct field. The derive macro
+    /// uses the corresponding column name, see [`vicocomo_column`
+    /// ](../derive.ActiveRecord.html#vicocomo_column).
+    ///
+    /// `query` is a [`Query`](struct.Query.html). If it has a limit or an
+    /// offset, the average is computed over the records that [`query()`
+    /// ](#tymethod.query) would return.
+    ///
+    /// The returned value is a [`DbValue::Float`
+    /// ](../database/enum.DbValue.html#variant.Float).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `field` is not a
+    /// numeric field in the model.
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database).
+    ///
+    fn avg(
+        db: DatabaseIf,
+        field: &str,
+        query: &Query,
+    ) -> Result<Option<DbValue>, Error>;
+
+    /// Returns the distinct values in a column for the records that satisfy
+    /// `query` together with the number of records having each value,
+    /// ordered by the value.
+    ///
+    /// `field` is the name of a model struct field, see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// `query` is a [`Query`](struct.Query.html), see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// The values are of the field's [`DbValue`
+    /// ](../database/enum.DbValue.html) type, so a NULL value is returned as
+    /// e.g. `DbValue::NulInt(None)`.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `field` is not a
+    /// field in the model.
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database).
+    ///
+    fn group_by(
+        db: DatabaseIf,
+        field: &str,
+        query: &Query,
+    ) -> Result<Vec<(DbValue, i64)>, Error>;
+
+    /// Returns the largest value in a column for the records that satisfy
+    /// `query`, or `None` if there are no such records or all the values are
+    /// NULL.
+    ///
+    /// `field` is the name of a model struct field, see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// `query` is a [`Query`](struct.Query.html), see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// The returned value is of the field's type, never a `Nul...` variant.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `field` is not a
+    /// field in the model.
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database).
+    ///
+    fn max(
+        db: DatabaseIf,
+        field: &str,
+        query: &Query,
+    ) -> Result<Option<DbValue>, Error>;
+
+    /// Returns the smallest value in a column, see [`max()`](#tymethod.max).
+    ///
+    fn min(
+        db: DatabaseIf,
+        field: &str,
+        query: &Query,
+    ) -> Result<Option<DbValue>, Error>;
+
+    /// Returns the sum of the values in a column for the records that satisfy
+    /// `query`, or `None` if there are no such records or all the values are
+    /// NULL.
+    ///
+    /// `field` is the name of a numeric model struct field, see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// `query` is a [`Query`](struct.Query.html), see [`avg()`
+    /// ](#tymethod.avg).
+    ///
+    /// The returned value is a [`DbValue::Int`
+    /// ](../database/enum.DbValue.html#variant.Int) or a [`DbValue::Float`
+    /// ](../database/enum.DbValue.html#variant.Float) depending on the type
+    /// of the field.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `field` is not a
+    /// numeric field in the model.
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database).
+    ///
+    fn sum(
+        db: DatabaseIf,
+        field: &str,
+        query: &Query,
+    ) -> Result<Option<DbValue>, Error>;
+
     //- Provided - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -//
 
-    /// Returns `true` iff there are no data in the datbase for this model.
+    /// Returns the number of records that satisfy `query` without loading
+    /// them.
+    ///
+    /// `query` is a [`Query`](struct.Query.html). The limit and offset are
+    /// respected, so the count is the number of records that [`query()`
+    /// ](#tymethod.query) would return. The order is ignored.
+    ///
+    /// The implementation generated by the derive macro also supports
+    /// [joins](struct.QueryBld.html#method.join).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `query` lacks a
+    /// value, or, by the default implementation, if `query` has joins.
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database).
+    ///
+    fn count(db: DatabaseIf, query: &Query) -> Result<usize, Error> {
+        let (filter, values) = query.filter_sql()?;
+        let total = match db
+            .query(
+                &format!("SELECT COUNT(*) FROM {} {}", Self::table(), filter),
+                &values,
+                &[DbType::Int],
+            )?
+            .first()
+            .and_then(|row| row.first())
+        {
+            Some(DbValue::Int(n)) => *n as usize,
+            _ => return Err(Error::this_cannot_happen("count")),
+        };
+        let rest = total.saturating_sub(query.offset().unwrap_or(0));
+        Ok(query.limit().map(|l| min(l, rest)).unwrap_or(rest))
+    }
+
+    /// Returns `true` iff there is at least one record that satisfies
+    /// `query`, without loading it.
+    ///
+    /// `query` is a [`Query`](struct.Query.html), see [`count()`
+    /// ](#method.count).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// See [`count()`](#method.count).
+    ///
+    fn exists(db: DatabaseIf, query: &Query) -> Result<bool, Error> {
+        let (filter, values) = query.filter_sql()?;
+        if query.limit() == Some(0) {
+            return Ok(false);
+        }
+        db.query(
+            &format!(
+                "SELECT CAST(1 AS BIGINT) FROM {} {} LIMIT 1 OFFSET {}",
+                Self::table(),
+                filter,
+                query.offset().unwrap_or(0),
+            ),
+            &values,
+            &[DbType::Int],
+        )
+        .map(|rows| !rows.is_empty())
+    }
+
+    /// Call `f` for each record that satisfies `query`.
+    ///
+    /// The default implementation calls [`query()`](#tymethod.query) and
+    /// then `f` for each returned record. The derive macro implements it
+    /// without keeping all of them in memory, see [`DatabaseIf::query_each()`
+    /// ](../database/struct.DatabaseIf.html#method.query_each).
+    ///
+    /// `query` is a [`Query`](struct.Query.html), see [`query()`
+    /// ](#tymethod.query).
+    ///
+    /// `f` must not use the database. It may stop the iteration by returning
+    /// an error.
+    ///
+    /// Returns the number of records given to `f`.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Forwards database errors as [`Error::Database`
+    /// ](../error/enum.Error.html#variant.Database), and any error from `f`.
+    ///
+    fn for_each<F>(
+        db: DatabaseIf,
+        query: &Query,
+        mut f: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(Self) -> Result<(), Error>,
+    {
+        let objs = Self::query(db, query)?;
+        let count = objs.len();
+        for obj in objs {
+            f(obj)?;
+        }
+        Ok(count)
+    }
+
+    /// Returns `true` iff there are no data in the datbase for this model,
+    /// counting also [soft deleted
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete) objects.
     ///
     fn is_empty(db: DatabaseIf) -> Result<bool, Error> {
-        Self::query(
+        Self::exists(
             db,
             &QueryBld::new()
-                .limit(1)
+                .with_deleted()
                 .query()
                 .ok_or_else(|| Error::this_cannot_happen(""))?,
         )
-        .map(|v| v.len() == 0)
+        .map(|e| !e)
     }
 
     /// Returns an [`Error::Model`](../error/enum.Error.html#variant.Model) if
@@ -261,10 +516,18 @@ pub trait ActiveRecord: Sized {
     ///   database, or
     /// - has an invalid remote reference (e.g. relying on the database's
     ///   referential integrity), or
-    /// - uses [`before_save()`](trait.BeforeSave.html#tymethod.before_save)
+    /// - uses [`validate()`](trait.Validate.html#tymethod.validate) or
+    ///   [`before_save()`](trait.BeforeSave.html#tymethod.before_save)
     ///   which returns an error, or
+    /// - uses [`after_save()`](trait.AfterSave.html#tymethod.after_save)
+    ///   which returns an error, in which case the insert should be rolled
+    ///   back, or
     /// - for application specific reasons.
     ///
+    /// The method generated by the derive macro calls the hooks for each
+    /// object in `data`, `after_save()` for the inserted objects that are
+    /// returned.
+    ///
     /// The method generated by the derive macro always returns
     /// [`Err(Error::Other("not-available")`
     /// ](../error/enum.Error.html#variant.Other) if the model has the
@@ -288,6 +551,11 @@ pub trait ActiveRecord: Sized {
     /// or [`vicocomo_random`](../derive.ActiveRecord.html#vicocomo_random)
     /// will be sent to the database only if it is `Some`.
     ///
+    /// If the model has a field with the attribute [`vicocomo_original`
+    /// ](../derive.ActiveRecord.html#vicocomo_original), the implementation
+    /// by the derive macro sends only the changed columns, and nothing at all
+    /// if none of them changed.
+    ///
     /// The implementation should ensure referential integrity, see
     /// [`insert_batch()`](#tymethod.insert_batch).
     ///
@@ -298,8 +566,12 @@ pub trait ActiveRecord: Sized {
     /// - does not have a primary key that exists in the database, or
     /// - has an invalid remote reference (e.g. relying on the database's
     ///   referential integrity), or
-    /// - uses [`before_save()`](trait.BeforeSave.html#tymethod.before_save)
+    /// - uses [`validate()`](trait.Validate.html#tymethod.validate) or
+    ///   [`before_save()`](trait.BeforeSave.html#tymethod.before_save)
     ///   which returns an error, or
+    /// - uses [`after_save()`](trait.AfterSave.html#tymethod.after_save)
+    ///   which returns an error, in which case the update should be rolled
+    ///   back, or
     /// - for application specific reasons.
     ///
     /// The method generated by the derive macro always returns
@@ -322,8 +594,9 @@ pub trait ActiveRecord: Sized {
     ///
     /// <b>Note</b> that this function updates directly to the database and
     /// should
-    /// - not call [`before_save()`
-    ///   ](trait.BeforeSave.html#tymethod.before_save),
+    /// - not call [`validate()`](trait.Validate.html#tymethod.validate),
+    ///   [`before_save()`](trait.BeforeSave.html#tymethod.before_save), or
+    ///   [`after_save()`](trait.AfterSave.html#tymethod.after_save),
     /// - ignore the visibility of the fields in `self` corresponding to the
     ///   `cols`,
     /// - send data to the database ignoring
@@ -448,7 +721,12 @@ pub trait ActiveRecord: Sized {
     ///   characters that appear in the column names.
     /// - A leading header line with names of columns in [`Self::table()`
     ///   ](#tymethod.table) is <b>required</b>.
-    /// - Only string and numeric values are accepted.
+    /// - Only string, numeric, boolean, and binary values are accepted.
+    ///   Binary values are hexadecimal strings, e.g. `"0a1b"`.
+    /// - Values of [native](../derive.ActiveRecord.html#vicocomo_native)
+    ///   boolean columns are `true`, `false`, `t`, `f`, `1`, or `0`, case
+    ///   insensitive. Values of native date and time columns are ISO 8601
+    ///   strings, e.g. `"2024-02-29 12:34:56"`.
     /// - An empty value is converted to `NULL`, so `,"",` -> `''` but `,,` ->
     ///   `NULL`.
     /// - There is no check that the statement is valid SQL.
@@ -601,7 +879,7 @@ pub trait ActiveRecord: Sized {
             ))
         })?;
         let mut sql = format!("INSERT INTO {} (", Self::table());
-        let mut is_text = Vec::new();
+        let mut col_types = Vec::new();
         let mut col_ix = 0usize;
         let mut rest = csv;
         let (line, more) = get_line(rest, sep)?;
@@ -612,8 +890,8 @@ pub trait ActiveRecord: Sized {
                     return Err(Error::invalid_input("invalid-csv-header"));
                 }
                 let nam = nam.unwrap();
-                is_text.push(match Self::col_type(nam) {
-                    Some(t) => t == DbType::Text || t == DbType::NulText,
+                col_types.push(match Self::col_type(nam) {
+                    Some(t) => t,
                     None => {
                         return Err(Error::invalid_input(
                             "invalid-csv-header",
@@ -643,15 +921,56 @@ pub trait ActiveRecord: Sized {
                 if col_ix > 0 {
                     sql += ", ";
                 }
-                match is_text.get(col_ix) {
-                    Some(quote_needed) => match val {
-                        Some(v) => {
-                            if *quote_needed {
+                match col_types.get(col_ix) {
+                    Some(col_type) => match val {
+                        Some(v) => match col_type {
+                            DbType::Date
+                            | DbType::DateTime
+                            | DbType::Text
+                            | DbType::Time
+                            | DbType::NulDate
+                            | DbType::NulDateTime
+                            | DbType::NulText
+                            | DbType::NulTime => {
                                 sql = sql + "'" + &requote(v) + "'";
-                            } else {
-                                sql += v;
                             }
-                        }
+                            DbType::Bool | DbType::NulBool => {
+                                sql += match v.to_lowercase().as_str() {
+                                    "true" | "t" | "1" => "TRUE",
+                                    "false" | "f" | "0" => "FALSE",
+                                    _ => {
+                                        return Err(Error::invalid_input(
+                                            format!(
+                                                "{}: {:?}",
+                                                t!("invalid-csv"),
+                                                first_n_chars_ellipsis(
+                                                    v, 40, " ...",
+                                                ),
+                                            ),
+                                        ));
+                                    }
+                                };
+                            }
+                            DbType::Blob | DbType::NulBlob => {
+                                if v.len() % 2 == 1
+                                    || !v
+                                        .chars()
+                                        .all(|c| c.is_ascii_hexdigit())
+                                {
+                                    return Err(Error::invalid_input(
+                                        format!(
+                                            "{}: {:?}",
+                                            t!("invalid-csv"),
+                                            first_n_chars_ellipsis(
+                                                v, 40, " ...",
+                                            ),
+                                        ),
+                                    ));
+                                }
+                                sql = sql + "decode('" + v + "', 'hex')";
+                            }
+                            _ => sql += v,
+                        },
                         None => sql += "NULL",
                     },
                     None => {
@@ -772,7 +1091,9 @@ pub trait ActiveRecord: Sized {
     /// statement, optionally semicolon terminated, subject to the following
     /// conditions:
     /// - <b>No parameterization, only literal values</b>
-    /// - <b>Only numeric and string literals</b>
+    /// - <b>Only numeric, string, and boolean literals</b>, and binary
+    ///   values in the form returned by [`DbValue::sql_value()`
+    ///   ](../database/enum.DbValue.html#method.sql_value)
     ///
     /// `sep` is the separator to use in the CSV string. Default `b','`.
     ///
@@ -835,6 +1156,21 @@ pub trait ActiveRecord: Sized {
             #[regex(r"[nN][uU][lL][lL]")]
             Nul,
 
+            #[regex(
+                r"[tT][rR][uU][eE]|[fF][aA][lL][sS][eE]",
+                |lex| lex.slice().to_lowercase(),
+            )]
+            Bol(String),
+
+            #[regex(
+                r"[dD][eE][cC][oO][dD][eE]\s*\(\s*'[0-9a-fA-F]*'\s*,\s*'hex'\s*\)",
+                |lex| {
+                    let hex = lex.slice().split('\'').nth(1).unwrap();
+                    hex.to_lowercase()
+                },
+            )]
+            Hex(String),
+
             #[token(";")]
             Sem,
             // End is not a token, it denotes lexer.next().is_none()
@@ -852,6 +1188,8 @@ pub trait ActiveRecord: Sized {
             ExpectValLit,  // Val       => ""                ExpectVals
             ExpectVals,    // Lpr       => ""                ExpectVal
             ExpectVal,     // Num|Txt   => "{val}"           GotVal
+                           // Hex       => "{hex}"           GotVal
+                           // Bol       => "true"|"false"    GotVal
                            // Nul       => ""                GotVal
             GotVal,        // Com       => "{sep}"           ExpectVal
                            // Rpr       => "{CRLF}"          GotVals
@@ -936,10 +1274,14 @@ pub trait ActiveRecord: Sized {
                                 csv += lex.slice();
                                 state = State::GotVal;
                             }
-                            Tok::Txt(val) => {
+                            Tok::Txt(val) | Tok::Hex(val) => {
                                 csv = csv + "\"" + &val + "\"";
                                 state = State::GotVal;
                             }
+                            Tok::Bol(val) => {
+                                csv += &val;
+                                state = State::GotVal;
+                            }
                             Tok::Nul => state = State::GotVal,
                             _ => state = State::Error,
                         },
@@ -986,17 +1328,76 @@ pub trait ActiveRecord: Sized {
     /// Returns the contents of the table corresponding to `Self` in a pair
     /// `(` *table name* `,` *a string suitable for saving as a CSV file* `)`.
     ///
-    /// This is simply [`try_sql_to_csv`
-    /// ](#method.try_sql_to_csv)[`(try_to_sql()`
-    /// ](#method.try_to_sql)`, sep, true)`.
-    ///
-    /// See those.
+    /// This is simply [`try_write_csv()`](#method.try_write_csv) to a
+    /// `Vec<u8>`, see that.
     ///
     fn try_to_csv(
         db: DatabaseIf,
         sep: Option<u8>,
     ) -> Result<(String, String), Error> {
-        Self::try_sql_to_csv(&Self::try_to_sql(db)?, sep, true)
+        let mut csv = Vec::new();
+        let table = Self::try_write_csv(db, sep, &mut csv)?;
+        Ok((table, map_error!(InvalidInput, String::from_utf8(csv))?))
+    }
+
+    /// Write the contents of the table corresponding to `Self` to `out` as
+    /// CSV, one record at a time using [`for_each()`
+    /// ](#method.for_each), and return the table name. [Soft deleted
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete) objects are
+    /// included.
+    ///
+    /// The CSV is what [`try_sql_to_csv`
+    /// ](#method.try_sql_to_csv)[`(try_to_sql()`
+    /// ](#method.try_to_sql)`, sep, true)` would return, i.e. a header line
+    /// followed by a line for each record, or only `"\r\n"` if the table is
+    /// empty. See those.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Err(Error::Other)`](../error/enum.Error.html#variant.Other)
+    /// if writing to `out` fails.
+    ///
+    /// Otherwise see [`try_sql_to_csv()`](#method.try_sql_to_csv) and
+    /// [`values()`](#tymethod.values).
+    ///
+    fn try_write_csv(
+        db: DatabaseIf,
+        sep: Option<u8>,
+        out: &mut dyn Write,
+    ) -> Result<String, Error> {
+        let table = Self::table();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ",
+            table,
+            Self::columns().join(", "),
+        );
+        // the header line is written before the first record
+        let mut headers = true;
+        let count = Self::for_each(
+            db,
+            &QueryBld::new().with_deleted().query().unwrap(),
+            |obj| {
+                let (_, csv) = Self::try_sql_to_csv(
+                    &format!(
+                        "{}({})",
+                        insert,
+                        obj.values()?
+                            .iter()
+                            .map(|v| v.sql_value())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                    sep,
+                    headers,
+                )?;
+                headers = false;
+                map_error!(Other, out.write_all(csv.as_bytes()))
+            },
+        )?;
+        if count == 0 {
+            map_error!(Other, out.write_all(b"\r\n"))?;
+        }
+        Ok(table)
     }
 
     /// Returns the contents of the table corresponding to `Self` in the form
@@ -1004,6 +1405,9 @@ pub trait ActiveRecord: Sized {
     /// `INSERT INTO `*table name*` (`*column*`, `...`) VALUES (`*value*`, `...`), `...`;`
     /// If the table is empty, an empty string is returned.
     ///
+    /// Like [`try_write_csv()`](#method.try_write_csv), this includes [soft
+    /// deleted](../derive.ActiveRecord.html#vicocomo_soft_delete) objects.
+    ///
     /// <b>Errors</b>
     ///
     /// See [`values()`](#tymethod.values).
@@ -1018,7 +1422,10 @@ pub trait ActiveRecord: Sized {
             &Self::columns().join(", "),
             {
                 let mut vals = Vec::new();
-                for obj in &Self::load(db)? {
+                for obj in &Self::query(
+                    db,
+                    &QueryBld::new().with_deleted().query().unwrap(),
+                )? {
                     vals.push(format!(
                         "({})",
                         obj.values()?
@@ -1032,6 +1439,140 @@ pub trait ActiveRecord: Sized {
             },
         ))
     }
+
+    //- Functions describing the database schema ---------------------------//
+
+    //- Required - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -//
+
+    /// The join tables of the many-to-many associations declared by
+    /// [`vicocomo_has_many`](../derive.ActiveRecord.html#vicocomo_has_many--).
+    ///
+    fn join_table_schemas() -> Vec<TableSchema>;
+
+    /// The foreign keys in other tables that refer to this model, as
+    /// declared by [`vicocomo_has_many`
+    /// ](../derive.ActiveRecord.html#vicocomo_has_many--).
+    ///
+    /// This is how [`table_schema()`](#tymethod.table_schema) of another
+    /// model knows the `on_delete` of its foreign keys.
+    ///
+    fn referencing_keys() -> Vec<ForeignKeySchema>;
+
+    /// A description of the table storing the model, derived from the field
+    /// types and attributes.
+    ///
+    fn table_schema() -> TableSchema;
+
+    //- Provided - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -//
+
+    /// A `CREATE TABLE` statement for [`table_schema()`
+    /// ](#tymethod.table_schema), see [`TableSchema::create_table_sql()`
+    /// ](../schema/struct.TableSchema.html#method.create_table_sql).
+    ///
+    /// To create a complete schema in dependency order, including join
+    /// tables, use [`define_schema!()`](../macro.define_schema.html).
+    ///
+    fn create_table_sql(dialect: SqlDialect) -> String {
+        Self::table_schema().create_table_sql(dialect)
+    }
+
+    /// Verify that the table storing the model in `db` matches
+    /// [`table_schema()`](#tymethod.table_schema), see
+    /// [`TableSchema::verify()`
+    /// ](../schema/struct.TableSchema.html#method.verify).
+    ///
+    /// Also verifies the [`join_table_schemas()`
+    /// ](#tymethod.join_table_schemas) and [`referencing_keys()`
+    /// ](#tymethod.referencing_keys). If the model is [`readonly()`
+    /// ](#tymethod.readonly), only missing columns are reported.
+    ///
+    /// To verify many models, see [`verify_all!()`
+    /// ](../macro.verify_all.html).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns all mismatches found. If the database adapter does not
+    /// implement [`DbConn::table_schema()`
+    /// ](../database/trait.DbConn.html#method.table_schema), each table is
+    /// reported as [`Unverifiable`
+    /// ](../schema/enum.SchemaMismatch.html#variant.Unverifiable).
+    ///
+    fn verify_schema(db: DatabaseIf) -> Result<(), Vec<SchemaMismatch>> {
+        let readonly = Self::readonly();
+        let mut result = Self::table_schema().verify(db.clone(), readonly);
+        if !readonly {
+            let found = Self::join_table_schemas()
+                .into_iter()
+                .flat_map(|join| join.verify(db.clone(), false))
+                .chain(
+                    Self::referencing_keys()
+                        .into_iter()
+                        .flat_map(|key| key.verify(db.clone())),
+                );
+            for mismatch in found {
+                if !result.contains(&mismatch) {
+                    result.push(mismatch);
+                }
+            }
+        }
+        if result.is_empty() {
+            Ok(())
+        } else {
+            Err(result)
+        }
+    }
+}
+
+// === AfterDelete ===========================================================
+
+/// A hook called by the [`delete()` function
+/// ](trait.ActiveRecord.html#tymethod.delete) as implemented by the
+/// [`ActiveRecord` derive macro](../derive.ActiveRecord.html).
+///
+pub trait AfterDelete {
+    /// Do whatever necessary after deleting `self` from the database.
+    ///
+    /// Called in the same transaction as the deletion, so an `Err` return
+    /// value means that the deletion is rolled back, and [`delete()`
+    /// ](trait.ActiveRecord.html#tymethod.delete) should return `Err` as
+    /// well.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// On error return, the variant should be an [`Error::Model`
+    /// ](../error/enum.Error.html#variant.Model) indicating what the problem
+    /// is.
+    ///
+    fn after_delete(&mut self, db: DatabaseIf) -> Result<(), Error>;
+}
+
+// === AfterSave =============================================================
+
+/// A hook called by the [`insert()`](trait.ActiveRecord.html#method.insert)
+/// and [`update()`](trait.ActiveRecord.html#tymethod.update) functions as
+/// implemented by the [`ActiveRecord` derive macro
+/// ](../derive.ActiveRecord.html).
+///
+pub trait AfterSave {
+    /// Do whatever necessary after saving `self` to the database.
+    ///
+    /// `self` is updated from the saved row, e.g. with a database generated
+    /// primary key.
+    ///
+    /// Called in the same transaction as the save, so an `Err` return value
+    /// means that the save is rolled back, and [`insert()`
+    /// ](trait.ActiveRecord.html#method.insert), [`save()`
+    /// ](trait.ActiveRecord.html#method.save), and [`update()`
+    /// ](trait.ActiveRecord.html#tymethod.update) should return `Err` as
+    /// well.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// On error return, the variant should be an [`Error::Model`
+    /// ](../error/enum.Error.html#variant.Model) indicating what the problem
+    /// is.
+    ///
+    fn after_save(&mut self, db: DatabaseIf) -> Result<(), Error>;
 }
 
 // === BeforeDelete ==========================================================
@@ -1130,6 +1671,21 @@ pub trait BeforeSave {
 /// Forwards any errors from [`try_to_csv()`
 /// ](#trait.ActiveRecord.html#method.try_to_csv).
 ///
+/// ##### `pub fn backup_to(db: `[`DatabaseIf`](../database/struct.DatabaseIf.html)`, out: &mut dyn std::io::Write) -> Result<(), `[`Error`](../error/enum.Error.html)`>`
+///
+/// Write the same contents as `backup()` returns to `out`, one record at a
+/// time using [`try_write_csv()`
+/// ](#trait.ActiveRecord.html#method.try_write_csv), so that large tables
+/// are not kept in memory. `backup()` is simply `backup_to()` a `Vec<u8>`.
+///
+/// <b>Errors</b>
+///
+/// Returns [`Err(Error::Other)`](../error/enum.Error.html#variant.Other)
+/// if writing to `out` fails.
+///
+/// Forwards any errors from [`try_write_csv()`
+/// ](#trait.ActiveRecord.html#method.try_write_csv).
+///
 /// ##### `pub fn restore(db: `[`DatabaseIf`](../database/struct.DatabaseIf.html)`, source: &[u8]) -> Result<(), `[`Error`](../error/enum.Error.html)`>`
 ///
 /// Restore the data of the given `$model`s from `source` using their
@@ -1161,22 +1717,39 @@ macro_rules! define_backup_and_restore {
         pub fn backup(
             db: ::vicocomo::DatabaseIf,
         ) -> Result<Vec<u8>, ::vicocomo::Error> {
-            let mut result = ::vicocomo::backup_version().into_bytes();
-            result.extend_from_slice(b"\r\n");
+            let mut result = Vec::new();
+            backup_to(db, &mut result)?;
+            Ok(result)
+        }
+
+        pub fn backup_to(
+            db: ::vicocomo::DatabaseIf,
+            out: &mut dyn ::std::io::Write,
+        ) -> Result<(), ::vicocomo::Error> {
+            ::vicocomo::map_error!(
+                Other,
+                out.write_all(
+                    (::vicocomo::backup_version() + "\r\n").as_bytes()
+                ),
+            )?;
         $(  {
-                let (table, csv) =
-                    <$model as ::vicocomo::ActiveRecord>::try_to_csv(
-                        db.clone(),
-                        None,
-                    )?;
-                result.extend_from_slice(
-                    (::std::string::String::from("--- ") + &table + " ---\r\n"
-                    ).as_bytes()
-                );
-                result.extend_from_slice(csv.as_bytes());
+                ::vicocomo::map_error!(
+                    Other,
+                    out.write_all(
+                        (::std::string::String::from("--- ")
+                            + &<$model as ::vicocomo::ActiveRecord>::table()
+                            + " ---\r\n"
+                        ).as_bytes()
+                    ),
+                )?;
+                <$model as ::vicocomo::ActiveRecord>::try_write_csv(
+                    db.clone(),
+                    None,
+                    out,
+                )?;
             }
         )*
-            Ok(result)
+            Ok(())
         }
 
         pub fn restore(
@@ -1276,6 +1849,47 @@ pub enum Order {
     NoOrder,
 }
 
+// === Original ==============================================================
+
+/// The column values of a model object as last loaded from or saved to the
+/// database, held by a field with the derive macro attribute
+/// [`vicocomo_original`](../derive.ActiveRecord.html#vicocomo_original).
+///
+/// Create with `Original::default()`, meaning that the object is not loaded
+/// from the database, so that all columns are considered changed.
+///
+/// Two `Original`s are always equal, so that deriving `PartialEq` for the
+/// model compares only the data.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Original(Option<Vec<Option<DbValue>>>);
+
+impl Original {
+    #[doc(hidden)]
+    pub fn new(values: Vec<Option<DbValue>>) -> Self {
+        Self(Some(values))
+    }
+
+    /// True iff the object was loaded from or saved to the database.
+    ///
+    pub fn is_loaded(&self) -> bool {
+        self.0.is_some()
+    }
+
+    #[doc(hidden)]
+    pub fn values(&self) -> Option<&[Option<DbValue>]> {
+        self.0.as_deref()
+    }
+}
+
+impl Eq for Original {}
+
+impl PartialEq for Original {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
 // === Query =================================================================
 
 /// A reusable query for
@@ -1286,6 +1900,8 @@ pub enum Order {
 pub struct Query {
     // The meat of a WHERE clause - no `WHERE`!
     filter: Option<String>,
+    // The names of the associations to join.
+    joins: Vec<String>,
     // The limit to send to the database.
     limit: Option<usize>,
     // The offset to send to the database.
@@ -1294,13 +1910,15 @@ pub struct Query {
     order: Order,
     // The values to put in the database query.
     values: Vec<Option<DbValue>>,
+    // Include soft deleted objects.
+    with_deleted: bool,
 }
 
 impl Query { // --------------------------------------------------------------
     /// Create a [query builder](struct.QueryBld.html) to extend `self`.
     ///
     pub fn builder(self) -> QueryBld {
-        QueryBld(self, QbState::Valid)
+        QueryBld(self, QbState::Valid, 0)
     }
 
     /// The meat of a WHERE clause - no `WHERE`!
@@ -1309,6 +1927,13 @@ impl Query { // --------------------------------------------------------------
         self.filter.as_ref().map(|s| s.as_str())
     }
 
+    /// The names of the associations to join, see [`QueryBld::join()`
+    /// ](struct.QueryBld.html#method.join).
+    ///
+    pub fn joins(&self) -> &[String] {
+        self.joins.as_slice()
+    }
+
     /// The limit to send to the database.
     ///
     pub fn limit(&self) -> Option<usize> {
@@ -1374,6 +1999,41 @@ impl Query { // --------------------------------------------------------------
     pub fn values(&self) -> &[Option<DbValue>] {
         self.values.as_slice()
     }
+
+    /// Whether to include soft deleted objects, see [`QueryBld::
+    /// with_deleted()`](struct.QueryBld.html#method.with_deleted).
+    ///
+    pub fn with_deleted(&self) -> bool {
+        self.with_deleted
+    }
+
+    // private - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+    // The WHERE clause, possibly empty, and the values, which must be set.
+    // Joins are not supported.
+    fn filter_sql(&self) -> Result<(String, Vec<DbValue>), Error> {
+        if !self.joins.is_empty() {
+            return Err(Error::invalid_input("database--Query--joins"));
+        }
+        let mut values: Vec<DbValue> = Vec::new();
+        for opt in &self.values {
+            match opt {
+                Some(v) => values.push(v.clone()),
+                None => {
+                    return Err(Error::invalid_input(
+                        "database--Query--value-missing",
+                    ))
+                }
+            }
+        }
+        Ok((
+            self.filter
+                .as_ref()
+                .map(|f| format!("WHERE {}", f))
+                .unwrap_or_default(),
+            values,
+        ))
+    }
 }
 
 // === QueryBld ==============================================================
@@ -1399,36 +2059,44 @@ impl Query { // --------------------------------------------------------------
 /// query.set_values(&[DbValue::Int(42), DbValue::Text("bar")]); // No Some()!
 /// query.set_limit(Some(4));    // The limit may be changed ...
 /// query.set_limit(None);       // ... or removed (the offset, too)
+///
+/// let query =
+/// QueryBld::new()
+/// .begin_group()               // "(" where a condition is expected
+/// .col("c1")
+/// .in_list(&[DbValue::Int(1), DbValue::Int(2)]) // "c1 IN ($1, $2)"
+/// .or("c1")
+/// .is_null()
+/// .end_group()                 // ")"
+/// .and_group()                 // " AND ("
+/// .col("c2")
+/// .between(None, None)         // "c2 BETWEEN $3 AND $4"
+/// .or("c3")
+/// .ilike(None)                 // "LOWER(c3) LIKE LOWER($5)"
+/// .end_group()
+/// .query().unwrap();
 /// ```
 ///
-/// Function sequences that do not make sense, e.g. `new().and()` or
-/// `and().`*any function except a relational operator* will make [`query()`
-/// ](struct.QueryBld.html#method.query) return None.
+/// Function sequences that do not make sense, e.g. `new().and()`,
+/// `and().`*any function except a relational operator*, or groups that are
+/// not balanced, will make [`query()`](struct.QueryBld.html#method.query)
+/// return None.
 ///
 /// For more complicated WHERE clauses, use the catch-all [`filter()`
 /// ](struct.QueryBld.html#method.filter).
 ///
 #[derive(Clone, Debug)]
-pub struct QueryBld(Query, QbState);
+pub struct QueryBld(Query, QbState, usize); // usize: number of open groups
 
 // --- QueryBld private macros -----------------------------------------------
 
 macro_rules! where_rel_op {
     ($( #[$meta:meta] )* $op_fn:ident, $op_str:literal) => {
         $( #[$meta] )*
-        pub fn $op_fn(mut self, value: Option<&DbValue>) -> Self {
-            match self.1 {
-                QbState::GotCol => {
-                    self.0.filter.as_mut().unwrap().push_str(&format!(
-                        concat!(" ", $op_str, " ${}"),
-                        self.0.values.len() + 1,
-                    ));
-                    self.0.values.push(value.map(|v| v.clone()));
-                    self.1 = QbState::Valid;
-                    self
-                },
-                _ => self.invalidate(),
-            }
+        pub fn $op_fn(self, value: Option<&DbValue>) -> Self {
+            self.condition(&[value], |col, ix| {
+                format!(concat!("{} ", $op_str, " ${}"), col, ix)
+            })
         }
     };
 }
@@ -1443,8 +2111,7 @@ macro_rules! where_log_op {
                     self.0.filter.as_mut().unwrap().push_str(
                         concat!(" ", $op_str, " ")
                     );
-                    self.0.filter.as_mut().unwrap().push_str(db_col);
-                    self.1 = QbState::GotCol;
+                    self.1 = QbState::GotCol(db_col.to_string());
                     self
                 },
                 _ => self.invalidate(),
@@ -1463,12 +2130,15 @@ impl QueryBld { // -----------------------------------------------------------
         Self(
             Query {
                 filter: None,
+                joins: Vec::new(),
                 limit: None,
                 offset: None,
                 order: Order::Dflt,
                 values: Vec::new(),
+                with_deleted: false,
             },
             QbState::Valid,
+            0,
         )
     }
 
@@ -1482,15 +2152,77 @@ impl QueryBld { // -----------------------------------------------------------
         and, "AND"
     }
 
-    /// Initiate building the first WHERE condition.
+    /// Open a group of WHERE conditions AND-ed to the previous, i.e.
+    /// `" AND ("`. Continue with [`col()`](#method.col) or
+    /// [`begin_group()`](#method.begin_group).
+    ///
+    pub fn and_group(self) -> Self {
+        self.log_op_group(" AND (")
+    }
+
+    /// Open a group of WHERE conditions, i.e. `"("`, where a condition is
+    /// expected: first, or after [`and_group()`](#method.and_group),
+    /// [`or_group()`](#method.or_group), or `begin_group()`.
+    ///
+    pub fn begin_group(mut self) -> Self {
+        match self.1 {
+            QbState::Valid if self.0.filter.is_none() => {
+                self.0.filter = Some("(".to_string());
+            }
+            QbState::GroupStart => {
+                self.0.filter.as_mut().unwrap().push('(');
+            }
+            _ => return self.invalidate(),
+        }
+        self.1 = QbState::GroupStart;
+        self.2 += 1;
+        self
+    }
+
+    /// Complete building a WHERE condition `"`*column*` BETWEEN `*low*` AND
+    /// `*high*`"`.
+    ///
+    /// `low` and `high` are the values to use or `None` for a reusable
+    /// [`Query`](struct.Query.html).
+    ///
+    pub fn between(
+        self,
+        low: Option<&DbValue>,
+        high: Option<&DbValue>,
+    ) -> Self {
+        self.condition(&[low, high], |col, ix| {
+            format!("{} BETWEEN ${} AND ${}", col, ix, ix + 1)
+        })
+    }
+
+    /// Initiate building the first WHERE condition, or the first condition
+    /// in a group.
     ///
     /// `db_col` is the column name in the database.
     ///
     pub fn col(mut self, db_col: &str) -> Self {
         match self.1 {
             QbState::Valid if self.0.filter.is_none() => {
-                self.0.filter = Some(db_col.to_string());
-                self.1 = QbState::GotCol;
+                self.1 = QbState::GotCol(db_col.to_string());
+                self
+            }
+            QbState::GroupStart => {
+                self.1 = QbState::GotCol(db_col.to_string());
+                self
+            }
+            _ => self.invalidate(),
+        }
+    }
+
+    /// Close the innermost group opened by [`begin_group()`
+    /// ](#method.begin_group), [`and_group()`](#method.and_group), or
+    /// [`or_group()`](#method.or_group), i.e. `")"`.
+    ///
+    pub fn end_group(mut self) -> Self {
+        match self.1 {
+            QbState::Valid if self.2 > 0 => {
+                self.0.filter.as_mut().unwrap().push(')');
+                self.2 -= 1;
                 self
             }
             _ => self.invalidate(),
@@ -1518,9 +2250,11 @@ impl QueryBld { // -----------------------------------------------------------
     ///
     /// `values` are the new parameter values, appended to any existing.
     ///
+    /// Not allowed inside a group.
+    ///
     pub fn filter(mut self, fltr: &str, values: &[Option<DbValue>]) -> Self {
         match self.1 {
-            QbState::Valid => {
+            QbState::Valid if self.2 == 0 => {
                 self.0.filter = Some(match self.0.filter {
                     Some(old_filter) => {
                         // add old parameter count to new parameter indexes
@@ -1575,6 +2309,41 @@ impl QueryBld { // -----------------------------------------------------------
         gt, ">"
     }
 
+    /// Complete building a case insensitive WHERE condition
+    /// `"LOWER(`*column*`) LIKE LOWER(`*pattern*`)"`.
+    ///
+    /// `pattern` is the pattern to use or `None` for a reusable [`Query`
+    /// ](struct.Query.html).
+    ///
+    pub fn ilike(self, pattern: Option<&DbValue>) -> Self {
+        self.condition(&[pattern], |col, ix| {
+            format!("LOWER({}) LIKE LOWER(${})", col, ix)
+        })
+    }
+
+    /// Complete building a WHERE condition `"`*column*` IN (`*values*`)"`.
+    ///
+    /// `values` are the values to use. They may be replaced for a reusable
+    /// [`Query`](struct.Query.html), but their number is fixed.
+    ///
+    /// If `values` is empty the condition is always false.
+    ///
+    pub fn in_list(self, values: &[DbValue]) -> Self {
+        self.list_condition("IN", "1 = 0", values)
+    }
+
+    /// Complete building a WHERE condition `"`*column*` IS NOT NULL"`.
+    ///
+    pub fn is_not_null(self) -> Self {
+        self.condition(&[], |col, _| format!("{} IS NOT NULL", col))
+    }
+
+    /// Complete building a WHERE condition `"`*column*` IS NULL"`.
+    ///
+    pub fn is_null(self) -> Self {
+        self.condition(&[], |col, _| format!("{} IS NULL", col))
+    }
+
     where_rel_op! {
         /// Complete building a WHERE condition.
         ///
@@ -1584,6 +2353,46 @@ impl QueryBld { // -----------------------------------------------------------
         le, "<="
     }
 
+    where_rel_op! {
+        /// Complete building a WHERE condition.
+        ///
+        /// `value` is the pattern to use or `None` for a reusable [`Query`
+        /// ](struct.Query.html). Note that the case sensitivity differs
+        /// between databases, see also [`ilike()`](#method.ilike).
+        ///
+        like, "LIKE"
+    }
+
+    /// Join the table of an association declared by [`vicocomo_belongs_to`
+    /// ](../derive.ActiveRecord.html#vicocomo_belongs_to) or
+    /// [`vicocomo_has_many`](../derive.ActiveRecord.html#vicocomo_has_many),
+    /// so that the WHERE and ORDER clauses may use its columns.
+    ///
+    /// `name` is the snake cased name of the function that the derive macro
+    /// generates to get the associated object(s), e.g. `"customer"` for a
+    /// belongs-to association or `"orders"` for a has-many association.
+    ///
+    /// The joined table is aliased `name` and the model's own table `this`,
+    /// so a condition may be e.g. `"customer.country = $1"`. A many-to-many
+    /// join table is aliased `name__join`. Each object is returned at most
+    /// once, also if joining a has-many association. If the order refers to
+    /// the columns of a has-many association, an object is placed by the
+    /// first of its associated rows in that order.
+    ///
+    /// Joining the same association more than once has no effect.
+    ///
+    /// Joins are supported by the implementation of [`ActiveRecord`
+    /// ](trait.ActiveRecord.html) generated by the derive macro. An unknown
+    /// `name` will make e.g. [`ActiveRecord::query()`
+    /// ](trait.ActiveRecord.html#tymethod.query) return an error.
+    ///
+    pub fn join(mut self, name: &str) -> Self {
+        if !self.0.joins.iter().any(|j| j == name) {
+            self.0.joins.push(name.to_string());
+        }
+        self
+    }
+
     /// Set a limit on the number of returned objects.
     ///
     /// `limit` is the limit to use.
@@ -1611,6 +2420,16 @@ impl QueryBld { // -----------------------------------------------------------
         ne, "<>"
     }
 
+    /// Complete building a WHERE condition
+    /// `"`*column*` NOT IN (`*values*`)"`, see [`in_list()`
+    /// ](#method.in_list).
+    ///
+    /// If `values` is empty the condition is always true.
+    ///
+    pub fn not_in(self, values: &[DbValue]) -> Self {
+        self.list_condition("NOT IN", "1 = 1", values)
+    }
+
     /// Remove the ORDER clause, e.g. to avoid default ordering.
     ///
     pub fn no_order(mut self) -> Self {
@@ -1635,6 +2454,13 @@ impl QueryBld { // -----------------------------------------------------------
         or, "OR"
     }
 
+    /// Open a group of WHERE conditions OR-ed to the previous, see
+    /// [`and_group()`](#method.and_group).
+    ///
+    pub fn or_group(self) -> Self {
+        self.log_op_group(" OR (")
+    }
+
     /// Define an ORDER clause.
     ///
     /// `order` is the meat of the ORDER clause - no `ORDER BY`!
@@ -1651,7 +2477,7 @@ impl QueryBld { // -----------------------------------------------------------
     ///
     pub fn query(self) -> Option<Query> {
         match self.1 {
-            QbState::Valid => Some(self.0),
+            QbState::Valid if self.2 == 0 => Some(self.0),
             _ => {
                 self.invalidate();
                 None
@@ -1659,12 +2485,109 @@ impl QueryBld { // -----------------------------------------------------------
         }
     }
 
+    /// Include objects that are [soft deleted
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete), which are
+    /// otherwise excluded by the implementation of [`ActiveRecord`
+    /// ](trait.ActiveRecord.html) generated by the derive macro. Also soft
+    /// deleted objects in [joined](#method.join) tables are included.
+    ///
+    pub fn with_deleted(mut self) -> Self {
+        self.0.with_deleted = true;
+        self
+    }
+
     // private - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
+    // Complete a WHERE condition on the column given to col(), and(), or
+    // or(). cond() gets the column and the index of the first value.
+    fn condition<F>(mut self, values: &[Option<&DbValue>], cond: F) -> Self
+    where
+        F: FnOnce(&str, usize) -> String,
+    {
+        match std::mem::replace(&mut self.1, QbState::Valid) {
+            QbState::GotCol(col) => {
+                let cond = cond(&col, self.0.values.len() + 1);
+                match self.0.filter.as_mut() {
+                    Some(filter) => filter.push_str(&cond),
+                    None => self.0.filter = Some(cond),
+                }
+                self.0.values.extend(values.iter().map(|v| v.cloned()));
+                self
+            }
+            _ => self.invalidate(),
+        }
+    }
+
     fn invalidate(mut self) -> Self {
         self.1 = QbState::Invalid;
         self
     }
+
+    fn list_condition(
+        self,
+        op: &str,
+        empty: &str,
+        values: &[DbValue],
+    ) -> Self {
+        let values = values.iter().map(Some).collect::<Vec<_>>();
+        self.condition(&values, |col, ix| {
+            if values.is_empty() {
+                empty.to_string()
+            } else {
+                format!(
+                    "{} {} ({})",
+                    col,
+                    op,
+                    (ix..(ix + values.len()))
+                        .map(|i| format!("${}", i))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+            }
+        })
+    }
+
+    fn log_op_group(mut self, op: &str) -> Self {
+        match self.1 {
+            QbState::Valid if self.0.filter.is_some() => {
+                self.0.filter.as_mut().unwrap().push_str(op);
+                self.1 = QbState::GroupStart;
+                self.2 += 1;
+                self
+            }
+            _ => self.invalidate(),
+        }
+    }
+}
+
+// === Validate ==============================================================
+
+/// A hook called by the [`insert()`](trait.ActiveRecord.html#method.insert)
+/// and [`update()`](trait.ActiveRecord.html#tymethod.update) functions as
+/// implemented by the [`ActiveRecord` derive macro
+/// ](../derive.ActiveRecord.html), before [`before_save()`
+/// ](trait.BeforeSave.html#tymethod.before_save).
+///
+pub trait Validate {
+    /// Check that `self` may be saved to the database.
+    ///
+    /// Rather than stopping at the first problem, add an error to `errors`
+    /// for each problem found, e.g. by [`ModelError::add_field_error()`
+    /// ](../error/struct.ModelError.html#method.add_field_error). If
+    /// `errors` [has errors
+    /// ](../error/struct.ModelError.html#method.has_errors) on return,
+    /// `self` cannot be saved, and [`insert()`
+    /// ](trait.ActiveRecord.html#method.insert), [`save()`
+    /// ](trait.ActiveRecord.html#method.save), and [`update()`
+    /// ](trait.ActiveRecord.html#tymethod.update) return it as an
+    /// [`Error::Model`](../error/enum.Error.html#variant.Model).
+    ///
+    /// When called by the derive macro, `errors` has the [kind
+    /// ](../error/enum.ModelErrorKind.html#variant.CannotSave) `CannotSave`
+    /// and contains any errors from [`vicocomo_required`
+    /// ](../derive.ActiveRecord.html#vicocomo_required) fields.
+    ///
+    fn validate(&self, db: DatabaseIf, errors: &mut ModelError);
 }
 
 // === functions =============================================================
@@ -1708,7 +2631,11 @@ pub fn check_backup(source: &[u8]) -> Result<&[u8], Error> {
 
 #[derive(Clone, Debug)]
 enum QbState {
+    // A complete WHERE clause so far, possibly with open groups
     Valid,
-    GotCol,
+    // Waiting for a relational operator on the column
+    GotCol(String),
+    // Waiting for the first condition in a group
+    GroupStart,
     Invalid,
 }
@@ -1,40 +1,95 @@
 //! Trait and helper types to abstract an SQL database.
 //!
-use crate::{db_value_convert, map_error, Error};
+use crate::{db_value_convert, map_error, Error, TableSchema};
 use chrono::{
     DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc,
 };
+use ljumvall_utils::first_n_chars_ellipsis;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::sync::Arc;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{sync_channel, Receiver},
+    Arc, Mutex,
+};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 /// An SQL abstraction for use by other `vicocomo` modules as well as
 /// applications.
 ///
+/// Clones share the connection as well as the transaction depth, see
+/// [`begin()`](#method.begin). If the database adapter keeps the transaction
+/// depth, see [`DbConn::transaction_depth()`
+/// ](trait.DbConn.html#method.transaction_depth), so do all interfaces to
+/// the same connection.
+///
+/// It also holds the [`Clock`](trait.Clock.html) used for timestamps, see
+/// [`with_clock()`](#method.with_clock).
+///
 #[derive(Clone)]
-pub struct DatabaseIf(Arc<dyn DbConn + Send + Sync>);
+pub struct DatabaseIf(
+    Arc<dyn DbConn + Send + Sync>,
+    Arc<AtomicUsize>,
+    Option<Arc<dyn Clock>>,
+);
 
 impl DatabaseIf {
-    /// Create an interface to `client`.
+    /// Create an interface to `client`, using a [`SystemClock`
+    /// ](struct.SystemClock.html) for timestamps.
     ///
     pub fn new(client: Arc<dyn DbConn + Send + Sync>) -> Self {
-        Self(client.clone())
+        Self(
+            client.clone(),
+            Arc::new(AtomicUsize::new(0)),
+            Some(Arc::new(SystemClock)),
+        )
     }
 
     /// Begin a transaction.
     ///
+    /// If a transaction is already in progress a savepoint is created
+    /// instead, see [`DbConn::savepoint()`
+    /// ](trait.DbConn.html#method.savepoint).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Forwards any error from the database adapter, in which case the
+    /// transaction depth is not changed.
+    ///
     pub fn begin(self) -> Result<(), Error> {
-        self.0.begin()
+        // Reserve the level before the round trip to the database, and undo
+        // it if that fails, so that the depth stays consistent.
+        let depth = self.depth().fetch_add(1, Ordering::SeqCst);
+        let result = if depth == 0 {
+            self.0.begin()
+        } else {
+            self.0.savepoint(&Self::savepoint_name(depth))
+        };
+        if result.is_err() {
+            self.leave_level();
+        }
+        result
     }
 
-    /// Commit the present transaction.
+    /// Commit the present transaction, or release the present savepoint if
+    /// this is a nested transaction.
     ///
     /// On error try to `rollback()` before returning error.
     ///
     pub fn commit(self) -> Result<(), Error> {
-        self.0.commit().map_err(|commit_err| {
-            if let Err(rollback_err) = self.0.rollback() {
+        let depth = self.leave_level();
+        let result = if depth > 0 {
+            self.0.release_savepoint(&Self::savepoint_name(depth))
+        } else {
+            self.0.commit()
+        };
+        result.map_err(|commit_err| {
+            if let Err(rollback_err) = self.rollback_level(depth) {
                 rollback_err
             } else {
                 commit_err
@@ -95,14 +150,103 @@ impl DatabaseIf {
         result
     }
 
-    /// Rollback the present transaction.
+    /// Execute an SQL query and call `f` for each row of the result, without
+    /// keeping the whole result in memory if the database adapter supports
+    /// it, see [`DbConn::query_each()`](trait.DbConn.html#method.query_each).
+    ///
+    /// For `sql`, `values`, and `types`, see [`query()`](#method.query).
+    ///
+    /// `f` must not use the database. It may stop the iteration by returning
+    /// an error.
+    ///
+    /// Returns the number of rows given to `f`.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Forwards any error from the database adapter or from `f`.
+    ///
+    pub fn query_each<F>(
+        self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+        mut f: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(Vec<DbValue>) -> Result<(), Error>,
+    {
+        self.0.query_each(sql, values, types, &mut f)
+    }
+
+    /// Execute an SQL query and return an iterator over the rows of the
+    /// result, see [`QueryIter`](struct.QueryIter.html).
+    ///
+    /// For `sql`, `values`, and `types`, see [`query()`](#method.query).
+    ///
+    pub fn query_iter(
+        self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+    ) -> QueryIter {
+        QueryIter::new(self, sql, values, types)
+    }
+
+    /// Execute an SQL query and return the result as [`DbRow`
+    /// ](struct.DbRow.html)s that know the column names.
+    ///
+    /// Unlike [`query()`](#method.query) no `types` are needed. The database
+    /// adapter chooses the `DbValue` variants from the declared or actual
+    /// column types, see [`DbConn::query_rows()`
+    /// ](trait.DbConn.html#method.query_rows).
+    ///
+    /// For `sql` and `values`, see [`query()`](#method.query).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Forwards any error from the database adapter.
+    ///
+    pub fn query_rows(
+        self,
+        sql: &str,
+        values: &[DbValue],
+    ) -> Result<Vec<DbRow>, Error> {
+        self.0.query_rows(sql, values)
+    }
+
+    /// Rollback the present transaction, or roll back to and release the
+    /// present savepoint if this is a nested transaction.
     ///
     /// <b>Errors</b>
     ///
     /// Forwards any error from the database adapter.
     ///
     pub fn rollback(self) -> Result<(), Error> {
-        self.0.rollback()
+        let depth = self.leave_level();
+        self.rollback_level(depth)
+    }
+
+    /// Statistics from the database adapter's cache of prepared statements,
+    /// see [`DbConn::statement_cache_stats()`
+    /// ](trait.DbConn.html#method.statement_cache_stats).
+    ///
+    pub fn statement_cache_stats(&self) -> Option<StatementCacheStats> {
+        self.0.statement_cache_stats()
+    }
+
+    /// A description of the table or view `table` as it is in the database,
+    /// or `None` if there is no such table, see [`DbConn::table_schema()`
+    /// ](trait.DbConn.html#method.table_schema).
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Forwards any error from the database adapter.
+    ///
+    pub fn table_schema(
+        self,
+        table: &str,
+    ) -> Result<Option<TableSchema>, Error> {
+        self.0.table_schema(table)
     }
 
     /// Wrap code in a database transaction and ensure `ROLLBACK` on any error
@@ -114,18 +258,232 @@ impl DatabaseIf {
     /// Returns what `action` returns. Before returning does a `COMMIT` or
     /// `ROLLBACK` depending on whether `action` succeeds.
     ///
+    /// Transactions may be nested. An inner transaction uses a savepoint, so
+    /// that an error only rolls back what was done inside it, and the outer
+    /// transaction is not committed until the outermost `action` returns.
+    ///
+    /// All clones of a `DatabaseIf` share one connection and one transaction
+    /// depth, so the connection must not be used from several threads during
+    /// a transaction. Any statement from another thread becomes part of the
+    /// transaction, and a transaction begun there is nested in it.
+    ///
     pub fn transaction<T, F>(self, action: F) -> Result<T, Error>
     where
         F: FnOnce(DatabaseIf) -> Result<T, Error>,
     {
-        let _ = self.0.begin();
+        let began = self.clone().begin().is_ok();
         let result = action(self.clone());
-        let _ = match result {
-            Ok(_) => self.0.commit(),
-            Err(_) => self.0.rollback(),
-        };
+        if began {
+            let _ = match result {
+                Ok(_) => self.commit(),
+                Err(_) => self.rollback(),
+            };
+        }
         result
     }
+
+    /// Like [`transaction()`](#method.transaction), but if the transaction
+    /// fails with an error that [`is_retryable()`
+    /// ](../error/enum.Error.html#method.is_retryable), e.g. a serialization
+    /// failure or a busy SQLite database, `action` is called again in a new
+    /// transaction as directed by `policy`.
+    ///
+    /// Unlike `transaction()`, an error from `COMMIT` is returned, since
+    /// that is where a serialization failure is typically detected.
+    ///
+    /// `action` must not have side effects outside the database that should
+    /// not be repeated.
+    ///
+    /// A nested call is never retried, as the retry has to be done by the
+    /// outermost transaction. The retryable error is returned instead.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns the last error from `action` or the database adapter.
+    ///
+    pub fn transaction_with_retry<T, F>(
+        self,
+        policy: &RetryPolicy,
+        mut action: F,
+    ) -> Result<T, Error>
+    where
+        F: FnMut(DatabaseIf) -> Result<T, Error>,
+    {
+        let nested = self.transaction_depth() > 0;
+        let mut attempt = 1;
+        loop {
+            let result = self.clone().begin().and_then(|_| {
+                match action(self.clone()) {
+                    Ok(value) => self.clone().commit().map(|_| value),
+                    Err(e) => {
+                        let _ = self.clone().rollback();
+                        Err(e)
+                    }
+                }
+            });
+            match result {
+                Err(e)
+                    if e.is_retryable()
+                        && !nested
+                        && attempt < policy.attempts =>
+                {
+                    thread::sleep(policy.delay(attempt));
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// The number of nested transactions presently in progress, `0` if none.
+    ///
+    pub fn transaction_depth(&self) -> usize {
+        self.depth().load(Ordering::SeqCst)
+    }
+
+    /// The present time according to the clock, or the system time if
+    /// [timestamps are turned off](#method.without_timestamps).
+    ///
+    /// Used by the generated code for the [`vicocomo_soft_delete`
+    /// ](../derive.ActiveRecord.html#vicocomo_soft_delete) attribute, since
+    /// a deleted row has to be marked also when not setting timestamps.
+    ///
+    pub fn now(&self) -> DateTime<Utc> {
+        self.timestamp().unwrap_or_else(|| SystemClock.now())
+    }
+
+    /// The present time according to the clock, or `None` if [timestamps are
+    /// turned off](#method.without_timestamps).
+    ///
+    /// Used by the generated code for the [`vicocomo_created_at`
+    /// ](../derive.ActiveRecord.html#vicocomo_created_at) and
+    /// `vicocomo_updated_at` attributes.
+    ///
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.2.as_ref().map(|clock| clock.now())
+    }
+
+    /// Use `clock` for timestamps, e.g. a [`FixedClock`
+    /// ](struct.FixedClock.html) to freeze time in tests.
+    ///
+    /// The returned interface shares the connection and transaction depth
+    /// with `self`.
+    ///
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.2 = Some(clock);
+        self
+    }
+
+    /// Do not set timestamps, e.g. when restoring data that should keep the
+    /// original values.
+    ///
+    /// The returned interface shares the connection and transaction depth
+    /// with `self`.
+    ///
+    pub fn without_timestamps(mut self) -> Self {
+        self.2 = None;
+        self
+    }
+
+    // The transaction depth counter of the adapter, or our own if none.
+    fn depth(&self) -> &AtomicUsize {
+        self.0.transaction_depth().unwrap_or(&self.1)
+    }
+
+    // Decrement the transaction depth if possible and return the new depth.
+    fn leave_level(&self) -> usize {
+        match self.depth().fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |d| d.checked_sub(1),
+        ) {
+            Ok(old) => old - 1,
+            Err(_) => 0,
+        }
+    }
+
+    // Roll back the transaction or savepoint begun at depth.
+    fn rollback_level(&self, depth: usize) -> Result<(), Error> {
+        if depth > 0 {
+            let name = Self::savepoint_name(depth);
+            self.0.rollback_to_savepoint(&name)?;
+            self.0.release_savepoint(&name)
+        } else {
+            self.0.rollback()
+        }
+    }
+
+    fn savepoint_name(depth: usize) -> String {
+        format!("__vicocomo__savepoint_{}", depth)
+    }
+}
+
+/// The source of the present time for timestamps, see [`DatabaseIf::
+/// with_clock()`](struct.DatabaseIf.html#method.with_clock).
+///
+/// Implemented by [`SystemClock`](struct.SystemClock.html) and
+/// [`FixedClock`](struct.FixedClock.html).
+///
+pub trait Clock: Send + Sync {
+    /// The present time.
+    ///
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A [`Clock`](trait.Clock.html) that returns the system time. This is the
+/// default.
+///
+#[derive(Clone, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A [`Clock`](trait.Clock.html) that returns a settable time, intended for
+/// tests. Clones share the time.
+///
+/// ```
+/// use chrono::{DateTime, TimeDelta, Utc};
+/// use vicocomo::{Clock, FixedClock};
+///
+/// let start = DateTime::<Utc>::from_timestamp(1_000_000_000, 0).unwrap();
+/// let clock = FixedClock::new(start);
+/// assert_eq!(clock.now(), start);
+/// clock.clone().advance(TimeDelta::try_seconds(17).unwrap());
+/// assert_eq!(clock.now().timestamp(), 1_000_000_017);
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct FixedClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl FixedClock {
+    /// A clock that returns `time` until changed.
+    ///
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self(Arc::new(Mutex::new(time)))
+    }
+
+    /// Move the time `delta` forward.
+    ///
+    pub fn advance(&self, delta: chrono::TimeDelta) {
+        let mut time = self.0.lock().unwrap();
+        *time = *time + delta;
+    }
+
+    /// Set the time.
+    ///
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.0.lock().unwrap() = time;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
 }
 
 /// An SQL abstraction trait for database adapter developers.
@@ -174,6 +532,56 @@ pub trait DbConn: Send + Sync {
         types: &[DbType],
     ) -> Result<Vec<Vec<DbValue>>, Error>;
 
+    /// See [`DatabaseIf::query_each()`
+    /// ](struct.DatabaseIf.html#method.query_each).
+    ///
+    /// The default method collects the rows using `query()` before calling
+    /// `f`. Implementations should override it to fetch the rows one by one
+    /// from the database.
+    ///
+    fn query_each(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+        f: &mut dyn FnMut(Vec<DbValue>) -> Result<(), Error>,
+    ) -> Result<usize, Error> {
+        let rows = self.query(sql, values, types)?;
+        let count = rows.len();
+        for row in rows {
+            f(row)?;
+        }
+        Ok(count)
+    }
+
+    /// See [`DatabaseIf::query_rows()`
+    /// ](struct.DatabaseIf.html#method.query_rows).
+    ///
+    /// Implementations should return a non-nullable variant of `DbValue` for
+    /// a value, and the nullable variant for the column's type for `NULL`.
+    ///
+    /// The default method returns [`Err(Error::Other("not-available")`
+    /// ](../error/enum.Error.html#variant.Other).
+    ///
+    fn query_rows(
+        &self,
+        _sql: &str,
+        _values: &[DbValue],
+    ) -> Result<Vec<DbRow>, Error> {
+        Err(Error::other("not-available"))
+    }
+
+    /// Release the savepoint `name`, keeping what was done after it.  The
+    /// default method simply uses `exec()` to send `RELEASE SAVEPOINT name`
+    /// to the database.
+    ///
+    fn release_savepoint(&self, name: &str) -> Result<(), Error> {
+        let sql = format!("RELEASE SAVEPOINT {}", name);
+        #[cfg(debug_assertions)]
+        eprintln!("{}", sql);
+        self.exec(&sql, &[]).map(|_| ())
+    }
+
     /// Rollback the present transaction.  The default method simply uses
     /// `exec()` to send `ROLLBACK` to the database.
     ///
@@ -182,25 +590,282 @@ pub trait DbConn: Send + Sync {
         eprintln!("ROLLBACK");
         self.exec("ROLLBACK", &[]).map(|_| ())
     }
+
+    /// Roll back what was done after the savepoint `name`.  The savepoint is
+    /// not released.  The default method simply uses `exec()` to send
+    /// `ROLLBACK TO SAVEPOINT name` to the database.
+    ///
+    fn rollback_to_savepoint(&self, name: &str) -> Result<(), Error> {
+        let sql = format!("ROLLBACK TO SAVEPOINT {}", name);
+        #[cfg(debug_assertions)]
+        eprintln!("{}", sql);
+        self.exec(&sql, &[]).map(|_| ())
+    }
+
+    /// Create the savepoint `name` inside the present transaction.  The
+    /// default method simply uses `exec()` to send `SAVEPOINT name` to the
+    /// database.
+    ///
+    fn savepoint(&self, name: &str) -> Result<(), Error> {
+        let sql = format!("SAVEPOINT {}", name);
+        #[cfg(debug_assertions)]
+        eprintln!("{}", sql);
+        self.exec(&sql, &[]).map(|_| ())
+    }
+
+    /// Statistics from the implementation's cache of prepared statements, if
+    /// any, see [`StatementCache`](struct.StatementCache.html).  The default
+    /// method returns `None`.
+    ///
+    fn statement_cache_stats(&self) -> Option<StatementCacheStats> {
+        None
+    }
+
+    /// See [`DatabaseIf::table_schema()`
+    /// ](struct.DatabaseIf.html#method.table_schema).
+    ///
+    /// Implementations should return the columns in declaration order with
+    /// the nullable `DbType` variant for a column that is not `NOT NULL`,
+    /// the primary key, the unique constraints, and the foreign keys.
+    /// Primary key columns should be reported as `NOT NULL`.
+    ///
+    /// The default method returns [`Err(Error::Other("not-available")`
+    /// ](../error/enum.Error.html#variant.Other).
+    ///
+    fn table_schema(
+        &self,
+        _table: &str,
+    ) -> Result<Option<TableSchema>, Error> {
+        Err(Error::other("not-available"))
+    }
+
+    /// The number of nested transactions in progress on the connection,
+    /// used by [`DatabaseIf`](struct.DatabaseIf.html) to decide whether to
+    /// begin a transaction or create a savepoint.
+    ///
+    /// An implementation holding a connection should keep the counter with
+    /// it, so that all `DatabaseIf`s using the connection share it, and one
+    /// wrapping another implementation should forward the call.
+    ///
+    /// The default method returns `None`, in which case each
+    /// `DatabaseIf::new()` and its clones keep a count of their own.
+    ///
+    fn transaction_depth(&self) -> Option<&AtomicUsize> {
+        None
+    }
+}
+
+/// A row returned by [`DatabaseIf::query_rows()`
+/// ](struct.DatabaseIf.html#method.query_rows), with the values in the order
+/// of the selected columns.
+///
+/// ```text
+/// for row in db.query_rows("SELECT u.name, count(*) AS n FROM ...", &[])? {
+///     let name: String = row.get("name")?;
+///     let n: i64 = row.get("n")?;
+///     // ...
+/// }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct DbRow {
+    columns: Arc<Vec<String>>,
+    values: Vec<DbValue>,
+}
+
+impl DbRow {
+    /// For database adapter developers.
+    ///
+    /// `columns` are the column names, shared by all rows in a result.
+    ///
+    /// `values` are the values, in the same order.
+    ///
+    pub fn new(columns: Arc<Vec<String>>, values: Vec<DbValue>) -> Self {
+        Self { columns, values }
+    }
+
+    /// The column names.
+    ///
+    pub fn columns(&self) -> &[String] {
+        self.columns.as_slice()
+    }
+
+    /// Convert the value of `col` to `T` using `T`'s `TryFrom<DbValue>`.
+    ///
+    /// The nullability of the value does not matter, e.g. both `Int(17)` and
+    /// `NulInt(Some(17))` convert to `i64` as well as `Option<i64>`, and any
+    /// `NULL` converts to any `Option<T>` that has a `TryFrom<DbValue>`.
+    ///
+    /// Native boolean, date, and time values convert to [`NativeField`
+    /// ](struct.NativeField.html) as well as to the plain types, e.g. a
+    /// `Bool` to `bool` and a `Date` to `NaiveDate`. Note that the plain
+    /// date and time types are stored as whole seconds.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Err(Error::InvalidInput)`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if there is no
+    /// column `col` or the value cannot be converted to `T`.
+    ///
+    pub fn get<T>(&self, col: &str) -> Result<T, Error>
+    where
+        T: TryFrom<DbValue, Error = Error>,
+    {
+        let value = self.value(col).ok_or_else(|| {
+            Error::invalid_input(format!("no column {}", col))
+        })?;
+        T::try_from(value.clone()).or_else(|err| {
+            for other in Self::alternatives(value) {
+                if let Ok(converted) = T::try_from(other) {
+                    return Ok(converted);
+                }
+            }
+            Err(err)
+        })
+    }
+
+    /// Consume the row and return the values.
+    ///
+    pub fn into_values(self) -> Vec<DbValue> {
+        self.values
+    }
+
+    /// The value of `col`, or `None` if there is no such column.
+    ///
+    pub fn value(&self, col: &str) -> Option<&DbValue> {
+        self.columns
+            .iter()
+            .position(|c| c == col)
+            .map(|ix| &self.values[ix])
+    }
+
+    /// The values in column order.
+    ///
+    pub fn values(&self) -> &[DbValue] {
+        self.values.as_slice()
+    }
+
+    // The variants that a value may be converted from, see get().
+    fn alternatives(value: &DbValue) -> Vec<DbValue> {
+        match value.to_option() {
+            Some(plain) => {
+                let nullable = match plain.clone() {
+                    DbValue::Blob(v) => DbValue::NulBlob(Some(v)),
+                    DbValue::Bool(v) => DbValue::NulBool(Some(v)),
+                    DbValue::Date(v) => DbValue::NulDate(Some(v)),
+                    DbValue::DateTime(v) => DbValue::NulDateTime(Some(v)),
+                    DbValue::Float(v) => DbValue::NulFloat(Some(v)),
+                    DbValue::Int(v) => DbValue::NulInt(Some(v)),
+                    DbValue::Text(v) => DbValue::NulText(Some(v)),
+                    DbValue::Time(v) => DbValue::NulTime(Some(v)),
+                    nul => nul,
+                };
+                // the Int encoding used by the plain types' conversions
+                let encoded = match &plain {
+                    DbValue::Bool(v) => Some(*v as i64),
+                    DbValue::Date(v) => Some(v.num_days_from_ce() as i64),
+                    DbValue::DateTime(v) => Some(v.and_utc().timestamp()),
+                    DbValue::Time(v) => {
+                        Some(v.num_seconds_from_midnight() as i64)
+                    }
+                    _ => None,
+                };
+                let mut result = vec![plain, nullable];
+                if let Some(int) = encoded {
+                    result.push(DbValue::Int(int));
+                    result.push(DbValue::NulInt(Some(int)));
+                }
+                result
+            }
+            None => vec![
+                DbValue::NulBlob(None),
+                DbValue::NulBool(None),
+                DbValue::NulDate(None),
+                DbValue::NulDateTime(None),
+                DbValue::NulFloat(None),
+                DbValue::NulInt(None),
+                DbValue::NulText(None),
+                DbValue::NulTime(None),
+            ],
+        }
+    }
 }
 
 /// The possible types as seen by the database.
 ///
 /// See [`DbConn::query()`](trait.DbConn.html#tymethod.query)
+///
+/// `Bool`, `Date`, `DateTime`, and `Time` and their nullable variants are
+/// native database types, see [`NativeField`](struct.NativeField.html).
+///
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DbType {
+    /// `Vec<u8>`
+    Blob,
+    /// `bool`
+    Bool,
+    /// `NaiveDate`
+    Date,
+    /// `NaiveDateTime`
+    DateTime,
     /// `f64`
     Float,
     /// `i64`
     Int,
     /// `String`
     Text,
+    /// `NaiveTime`
+    Time,
+    /// `Option<Vec<u8>>`
+    NulBlob,
+    /// `Option<bool>`
+    NulBool,
+    /// `Option<NaiveDate>`
+    NulDate,
+    /// `Option<NaiveDateTime>`
+    NulDateTime,
     /// `Option<f64>`
     NulFloat,
     /// `Option<i64>`
     NulInt,
     /// `Option<String>`
     NulText,
+    /// `Option<NaiveTime>`
+    NulTime,
+}
+
+impl DbType {
+    /// Whether this is one of the `Nul...` variants.
+    ///
+    pub fn is_nullable(&self) -> bool {
+        matches!(
+            self,
+            Self::NulBlob
+                | Self::NulBool
+                | Self::NulDate
+                | Self::NulDateTime
+                | Self::NulFloat
+                | Self::NulInt
+                | Self::NulText
+                | Self::NulTime
+        )
+    }
+
+    /// The corresponding `Nul...` variant.
+    ///
+    pub fn nullable(self) -> Self {
+        match self {
+            Self::Blob => Self::NulBlob,
+            Self::Bool => Self::NulBool,
+            Self::Date => Self::NulDate,
+            Self::DateTime => Self::NulDateTime,
+            Self::Float => Self::NulFloat,
+            Self::Int => Self::NulInt,
+            Self::Text => Self::NulText,
+            Self::Time => Self::NulTime,
+            nul => nul,
+        }
+    }
 }
 
 impl Copy for DbType {}
@@ -210,12 +875,22 @@ impl Copy for DbType {}
 impl From<DbValue> for DbType {
     fn from(v: DbValue) -> Self {
         match v {
+            DbValue::Blob(_) => Self::Blob,
+            DbValue::Bool(_) => Self::Bool,
+            DbValue::Date(_) => Self::Date,
+            DbValue::DateTime(_) => Self::DateTime,
             DbValue::Float(_) => Self::Float,
             DbValue::Int(_) => Self::Int,
             DbValue::Text(_) => Self::Text,
+            DbValue::Time(_) => Self::Time,
+            DbValue::NulBlob(_) => Self::NulBlob,
+            DbValue::NulBool(_) => Self::NulBool,
+            DbValue::NulDate(_) => Self::NulDate,
+            DbValue::NulDateTime(_) => Self::NulDateTime,
             DbValue::NulFloat(_) => Self::NulFloat,
             DbValue::NulInt(_) => Self::NulInt,
             DbValue::NulText(_) => Self::NulText,
+            DbValue::NulTime(_) => Self::NulTime,
         }
     }
 }
@@ -227,14 +902,24 @@ impl From<DbValue> for DbType {
 /// [`db_value_convert`](../macro.db_value_convert.html) can be used to
 /// implement more conversions, here or in application code.
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DbValue {
+    Blob(Vec<u8>),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
     Float(f64),
     Int(i64),
     Text(String),
+    Time(NaiveTime),
+    NulBlob(Option<Vec<u8>>),
+    NulBool(Option<bool>),
+    NulDate(Option<NaiveDate>),
+    NulDateTime(Option<NaiveDateTime>),
     NulFloat(Option<f64>),
     NulInt(Option<i64>),
     NulText(Option<String>),
+    NulTime(Option<NaiveTime>),
 }
 
 impl DbValue {
@@ -246,9 +931,14 @@ impl DbValue {
     /// ```
     pub fn to_option(&self) -> Option<Self> {
         match self {
+            Self::NulBlob(opt) => opt.as_ref().map(|b| Self::Blob(b.clone())),
+            Self::NulBool(opt) => opt.map(Self::Bool),
+            Self::NulDate(opt) => opt.map(Self::Date),
+            Self::NulDateTime(opt) => opt.map(Self::DateTime),
             Self::NulFloat(opt) => opt.map(|f| Self::Float(f)),
             Self::NulInt(opt) => opt.map(|i| Self::Int(i)),
             Self::NulText(opt) => opt.as_ref().map(|s| Self::Text(s.clone())),
+            Self::NulTime(opt) => opt.map(Self::Time),
             _ => Some(self.clone()),
         }
     }
@@ -259,24 +949,30 @@ impl DbValue {
     /// NulInt(None)     -> "NULL"
     /// NulInt(Some(42)) -> "42"
     /// Text("foo")      -> "'foo'"
+    /// Blob(vec![1, 2]) -> "decode('0102', 'hex')"
+    /// Bool(true)       -> "TRUE"
+    /// Date(d)          -> "'2024-02-29'"
+    /// DateTime(d)      -> "'2024-02-29 12:34:56'"
+    /// Time(t)          -> "'12:34:56.789'"
     /// ```
+    /// The notation for binary values is PostgreSQL's, database adapters for
+    /// other databases are expected to define the `decode()` function.
+    ///
     pub fn sql_value(&self) -> String {
-        match self {
-            DbValue::Float(v) => v.to_string(),
-            DbValue::Int(v) => v.to_string(),
-            DbValue::Text(v) => format!("'{}'", v.replace("'", "''")),
-            DbValue::NulFloat(v) => match v {
-                Some(v) => v.to_string(),
-                None => "NULL".to_string(),
-            },
-            DbValue::NulInt(v) => match v {
-                Some(v) => v.to_string(),
-                None => "NULL".to_string(),
-            },
-            DbValue::NulText(v) => match v {
-                Some(v) => format!("'{}'", v.replace("'", "''")),
-                None => "NULL".to_string(),
-            },
+        match self.to_option() {
+            Some(DbValue::Blob(v)) => {
+                format!("decode('{}', 'hex')", to_hex(&v))
+            }
+            Some(DbValue::Bool(v)) => {
+                (if v { "TRUE" } else { "FALSE" }).to_string()
+            }
+            Some(DbValue::Date(v)) => format!("'{}'", v),
+            Some(DbValue::DateTime(v)) => format!("'{}'", v),
+            Some(DbValue::Float(v)) => v.to_string(),
+            Some(DbValue::Int(v)) => v.to_string(),
+            Some(DbValue::Text(v)) => format!("'{}'", v.replace("'", "''")),
+            Some(DbValue::Time(v)) => format!("'{}'", v),
+            _ => "NULL".to_string(),
         }
     }
 }
@@ -293,12 +989,25 @@ macro_rules! write_opt {
 impl fmt::Display for DbValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
+            DbValue::Blob(v) => write!(f, "{}", to_hex(v)),
+            DbValue::Bool(v) => write!(f, "{}", v),
+            DbValue::Date(v) => write!(f, "{}", v),
+            DbValue::DateTime(v) => write!(f, "{}", v),
             DbValue::Float(v) => write!(f, "{}", v),
             DbValue::Int(v) => write!(f, "{}", v),
             DbValue::Text(v) => write!(f, "{}", v),
+            DbValue::Time(v) => write!(f, "{}", v),
+            DbValue::NulBlob(v) => {
+                let v = v.as_ref().map(|b| to_hex(b));
+                write_opt!(f, v)
+            }
+            DbValue::NulBool(v) => write_opt!(f, v),
+            DbValue::NulDate(v) => write_opt!(f, v),
+            DbValue::NulDateTime(v) => write_opt!(f, v),
             DbValue::NulFloat(v) => write_opt!(f, v),
             DbValue::NulInt(v) => write_opt!(f, v),
             DbValue::NulText(v) => write_opt!(f, v),
+            DbValue::NulTime(v) => write_opt!(f, v),
         }
     }
 }
@@ -322,6 +1031,13 @@ db_value_convert! {
     DateTime::<Utc>::from_timestamp(value, 0).unwrap().naive_utc(),
     other.and_utc().timestamp(),
 }
+db_value_convert! {
+    in_db_value_module,
+    DateTime<Utc>,
+    Int,
+    DateTime::<Utc>::from_timestamp(value, 0).unwrap(),
+    other.timestamp(),
+}
 db_value_convert! {
     in_db_value_module,
     NaiveTime,
@@ -333,6 +1049,13 @@ db_value_convert! { in_db_value_module, String, Text }
 db_value_convert! { in_db_value_module, u32, Int }
 db_value_convert! { in_db_value_module, u64, Int }
 db_value_convert! { in_db_value_module, usize, Int }
+db_value_convert! { in_db_value_module, Vec<u8>, Blob }
+
+// Lower case hexadecimal representation of bytes, as used for DbValue::Blob
+// in SQL and CSV.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
 
 /// Facilitates conversions between [`DbValue::Text`
 /// ](enum.DbValue.html#variant.Text) and any JSON-serializable type.
@@ -370,6 +1093,87 @@ impl<T: ::std::fmt::Debug + DeserializeOwned + Serialize> TryFrom<DbValue>
     }
 }
 
+/// Facilitates conversions between `bool`, `NaiveDate`, `NaiveDateTime`,
+/// `DateTime<Utc>`, and `NaiveTime` and the [`DbValue`](enum.DbValue.html)
+/// variants for native database types, i.e. [`DbValue::Bool`
+/// ](enum.DbValue.html#variant.Bool), `Date`, `DateTime`, and `Time`, and
+/// their nullable variants.
+///
+/// Without this wrapper these types are stored as [`DbValue::Int`
+/// ](enum.DbValue.html#variant.Int), as days, seconds, or 0 and 1.
+///
+/// The conversions are implemented for `NativeField<T>` and
+/// `NativeField<Option<T>>`.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NativeField<T>(pub T);
+
+// $to_db and $from_db convert between $typ and the type in the variant
+macro_rules! native_field_convert {
+    ($typ:ty, $variant:ident, $nul_variant:ident $(,)?) => {
+        native_field_convert!($typ, $variant, $nul_variant, |v| v, |v| v);
+    };
+    (
+        $typ:ty,
+        $variant:ident,
+        $nul_variant:ident,
+        $to_db:expr,
+        $from_db:expr $(,)?
+    ) => {
+        impl From<NativeField<$typ>> for DbValue {
+            fn from(native: NativeField<$typ>) -> Self {
+                DbValue::$variant(($to_db)(native.0))
+            }
+        }
+
+        impl TryFrom<DbValue> for NativeField<$typ> {
+            type Error = Error;
+            fn try_from(db_value: DbValue) -> Result<Self, Self::Error> {
+                match db_value {
+                    DbValue::$variant(value) => Ok(Self(($from_db)(value))),
+                    _ => Err(Error::invalid_input(&format!(
+                        "cannot convert {db_value:?} into {}",
+                        std::any::type_name::<Self>(),
+                    ))),
+                }
+            }
+        }
+
+        impl From<NativeField<Option<$typ>>> for DbValue {
+            fn from(native: NativeField<Option<$typ>>) -> Self {
+                DbValue::$nul_variant(native.0.map($to_db))
+            }
+        }
+
+        impl TryFrom<DbValue> for NativeField<Option<$typ>> {
+            type Error = Error;
+            fn try_from(db_value: DbValue) -> Result<Self, Self::Error> {
+                match db_value {
+                    DbValue::$nul_variant(value) => {
+                        Ok(Self(value.map($from_db)))
+                    }
+                    _ => Err(Error::invalid_input(&format!(
+                        "cannot convert {db_value:?} into {}",
+                        std::any::type_name::<Self>(),
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+native_field_convert!(bool, Bool, NulBool);
+native_field_convert!(NaiveDate, Date, NulDate);
+native_field_convert!(NaiveDateTime, DateTime, NulDateTime);
+native_field_convert!(
+    DateTime<Utc>,
+    DateTime,
+    NulDateTime,
+    |v: DateTime<Utc>| v.naive_utc(),
+    |v: NaiveDateTime| v.and_utc(),
+);
+native_field_convert!(NaiveTime, Time, NulTime);
+
 /// An implementation of [`DbConn`](trait.DbConn.html) that does nothing and
 /// returns [`Error`](../error/enum.Error.html).
 ///
@@ -391,28 +1195,670 @@ impl DbConn for NullConn {
     }
 }
 
-/// Try to execute SQL statements read from `source`. The contents of `file`
-/// are simply split at `';'` to produce a number of SQL parameters to
-/// [`DatabaseIf::exec()`](struct.DatabaseIf.html#method.exec).
+/// An iterator over the rows of a query result, returned by
+/// [`DatabaseIf::query_iter()`](struct.DatabaseIf.html#method.query_iter).
+///
+/// The query is run by [`DatabaseIf::query_each()`
+/// ](struct.DatabaseIf.html#method.query_each) in a separate thread, which
+/// fetches at most [`QUERY_ITER_BUFFER`](constant.QUERY_ITER_BUFFER.html)
+/// rows ahead of the iterator.
+///
+/// The database must not be used until the iterator is exhausted or dropped.
+///
+/// An error from the database is returned as the last item.
+///
+/// ```text
+/// for row in db.query_iter("SELECT id, data FROM audits", &[], &types) {
+///     let row = row?;
+///     // ...
+/// }
+/// ```
+///
+pub struct QueryIter {
+    rows: Option<Receiver<Result<Vec<DbValue>, Error>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// The number of rows a [`QueryIter`](struct.QueryIter.html) fetches ahead.
+///
+pub const QUERY_ITER_BUFFER: usize = 64;
+
+impl QueryIter {
+    fn new(
+        db: DatabaseIf,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+    ) -> Self {
+        let (tx, rx) = sync_channel(QUERY_ITER_BUFFER);
+        let sql = sql.to_string();
+        let values = values.to_vec();
+        let types = types.to_vec();
+        let thread = thread::spawn(move || {
+            let result = db.query_each(&sql, &values, &types, |row| {
+                // the receiver is gone if the iterator was dropped
+                tx.send(Ok(row)).map_err(Error::other)
+            });
+            if let Err(e) = result {
+                let _ = tx.send(Err(e));
+            }
+        });
+        Self {
+            rows: Some(rx),
+            thread: Some(thread),
+        }
+    }
+
+    // Wait for the query thread, forwarding a panic.
+    fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl Drop for QueryIter {
+    fn drop(&mut self) {
+        self.rows = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Iterator for QueryIter {
+    type Item = Result<Vec<DbValue>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.rows.as_ref().and_then(|rows| rows.recv().ok());
+        match next {
+            Some(item) => Some(item),
+            None => {
+                self.rows = None;
+                self.join();
+                None
+            }
+        }
+    }
+}
+
+/// How [`DatabaseIf::transaction_with_retry()`
+/// ](struct.DatabaseIf.html#method.transaction_with_retry) retries a failed
+/// transaction.
+///
+/// The delay before retry number `n` is `backoff * 2^(n - 1)`, but at most
+/// `max_backoff`, randomly reduced by up to a half to avoid retrying in step
+/// with a competing transaction.
+///
+/// The default is 5 attempts, 10 ms initial backoff, and 1 s max backoff.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    ///
+    pub attempts: u32,
+
+    /// The delay before the first retry.
+    ///
+    pub backoff: Duration,
+
+    /// The maximum delay before a retry.
+    ///
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    // The delay after failed attempt number attempt, counting from 1.
+    fn delay(&self, attempt: u32) -> Duration {
+        use rand::{thread_rng, Rng};
+
+        let max = self
+            .backoff
+            .checked_mul(1 << (attempt - 1).min(31))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        max.mul_f64(thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A [`DbConn`](trait.DbConn.html) that forwards everything to the wrapped
+/// `C` and records each statement as a [`QueryLogEntry`
+/// ](struct.QueryLogEntry.html) sent to a [`QueryLogSink`
+/// ](trait.QueryLogSink.html).
+///
+/// Intended for finding slow queries and repeated queries, e.g. the "N+1"
+/// queries produced by calling a generated `has_many` getter for each
+/// object in a list.
+///
+/// The HTTP server adapters wrap their connection in a `LoggingConn` if the
+/// [`app_config`](../http/server/struct.HttpServerIf.html#query_log)
+/// attribute `query_log` or `slow_query_ms` is defined.
+///
+/// ```text
+/// let sink = vicocomo::MemoryLog::default();
+/// let db = vicocomo::DatabaseIf::new(Arc::new(
+///     vicocomo::LoggingConn::new(conn, Arc::new(sink.clone()))
+/// ));
+/// // ... use db ...
+/// for entry in sink.entries() {
+///     println!("{}", entry);
+/// }
+/// ```
+///
+pub struct LoggingConn<C: DbConn> {
+    conn: C,
+    sink: Arc<dyn QueryLogSink>,
+    slow: Option<Duration>,
+}
+
+impl<C: DbConn> LoggingConn<C> {
+    /// Wrap `conn`, logging every statement to `sink`.
+    ///
+    pub fn new(conn: C, sink: Arc<dyn QueryLogSink>) -> Self {
+        Self {
+            conn,
+            sink,
+            slow: None,
+        }
+    }
+
+    /// Only log statements that take `threshold` or longer.
+    ///
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow = Some(threshold);
+        self
+    }
+
+    // Time action and log the result, counting rows using count.
+    fn log<T, A, R>(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        action: A,
+        count: R,
+    ) -> Result<T, Error>
+    where
+        A: FnOnce(&C) -> Result<T, Error>,
+        R: FnOnce(&T) -> usize,
+    {
+        let start = Instant::now();
+        let result = action(&self.conn);
+        let elapsed = start.elapsed();
+        if self.slow.map(|slow| elapsed >= slow).unwrap_or(true) {
+            self.sink.log(QueryLogEntry {
+                sql: sql.to_string(),
+                values: values.to_vec(),
+                rows: result.as_ref().ok().map(count),
+                elapsed,
+                error: result.as_ref().err().cloned(),
+            });
+        }
+        result
+    }
+}
+
+impl<C: DbConn> DbConn for LoggingConn<C> {
+    fn begin(&self) -> Result<(), Error> {
+        self.log("BEGIN", &[], |c| c.begin(), |_| 0)
+    }
+
+    fn commit(&self) -> Result<(), Error> {
+        self.log("COMMIT", &[], |c| c.commit(), |_| 0)
+    }
+
+    fn exec(&self, sql: &str, values: &[DbValue]) -> Result<usize, Error> {
+        self.log(sql, values, |c| c.exec(sql, values), |n| *n)
+    }
+
+    fn query(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+    ) -> Result<Vec<Vec<DbValue>>, Error> {
+        self.log(
+            sql,
+            values,
+            |c| c.query(sql, values, types),
+            |rows| rows.len(),
+        )
+    }
+
+    fn query_each(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+        f: &mut dyn FnMut(Vec<DbValue>) -> Result<(), Error>,
+    ) -> Result<usize, Error> {
+        self.log(sql, values, |c| c.query_each(sql, values, types, f), |n| *n)
+    }
+
+    fn query_rows(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+    ) -> Result<Vec<DbRow>, Error> {
+        self.log(
+            sql,
+            values,
+            |c| c.query_rows(sql, values),
+            |rows| rows.len(),
+        )
+    }
+
+    fn release_savepoint(&self, name: &str) -> Result<(), Error> {
+        self.log(
+            &format!("RELEASE SAVEPOINT {}", name),
+            &[],
+            |c| c.release_savepoint(name),
+            |_| 0,
+        )
+    }
+
+    fn rollback(&self) -> Result<(), Error> {
+        self.log("ROLLBACK", &[], |c| c.rollback(), |_| 0)
+    }
+
+    fn rollback_to_savepoint(&self, name: &str) -> Result<(), Error> {
+        self.log(
+            &format!("ROLLBACK TO SAVEPOINT {}", name),
+            &[],
+            |c| c.rollback_to_savepoint(name),
+            |_| 0,
+        )
+    }
+
+    fn savepoint(&self, name: &str) -> Result<(), Error> {
+        self.log(
+            &format!("SAVEPOINT {}", name),
+            &[],
+            |c| c.savepoint(name),
+            |_| 0,
+        )
+    }
+
+    fn statement_cache_stats(&self) -> Option<StatementCacheStats> {
+        self.conn.statement_cache_stats()
+    }
+
+    fn table_schema(
+        &self,
+        table: &str,
+    ) -> Result<Option<TableSchema>, Error> {
+        self.conn.table_schema(table)
+    }
+
+    fn transaction_depth(&self) -> Option<&AtomicUsize> {
+        self.conn.transaction_depth()
+    }
+}
+
+/// A least recently used cache of prepared statements of type `T` keyed by
+/// their SQL, for use by database adapters.
+///
+/// Keeps count of hits and misses, see [`stats()`](#method.stats).
+///
+#[derive(Clone, Debug)]
+pub struct StatementCache<T> {
+    capacity: usize,
+    // most recently used first
+    entries: Vec<(String, T)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<T: Clone> StatementCache<T> {
+    /// Create an empty cache that will hold at most `capacity` statements.
+    /// A `capacity` of `0` disables caching.
+    ///
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Remove all statements. The counters are not reset.
+    ///
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Get the statement for `sql` if cached, counting a hit or a miss.
+    ///
+    pub fn get(&mut self, sql: &str) -> Option<T> {
+        match self.entries.iter().position(|(key, _)| key == sql) {
+            Some(ix) => {
+                self.hits += 1;
+                let entry = self.entries.remove(ix);
+                let stmt = entry.1.clone();
+                self.entries.insert(0, entry);
+                Some(stmt)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Cache `stmt` for `sql`, evicting the least recently used statement if
+    /// the cache is full.
+    ///
+    pub fn insert(&mut self, sql: &str, stmt: T) {
+        self.remove(sql);
+        self.entries.insert(0, (sql.to_string(), stmt));
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Remove the statement for `sql` if cached.
+    ///
+    pub fn remove(&mut self, sql: &str) {
+        self.entries.retain(|(key, _)| key != sql);
+    }
+
+    /// Change the capacity, evicting the least recently used statements if
+    /// needed.
+    ///
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// The present capacity, size, and counters.
+    ///
+    pub fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            capacity: self.capacity,
+            size: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+/// Statistics from a [`StatementCache`](struct.StatementCache.html).
+///
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StatementCacheStats {
+    /// The maximum number of cached statements.
+    pub capacity: usize,
+    /// The number of cached statements.
+    pub size: usize,
+    /// The number of times a statement was found in the cache.
+    pub hits: u64,
+    /// The number of times a statement had to be prepared.
+    pub misses: u64,
+}
+
+/// A statement executed by a [`LoggingConn`](struct.LoggingConn.html).
+///
+/// `Display` writes the entry on one line as e.g.
+/// ```text
+/// [1.234ms] 2 rows: SELECT id FROM foos WHERE bar = $1 -- [Text("baz")]
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct QueryLogEntry {
+    /// The SQL as given to the wrapped connection.
+    pub sql: String,
+    /// The values for the parameters in `sql`.
+    pub values: Vec<DbValue>,
+    /// The number of affected or returned rows, `None` if `error` is
+    /// `Some(_)`.
+    pub rows: Option<usize>,
+    /// The wall clock time the wrapped connection used.
+    pub elapsed: Duration,
+    /// The error returned by the wrapped connection, if any.
+    pub error: Option<Error>,
+}
+
+impl fmt::Display for QueryLogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?}] ", self.elapsed)?;
+        match (&self.rows, &self.error) {
+            (Some(rows), _) => write!(f, "{} rows: ", rows)?,
+            (None, Some(err)) => write!(f, "{:?}: ", err)?,
+            _ => (),
+        }
+        write!(
+            f,
+            "{}",
+            self.sql.split_whitespace().collect::<Vec<_>>().join(" ")
+        )?;
+        if !self.values.is_empty() {
+            write!(f, " -- {:?}", self.values)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a [`LoggingConn`](struct.LoggingConn.html) sends its entries.
+///
+/// Implemented by [`StderrLog`](struct.StderrLog.html), [`FileLog`
+/// ](struct.FileLog.html), and [`MemoryLog`](struct.MemoryLog.html).
+///
+pub trait QueryLogSink: Send + Sync {
+    /// Record `entry`. Should not fail, ignore any errors.
+    ///
+    fn log(&self, entry: QueryLogEntry);
+}
+
+/// A [`QueryLogSink`](trait.QueryLogSink.html) that writes each entry on a
+/// line to a file.
+///
+pub struct FileLog(Mutex<File>);
+
+impl FileLog {
+    /// Open `path` for appending, creating it if needed.
+    ///
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map(|file| Self(Mutex::new(file)))
+            .map_err(|e| Error::other(format!("{}: {}", path.display(), e)))
+    }
+}
+
+impl QueryLogSink for FileLog {
+    fn log(&self, entry: QueryLogEntry) {
+        if let Ok(mut file) = self.0.lock() {
+            let _ = writeln!(file, "{}", entry);
+        }
+    }
+}
+
+/// A [`QueryLogSink`](trait.QueryLogSink.html) that keeps the entries in
+/// memory. Clones share the entries.
+///
+#[derive(Clone, Debug, Default)]
+pub struct MemoryLog(Arc<Mutex<Vec<QueryLogEntry>>>);
+
+impl MemoryLog {
+    /// Remove all entries.
+    ///
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// The entries logged so far, oldest first.
+    ///
+    pub fn entries(&self) -> Vec<QueryLogEntry> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl QueryLogSink for MemoryLog {
+    fn log(&self, entry: QueryLogEntry) {
+        self.0.lock().unwrap().push(entry);
+    }
+}
+
+/// A [`QueryLogSink`](trait.QueryLogSink.html) that writes each entry on a
+/// line to `stderr`.
+///
+#[derive(Clone, Debug)]
+pub struct StderrLog;
+
+impl QueryLogSink for StderrLog {
+    fn log(&self, entry: QueryLogEntry) {
+        eprintln!("{}", entry);
+    }
+}
+
+/// Try to execute SQL statements read from `source`. The statements are
+/// separated by `';'` and given one by one to [`DatabaseIf::exec()`
+/// ](struct.DatabaseIf.html#method.exec).
+///
+/// A `';'` does not separate statements if it is inside
+/// - a string literal `'...'` or a quoted identifier `"..."`,
+/// - a PostgreSQL dollar quoted string `$$...$$` or `$tag$...$tag$`,
+/// - a comment `-- ...` or `/* ... */`, or
+/// - the `BEGIN ... END` block of a `CREATE TRIGGER` statement.
+///
+/// Statements that contain nothing but white space and comments are ignored.
 ///
 /// <b>Errors</b>
 ///
 /// If execution fails the `original_error` is returned if `Some(_)`,
-/// otherwise the error returned from the failing `exec()`.
+/// otherwise the error returned from the failing `exec()`. If the latter is
+/// an [`Error::Database`](../error/enum.Error.html#variant.Database), its
+/// text is prefixed by the (1-based) index and the text of the failing
+/// statement.
 ///
 pub fn try_exec_sql(
     db: DatabaseIf,
     source: &str,
     original_error: Option<Error>,
 ) -> Result<(), Error> {
-    for statement in source.split(';') {
-        let statement = statement.trim();
-        if statement.is_empty() {
-            continue;
-        }
+    for (ix, statement) in split_sql(source).iter().enumerate() {
         if let Err(e) = db.clone().exec(statement, &[]) {
-            return Err(original_error.unwrap_or(e));
+            return Err(original_error.unwrap_or_else(|| match e {
+                Error::Database(mut db_err) => {
+                    db_err.text = format!(
+                        "statement {} `{}`: {}",
+                        ix + 1,
+                        first_n_chars_ellipsis(statement, 100, " ..."),
+                        db_err.text,
+                    );
+                    Error::Database(db_err)
+                }
+                _ => e,
+            }));
         }
     }
     Ok(())
 }
+
+// Split source into trimmed statements as described for try_exec_sql().
+//
+fn split_sql(source: &str) -> Vec<&str> {
+    use logos::{Lexer, Logos};
+
+    #[derive(Logos, Debug, PartialEq)]
+    #[logos(skip r"\s+")]
+    enum Tok {
+        #[token("--", line_comment)]
+        #[regex(r"/\*[^*]*\*+(?:[^/*][^*]*\*+)*/")]
+        Cmt,
+
+        #[regex(r"\$(?:[A-Za-z_]\w*)?\$", dollar_quoted)]
+        Dlr,
+
+        #[regex(r#""[^"]*(?:""[^"]*)*""#)]
+        Qid,
+
+        #[token(";")]
+        Sem,
+
+        #[regex(r"'[^']*(?:''[^']*)*'")]
+        Txt,
+
+        #[regex(r"[A-Za-z_]\w*")]
+        Wrd,
+        // Anything else is lexed as an error and treated as SQL code
+    }
+
+    // Skip to the end of the line.
+    fn line_comment(lex: &mut Lexer<Tok>) {
+        lex.bump(lex.remainder().find('\n').unwrap_or(lex.remainder().len()));
+    }
+
+    // Skip to after the closing tag, or to the end if there is none.
+    fn dollar_quoted(lex: &mut Lexer<Tok>) {
+        let tag = lex.slice();
+        let len = match lex.remainder().find(tag) {
+            Some(pos) => pos + tag.len(),
+            None => lex.remainder().len(),
+        };
+        lex.bump(len);
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0; // of the current statement
+    let mut code = false; // the current statement is not only comments
+    let mut head: Vec<String> = Vec::new(); // first words of the statement
+    let mut trigger = false; // the current statement is CREATE TRIGGER
+    let mut depth = 0; // of BEGIN/CASE ... END in a trigger
+    let mut lex = Tok::lexer(source);
+    while let Some(tok) = lex.next() {
+        match tok {
+            Ok(Tok::Cmt) => continue,
+            Ok(Tok::Sem) if depth == 0 => {
+                let statement = source[start..lex.span().start].trim();
+                if code {
+                    result.push(statement);
+                }
+                start = lex.span().end;
+                code = false;
+                head.clear();
+                trigger = false;
+                continue;
+            }
+            Ok(Tok::Wrd) => {
+                let word = lex.slice().to_uppercase();
+                if head.len() < 3 {
+                    head.push(word.clone());
+                    trigger = trigger
+                        || matches!(
+                            head.iter()
+                                .map(|w| w.as_str())
+                                .collect::<Vec<_>>()[..],
+                            ["CREATE", "TRIGGER"]
+                                | ["CREATE", "TEMP" | "TEMPORARY", "TRIGGER"]
+                        );
+                }
+                match word.as_str() {
+                    "BEGIN" if trigger => depth += 1,
+                    "CASE" if depth > 0 => depth += 1,
+                    "END" if depth > 0 => depth -= 1,
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        code = true;
+    }
+    let statement = source[start..].trim();
+    if code {
+        result.push(statement);
+    }
+    result
+}
@@ -4,12 +4,44 @@
 use crate::texts::get_text;
 use std::fmt::Display;
 
+/// Grabbed from the [PosgreSQL docs
+/// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
+/// actual SQLSTATE standard is not open source?
+///
+pub const SQLSTATE_CHECK_VIOLATION: &'static str = "23514";
+
+/// Grabbed from the [PosgreSQL docs
+/// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
+/// actual SQLSTATE standard is not open source?
+///
+pub const SQLSTATE_DEADLOCK_DETECTED: &'static str = "40P01";
+
 /// Grabbed from the [PosgreSQL docs
 /// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
 /// actual SQLSTATE standard is not open source?
 ///
 pub const SQLSTATE_FOREIGN_KEY_VIOLATION: &'static str = "23503";
 
+/// Grabbed from the [PosgreSQL docs
+/// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
+/// actual SQLSTATE standard is not open source?
+///
+/// The SQLite adapter uses this also for `SQLITE_BUSY` and `SQLITE_LOCKED`.
+///
+pub const SQLSTATE_LOCK_NOT_AVAILABLE: &'static str = "55P03";
+
+/// Grabbed from the [PosgreSQL docs
+/// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
+/// actual SQLSTATE standard is not open source?
+///
+pub const SQLSTATE_NOT_NULL_VIOLATION: &'static str = "23502";
+
+/// Grabbed from the [PosgreSQL docs
+/// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
+/// actual SQLSTATE standard is not open source?
+///
+pub const SQLSTATE_SERIALIZATION_FAILURE: &'static str = "40001";
+
 /// Grabbed from the [PosgreSQL docs
 /// ](https://www.postgresql.org/docs/current/errcodes-appendix.html). The
 /// actual SQLSTATE standard is not open source?
@@ -61,6 +93,9 @@ impl Error {
         Self::Database(DatabaseError {
             sqlstate: sqlstate.map(|c| c.to_string()),
             text: text.to_string(),
+            table: None,
+            column: None,
+            constraint: None,
         })
     }
 
@@ -82,6 +117,13 @@ impl Error {
         }
     }
 
+    /// The variant is [`Database`](#variant.Database) and the database error
+    /// code is `SQLSTATE 23514`.
+    ///
+    pub fn is_check_violation(&self) -> bool {
+        self.is_database_error(SQLSTATE_CHECK_VIOLATION)
+    }
+
     /// The variant is [`Database`](#variant.Database) and the database error
     /// code is `SQLSTATE 23503`.
     ///
@@ -89,6 +131,26 @@ impl Error {
         self.is_database_error(SQLSTATE_FOREIGN_KEY_VIOLATION)
     }
 
+    /// The variant is [`Database`](#variant.Database) and the database error
+    /// code is `SQLSTATE 23502`.
+    ///
+    pub fn is_not_null_violation(&self) -> bool {
+        self.is_database_error(SQLSTATE_NOT_NULL_VIOLATION)
+    }
+
+    /// The variant is [`Database`](#variant.Database) and the database error
+    /// code indicates that the transaction may succeed if retried, i.e.
+    /// `SQLSTATE 40001`, `40P01`, or `55P03`.
+    ///
+    /// See [`DatabaseIf::transaction_with_retry()`
+    /// ](../database/struct.DatabaseIf.html#method.transaction_with_retry).
+    ///
+    pub fn is_retryable(&self) -> bool {
+        self.is_database_error(SQLSTATE_SERIALIZATION_FAILURE)
+            || self.is_database_error(SQLSTATE_DEADLOCK_DETECTED)
+            || self.is_database_error(SQLSTATE_LOCK_NOT_AVAILABLE)
+    }
+
     /// The variant is [`Database`](#variant.Database) and the database error
     /// code is `SQLSTATE 23505`.
     ///
@@ -237,7 +299,7 @@ impl Error {
 
     #[doc(hidden)] // used by the macro HtmlForm
     pub fn format_database(err: &DatabaseError) -> String {
-        let DatabaseError { sqlstate, text } = err;
+        let DatabaseError { sqlstate, text, .. } = err;
         let formatted = if let Some(ss) = sqlstate.as_ref() {
             format!("{}--", ss)
         } else {
@@ -295,9 +357,12 @@ impl Error {
             match err.error {
                 ModelErrorKind::CannotSave => "CannotSave",
                 ModelErrorKind::CannotDelete => "CannotDelete",
+                ModelErrorKind::CheckFailed => "CheckFailed",
                 ModelErrorKind::Invalid => "Invalid",
                 ModelErrorKind::NotFound => "NotFound",
+                ModelErrorKind::NotNull => "NotNull",
                 ModelErrorKind::NotUnique => "NotUnique",
+                ModelErrorKind::StaleObject => "StaleObject",
             }
         )
     }
@@ -311,6 +376,23 @@ impl Error {
         format!("error--{}", var)
     }
 
+    // The variant is Database and the error refers to table.column, or to
+    // a constraint with PostgreSQL's default name for a check constraint on
+    // column.
+    #[doc(hidden)] // used by derive(ActiveRecord)
+    pub fn mentions_column(&self, table: &str, column: &str) -> bool {
+        let de = match self {
+            Self::Database(de) => de,
+            _ => return false,
+        };
+        de.table.as_ref().is_none_or(|t| t == table)
+            && de.column.as_ref().is_some_and(|c| c == column)
+            || de
+                .constraint
+                .as_ref()
+                .is_some_and(|c| *c == format!("{}_{}_check", table, column))
+    }
+
     fn format(&self, localize: bool) -> Vec<String> {
         use v_htmlescape::escape;
 
@@ -621,6 +703,16 @@ pub struct DatabaseError {
     /// Error text as received from the database driver.
     ///
     pub text: String,
+    /// The table the error refers to, if reported by the database driver.
+    ///
+    pub table: Option<String>,
+    /// The column the error refers to, if reported by the database driver.
+    ///
+    pub column: Option<String>,
+    /// The name of the constraint the error refers to, if reported by the
+    /// database driver.
+    ///
+    pub constraint: Option<String>,
 }
 
 /// The contents of the [error](enum.Error.html) variant [`Model`
@@ -655,6 +747,25 @@ pub struct ModelError {
 }
 
 impl ModelError {
+    /// Add `error` to the error texts for `field`.
+    ///
+    pub fn add_field_error(&mut self, field: &str, error: &str) {
+        match self.field_errors.iter_mut().find(|e| e.0 == field) {
+            Some(fld_errs) => fld_errs.1.push(error.to_string()),
+            None => self
+                .field_errors
+                .push((field.to_string(), vec![error.to_string()])),
+        }
+    }
+
+    /// Returns `true` iff there is a general, field, or association error.
+    ///
+    pub fn has_errors(&self) -> bool {
+        self.general.is_some()
+            || !self.field_errors.is_empty()
+            || !self.assoc_errors.is_empty()
+    }
+
     #[doc(hidden)] // used by the macro is_error
     pub fn fld_errors_include(
         errs: &[(String, Vec<String>)],
@@ -667,13 +778,19 @@ impl ModelError {
     }
 }
 
-/// The error kind, the names are self-explanatory.
+/// The error kind, the names are mostly self-explanatory.
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ModelErrorKind {
     CannotSave,
     CannotDelete,
+    CheckFailed,
     Invalid,
     NotFound,
+    NotNull,
     NotUnique,
+    /// The object was changed by someone else since it was loaded, see
+    /// [`vicocomo_lock_version`
+    /// ](../derive.ActiveRecord.html#vicocomo_lock_version).
+    StaleObject,
 }
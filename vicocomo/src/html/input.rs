@@ -1037,6 +1037,43 @@ impl InputType {
 ///     }),
 /// );
 /// ```
+///
+/// ### Optimistic locking
+///
+/// A model's [`vicocomo_lock_version`
+/// ](../../derive.ActiveRecord.html#vicocomo_lock_version) field can be
+/// carried through the form in a hidden input, and a resulting
+/// `StaleObject` error added to the form.
+/// ```
+/// use serde_json::json;
+/// use vicocomo::{model_error, HtmlForm, HtmlInput};
+///
+/// #[derive(Clone, HtmlForm)]
+/// struct EditForm {
+///     errors: Vec<String>,
+///     name: HtmlInput<String>,
+///     #[vicocomo_html_input_type = "Hidden"]
+///     lock_version: HtmlInput<i64>,
+/// }
+///
+/// let mut edit = EditForm::new();
+/// edit.lock_version.set(3);
+/// assert_eq!(
+///     edit.lock_version.render(),
+///     json!({
+///         "errors": [],
+///         "data": {
+///             "label": null,
+///             "tag": r#"<input type="hidden" id="lock_version" name="lock_version" value="3">"#,
+///         },
+///     }),
+/// );
+/// assert!(edit.update(&json!({"lock_version": "4"})).is_ok());
+/// assert_eq!(edit.lock_version.get(), Some(4));
+///
+/// edit.add_error(&model_error!(StaleObject, "Model": ""), &[]);
+/// assert_eq!(edit.errors, vec!["error--Model-StaleObject--Model"]);
+/// ```
 pub trait HtmlForm: Clone + Sized {
     /// Add the error error messages in `error` to those in the form and its
     /// `HtmlInput` fields.
@@ -711,6 +711,33 @@ impl HttpResponse {
 ///
 /// Optional, default `""` meaning the working directory of the HTTP server.
 ///
+/// ### `migrations`
+///
+/// The value should be `true` or a string literal that is the file system
+/// path of a directory containing [database migrations
+/// ](../../migration/index.html). The HTTP server adapter applies any pending
+/// migrations before the database is used.
+///
+/// `true` gives the default directory `db/migrations` in [`resource_dir`
+/// ](#resource_dir). If a string literal has no leading slash, the working
+/// directory of the HTTP server is prepended.
+///
+/// Optional, no migrations are applied if not present.
+///
+/// ### `query_log`
+///
+/// The value should be `true` or a string literal that is a file system
+/// path. The HTTP server adapter wraps the database connection in a
+/// [`LoggingConn`](../../database/struct.LoggingConn.html) that logs every
+/// statement with its parameter values, number of rows, and elapsed time.
+///
+/// `true` logs to `stderr`, a string literal appends to that file. If the
+/// string literal has no leading slash, the working directory of the HTTP
+/// server is prepended.
+///
+/// Optional, statements are not logged if neither this nor `slow_query_ms`
+/// is present.
+///
 /// ### `resource_dir`
 ///
 /// The direcotry root of the application's resoruces.
@@ -741,6 +768,14 @@ impl HttpResponse {
 ///
 /// The predefined role `Superuser` is added if omitted.
 ///
+/// ### `slow_query_ms`
+///
+/// The value should be an integer. If present, only statements taking at
+/// least that many milliseconds are logged, see [`query_log`](#query_log).
+///
+/// Optional, all statements are logged if `query_log` is present. If
+/// `query_log` is not present the default is to log to `stderr`.
+///
 /// ### `strip_mtime`
 ///
 /// The value should be `true` or `false`. Works together with
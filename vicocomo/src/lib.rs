@@ -6,22 +6,32 @@ pub mod error;
 pub mod flash;
 pub mod html;
 pub mod http;
+pub mod migration;
+pub mod schema;
 pub mod session_model;
 pub mod texts;
 pub mod view;
 
 pub use active_record::{
-    backup_version, check_backup, ActiveRecord, BeforeDelete, BeforeSave,
-    Order, Query, QueryBld, BACKUP_VERSION,
+    backup_version, check_backup, ActiveRecord, AfterDelete, AfterSave,
+    BeforeDelete, BeforeSave, Order, Original, Query, QueryBld, Validate,
+    BACKUP_VERSION,
 };
 pub use authorization::{PasswordDigest, UserRole};
 pub use controller::Controller;
 pub use database::{
-    try_exec_sql, DatabaseIf, DbConn, DbType, DbValue, JsonField, NullConn,
+    try_exec_sql, Clock, DatabaseIf, DbConn, DbRow, DbType, DbValue,
+    FileLog, FixedClock, JsonField, LoggingConn, MemoryLog, NativeField,
+    NullConn, QueryIter, QueryLogEntry, QueryLogSink, RetryPolicy,
+    StatementCache, StatementCacheStats, StderrLog, SystemClock,
+    QUERY_ITER_BUFFER,
 };
 pub use error::{
     DatabaseError, Error, ModelError, ModelErrorKind,
-    SQLSTATE_FOREIGN_KEY_VIOLATION, SQLSTATE_UNIQUE_VIOLATION,
+    SQLSTATE_CHECK_VIOLATION, SQLSTATE_DEADLOCK_DETECTED,
+    SQLSTATE_FOREIGN_KEY_VIOLATION, SQLSTATE_LOCK_NOT_AVAILABLE,
+    SQLSTATE_NOT_NULL_VIOLATION, SQLSTATE_SERIALIZATION_FAILURE,
+    SQLSTATE_UNIQUE_VIOLATION,
 };
 pub use flash::{Flash, FlashData};
 pub use html::input::{HtmlForm, HtmlInput, InputType};
@@ -33,6 +43,10 @@ pub use http::{
     HttpResponse, HttpServer, HttpServerIf, HttpServerImpl, HttpSession,
     HttpStatus, NullTemplEng, TemplEng, TemplEngIf,
 };
+pub use schema::{
+    ColumnSchema, ForeignKeyAction, ForeignKeySchema, SchemaMismatch,
+    SqlDialect, TableSchema,
+};
 pub use session_model::SessionModel;
 pub use vicocomo_active_record::ActiveRecord;
 pub use vicocomo_db_macros::db_value_convert;
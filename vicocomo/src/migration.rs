@@ -0,0 +1,305 @@
+//! Versioned database schema migrations.
+//!
+//! A migration directory contains SQL files named
+//! *version*`_`*name*`.sql`, where *version* is a decimal number and *name*
+//! does not contain any dots. The file contains SQL statements that are
+//! executed by [`try_exec_sql()`](../database/fn.try_exec_sql.html).
+//!
+//! A migration may be reverted if there is a file named
+//! *version*`_`*name*`.down.sql` with the same *version* and *name*. For
+//! symmetry the forward file may also be named *version*`_`*name*`.up.sql`.
+//!
+//! Files not matching any of the patterns above are ignored.
+//!
+//! The versions of the applied migrations are recorded in the table
+//! [`MIGRATIONS_TABLE`](constant.MIGRATIONS_TABLE.html), which is created if
+//! it does not exist.
+//!
+//! # Example
+//! ```text
+//! db/migrations/
+//!     0001_create_users.sql
+//!     0001_create_users.down.sql
+//!     0002_add_user_email.up.sql
+//!     0002_add_user_email.down.sql
+//! ```
+//! ```text
+//! vicocomo::migration::migrate(db, Path::new("db/migrations"))?;
+//! ```
+
+use crate::{t, try_exec_sql, DatabaseIf, DbType, DbValue, Error};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// The name of the table where applied migrations are recorded.
+///
+pub const MIGRATIONS_TABLE: &str = "__vicocomo__migrations";
+
+/// One migration as read from a migration directory, see the [module
+/// documentation](index.html).
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migration {
+    /// The numeric prefix of the file name.
+    pub version: u64,
+    /// The file name between the version and the extension.
+    pub name: String,
+    /// The contents of the forward file.
+    pub up: String,
+    /// The contents of the `.down.sql` file, if any.
+    pub down: Option<String>,
+}
+
+impl Migration {
+    /// Read the migrations in `dir`, ordered by version.
+    ///
+    /// <b>Errors</b>
+    ///
+    /// Returns [`Error::InvalidInput`
+    /// ](../error/enum.Error.html#variant.InvalidInput) if `dir` or a
+    /// migration file cannot be read, if two migrations have the same
+    /// version, or if there is a `.down.sql` file without a corresponding
+    /// forward file.
+    ///
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>, Error> {
+        static FILE_NAME: OnceLock<Regex> = OnceLock::new();
+        let file_name = FILE_NAME.get_or_init(|| {
+            Regex::new(r"^(\d+)_([^.]+?)(\.up|\.down)?\.sql$").unwrap()
+        });
+        let read_error = |path: &Path, e: std::io::Error| {
+            Error::invalid_input(&t!(
+                "file--read",
+                "path": path.display(),
+                "error": e,
+            ))
+        };
+        let mut ups: HashMap<u64, (String, String)> = HashMap::new();
+        let mut downs: HashMap<u64, (String, String)> = HashMap::new();
+        for entry in std::fs::read_dir(dir).map_err(|e| read_error(dir, e))? {
+            let path = entry.map_err(|e| read_error(dir, e))?.path();
+            let fnam = match path.file_name().and_then(|f| f.to_str()) {
+                Some(f) => f.to_string(),
+                None => continue,
+            };
+            let caps = match file_name.captures(&fnam) {
+                Some(c) => c,
+                None => continue,
+            };
+            let version: u64 = caps[1].parse().map_err(|_| {
+                Error::invalid_input(format!("migration-version: {fnam}"))
+            })?;
+            let name = caps[2].to_string();
+            let sql = std::fs::read_to_string(&path)
+                .map_err(|e| read_error(&path, e))?;
+            let map = match caps.get(3).map(|m| m.as_str()) {
+                Some(".down") => &mut downs,
+                _ => &mut ups,
+            };
+            if map.insert(version, (name, sql)).is_some() {
+                return Err(Error::invalid_input(format!(
+                    "migration-version-not-unique: {version}"
+                )));
+            }
+        }
+        let mut result = Vec::new();
+        for (version, (name, up)) in ups.drain() {
+            let down = match downs.remove(&version) {
+                Some((down_name, down)) if down_name == name => Some(down),
+                Some((down_name, _)) => {
+                    return Err(Error::invalid_input(format!(
+                        "migration-name-mismatch: {version} {name} \
+                        {down_name}"
+                    )));
+                }
+                None => None,
+            };
+            result.push(Self {
+                version,
+                name,
+                up,
+                down,
+            });
+        }
+        if let Some(version) = downs.keys().next() {
+            return Err(Error::invalid_input(format!(
+                "migration-down-without-up: {version}"
+            )));
+        }
+        result.sort_by_key(|m| m.version);
+        Ok(result)
+    }
+}
+
+/// The versions of the migrations applied to `db`, ascending.
+///
+/// Creates [`MIGRATIONS_TABLE`](constant.MIGRATIONS_TABLE.html) if it does
+/// not exist.
+///
+/// <b>Errors</b>
+///
+/// Forwards any error from the database.
+///
+pub fn applied(db: DatabaseIf) -> Result<Vec<u64>, Error> {
+    db.clone().exec(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} \
+            (version BIGINT PRIMARY KEY, name TEXT NOT NULL, \
+            applied_at BIGINT NOT NULL)"
+        ),
+        &[],
+    )?;
+    db.query(
+        &format!("SELECT version FROM {MIGRATIONS_TABLE} ORDER BY version"),
+        &[],
+        &[DbType::Int],
+    )?
+    .drain(..)
+    .map(|mut row| row.remove(0).try_into())
+    .collect()
+}
+
+/// Apply the migrations in `dir` that are not yet applied to `db`, in
+/// version order. Each migration is applied in its own transaction.
+///
+/// Returns the versions of the migrations applied, ascending.
+///
+/// <b>Errors</b>
+///
+/// See [`Migration::load_dir()`](struct.Migration.html#method.load_dir).
+///
+/// If a migration fails, the error is returned after rolling back that
+/// migration. Migrations applied before it remain applied. The text of a
+/// database error is prefixed by the migration version and name.
+///
+pub fn migrate(db: DatabaseIf, dir: &Path) -> Result<Vec<u64>, Error> {
+    let done = applied(db.clone())?;
+    let mut result = Vec::new();
+    for migration in Migration::load_dir(dir)? {
+        if done.contains(&migration.version) {
+            continue;
+        }
+        #[cfg(debug_assertions)]
+        eprintln!(
+            "applying migration {} {}",
+            migration.version, migration.name,
+        );
+        db.clone()
+            .transaction(|db| {
+                try_exec_sql(db.clone(), &migration.up, None)?;
+                db.exec(
+                    &format!(
+                        "INSERT INTO {MIGRATIONS_TABLE} \
+                        (version, name, applied_at) VALUES ($1, $2, $3)"
+                    ),
+                    &[
+                        migration.version.into(),
+                        migration.name.clone().into(),
+                        DbValue::Int(chrono::Utc::now().timestamp()),
+                    ],
+                )
+            })
+            .map_err(|e| migration_error(&migration, e))?;
+        result.push(migration.version);
+    }
+    Ok(result)
+}
+
+/// Revert the migrations applied to `db` that have a version greater than
+/// `version`, in descending version order. Each migration is reverted in its
+/// own transaction. `migrate_down(db, dir, 0)` reverts all migrations.
+///
+/// Returns the versions of the migrations reverted, descending.
+///
+/// <b>Errors</b>
+///
+/// See [`Migration::load_dir()`](struct.Migration.html#method.load_dir).
+///
+/// Returns [`Error::InvalidInput`
+/// ](../error/enum.Error.html#variant.InvalidInput) without reverting
+/// anything if a migration to revert is not in `dir` or has no `.down.sql`
+/// file.
+///
+/// If reverting a migration fails, the error is returned after rolling back
+/// that migration. Migrations reverted before it remain reverted. The text
+/// of a database error is prefixed by the migration version and name.
+///
+pub fn migrate_down(
+    db: DatabaseIf,
+    dir: &Path,
+    version: u64,
+) -> Result<Vec<u64>, Error> {
+    let mut migrations = Migration::load_dir(dir)?;
+    let mut to_revert = Vec::new();
+    for done in applied(db.clone())?.iter().rev() {
+        if *done <= version {
+            break;
+        }
+        match migrations.iter().position(|m| m.version == *done) {
+            Some(ix) if migrations[ix].down.is_some() => {
+                to_revert.push(migrations.remove(ix));
+            }
+            _ => {
+                return Err(Error::invalid_input(format!(
+                    "migration-cannot-revert: {done}"
+                )));
+            }
+        }
+    }
+    let mut result = Vec::new();
+    for migration in to_revert {
+        #[cfg(debug_assertions)]
+        eprintln!(
+            "reverting migration {} {}",
+            migration.version, migration.name,
+        );
+        db.clone()
+            .transaction(|db| {
+                try_exec_sql(
+                    db.clone(),
+                    migration.down.as_ref().unwrap(),
+                    None,
+                )?;
+                db.exec(
+                    &format!(
+                        "DELETE FROM {MIGRATIONS_TABLE} WHERE version = $1"
+                    ),
+                    &[migration.version.into()],
+                )
+            })
+            .map_err(|e| migration_error(&migration, e))?;
+        result.push(migration.version);
+    }
+    Ok(result)
+}
+
+/// The migrations in `dir` that are not yet applied to `db`, ordered by
+/// version.
+///
+/// <b>Errors</b>
+///
+/// See [`applied()`](fn.applied.html) and [`Migration::load_dir()`
+/// ](struct.Migration.html#method.load_dir).
+///
+pub fn pending(db: DatabaseIf, dir: &Path) -> Result<Vec<Migration>, Error> {
+    let done = applied(db)?;
+    Ok(Migration::load_dir(dir)?
+        .drain(..)
+        .filter(|m| !done.contains(&m.version))
+        .collect())
+}
+
+// Prefix the text of a database error with the migration version and name.
+fn migration_error(migration: &Migration, err: Error) -> Error {
+    match err {
+        Error::Database(mut db_err) => {
+            db_err.text = format!(
+                "migration {} {}: {}",
+                migration.version, migration.name, db_err.text,
+            );
+            Error::Database(db_err)
+        }
+        _ => err,
+    }
+}
@@ -0,0 +1,564 @@
+//! Descriptions of the database tables storing [`ActiveRecord`
+//! ](../active_record/trait.ActiveRecord.html) models, and SQL to create
+//! them, and to verify that the tables in a database match them.
+//!
+//! The descriptions are generated by the [derive macro
+//! ](../../vicocomo_active_record/derive.ActiveRecord.html) from the field
+//! types and attributes, see [`ActiveRecord::table_schema()`
+//! ](../active_record/trait.ActiveRecord.html#tymethod.table_schema) and
+//! [`define_schema!()`](../macro.define_schema.html).
+//!
+//! # Example
+//! ```text
+//! vicocomo::define_schema! { models::User, models::Post }
+//!
+//! std::fs::write("db/schema.sql", schema_sql(SqlDialect::Sqlite))?;
+//!
+//! // at startup
+//! if let Err(mismatches) =
+//!     vicocomo::verify_all!(db.clone(), models::User, models::Post)
+//! {
+//!     for mismatch in mismatches {
+//!         eprintln!("{}", mismatch);
+//!     }
+//! }
+//! ```
+
+use crate::{DatabaseIf, DbType, Error};
+use std::fmt;
+
+/// What to do with the referring rows when a referred row is deleted.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ForeignKeyAction {
+    /// `ON DELETE CASCADE`.
+    ///
+    Cascade,
+
+    /// The default, which works like `RESTRICT` outside a transaction but is
+    /// deferrable.
+    ///
+    NoAction,
+
+    /// `ON DELETE SET NULL`.
+    ///
+    SetNull,
+}
+
+/// A difference between a [`TableSchema`](struct.TableSchema.html) and the
+/// table in the database, see [`TableSchema::verify()`
+/// ](struct.TableSchema.html#method.verify).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaMismatch {
+    /// There is no `column` in `table`.
+    ///
+    MissingColumn { table: String, column: String },
+
+    /// There is no foreign key from `columns` in `table` to `remote_table`.
+    ///
+    MissingForeignKey {
+        table: String,
+        columns: Vec<String>,
+        remote_table: String,
+    },
+
+    /// The primary key of `table` is not `columns`.
+    ///
+    MissingPrimaryKey { table: String, columns: Vec<String> },
+
+    /// There is no table or view named `table`.
+    ///
+    MissingTable { table: String },
+
+    /// The `columns` in `table` are not unique.
+    ///
+    MissingUnique { table: String, columns: Vec<String> },
+
+    /// The `column` in `table` is nullable but should not be, or vice versa.
+    /// `nullable` tells what it should be.
+    ///
+    Nullability {
+        table: String,
+        column: String,
+        nullable: bool,
+    },
+
+    /// `table` could not be examined because of `error`.
+    ///
+    Unverifiable { table: String, error: Error },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingColumn { table, column } => {
+                write!(f, "{}: missing column {}", table, column)
+            }
+            Self::MissingForeignKey {
+                table,
+                columns,
+                remote_table,
+            } => write!(
+                f,
+                "{}: missing foreign key ({}) to {}",
+                table,
+                columns.join(", "),
+                remote_table,
+            ),
+            Self::MissingPrimaryKey { table, columns } => write!(
+                f,
+                "{}: primary key should be ({})",
+                table,
+                columns.join(", "),
+            ),
+            Self::MissingTable { table } => write!(f, "{}: missing", table),
+            Self::MissingUnique { table, columns } => write!(
+                f,
+                "{}: missing unique ({})",
+                table,
+                columns.join(", "),
+            ),
+            Self::Nullability {
+                table,
+                column,
+                nullable,
+            } => write!(
+                f,
+                "{}: column {} should {}be nullable",
+                table,
+                column,
+                if *nullable { "" } else { "not " },
+            ),
+            Self::Unverifiable { table, error } => {
+                write!(f, "{}: cannot verify: {}", table, error)
+            }
+        }
+    }
+}
+
+/// The SQL dialect to generate.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SqlDialect {
+    Postgres,
+    Sqlite,
+}
+
+/// A column in a [`TableSchema`](struct.TableSchema.html).
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnSchema {
+    /// The column name.
+    ///
+    pub name: String,
+
+    /// The type of the values stored. The column is `NOT NULL` unless the
+    /// type is one of the `Nul...` variants.
+    ///
+    pub db_type: DbType,
+
+    /// The SQL for a default value, if any.
+    ///
+    pub default: Option<String>,
+
+    /// The column is a single primary key that the database assigns when
+    /// inserting.
+    ///
+    pub auto_increment: bool,
+}
+
+/// A foreign key constraint, always `DEFERRABLE INITIALLY DEFERRED`.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignKeySchema {
+    /// The name of the table containing the foreign key.
+    ///
+    pub table: String,
+
+    /// The foreign key columns.
+    ///
+    pub columns: Vec<String>,
+
+    /// The name of the referred table.
+    ///
+    pub remote_table: String,
+
+    /// The referred columns, in the order of `columns`.
+    ///
+    pub remote_columns: Vec<String>,
+
+    /// What to do when a referred row is deleted.
+    ///
+    pub on_delete: ForeignKeyAction,
+}
+
+impl ForeignKeySchema {
+    /// Verify that the foreign key is in [`table`](#structfield.table) in
+    /// `db`, see [`TableSchema::mismatches()`
+    /// ](struct.TableSchema.html#method.mismatches).
+    ///
+    pub fn verify(&self, db: DatabaseIf) -> Vec<SchemaMismatch> {
+        match db.table_schema(&self.table) {
+            Ok(Some(actual)) if self.is_in(&actual) => Vec::new(),
+            Ok(Some(_)) => vec![self.missing()],
+            Ok(None) => vec![SchemaMismatch::MissingTable {
+                table: self.table.clone(),
+            }],
+            Err(error) => vec![SchemaMismatch::Unverifiable {
+                table: self.table.clone(),
+                error,
+            }],
+        }
+    }
+
+    fn is_in(&self, table: &TableSchema) -> bool {
+        table.foreign_keys.iter().any(|fk| {
+            fk.remote_table == self.remote_table
+                && same_columns(&fk.columns, &self.columns)
+        })
+    }
+
+    fn missing(&self) -> SchemaMismatch {
+        SchemaMismatch::MissingForeignKey {
+            table: self.table.clone(),
+            columns: self.columns.clone(),
+            remote_table: self.remote_table.clone(),
+        }
+    }
+}
+
+/// A database table.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableSchema {
+    /// The table name.
+    ///
+    pub name: String,
+
+    /// The columns in the order of the model fields.
+    ///
+    pub columns: Vec<ColumnSchema>,
+
+    /// The primary key columns, empty if none.
+    ///
+    pub primary_key: Vec<String>,
+
+    /// The column tuples that should be unique.
+    ///
+    pub uniques: Vec<Vec<String>>,
+
+    /// The foreign keys in this table.
+    ///
+    pub foreign_keys: Vec<ForeignKeySchema>,
+}
+
+impl TableSchema {
+    /// A `CREATE TABLE` statement in `dialect`, terminated by a semicolon and
+    /// a newline.
+    ///
+    pub fn create_table_sql(&self, dialect: SqlDialect) -> String {
+        let mut items: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| column_sql(c, dialect))
+            .collect();
+        if !self.primary_key.is_empty()
+            && !self.columns.iter().any(|c| c.auto_increment)
+        {
+            items.push(format!(
+                "PRIMARY KEY({})",
+                self.primary_key.join(", ")
+            ));
+        }
+        for unique in &self.uniques {
+            items.push(format!("UNIQUE({})", unique.join(", ")));
+        }
+        for fk in &self.foreign_keys {
+            items.push(format!(
+                "FOREIGN KEY({}) REFERENCES {}({}){} \
+                DEFERRABLE INITIALLY DEFERRED",
+                fk.columns.join(", "),
+                fk.remote_table,
+                fk.remote_columns.join(", "),
+                match fk.on_delete {
+                    ForeignKeyAction::Cascade => " ON DELETE CASCADE",
+                    ForeignKeyAction::NoAction => "",
+                    ForeignKeyAction::SetNull => " ON DELETE SET NULL",
+                },
+            ));
+        }
+        format!(
+            "CREATE TABLE {} (\n    {}\n);\n",
+            self.name,
+            items.join(",\n    "),
+        )
+    }
+
+    /// The differences between `self` and `actual`, e.g. a description of a
+    /// table in the database from [`DatabaseIf::table_schema()`
+    /// ](../database/struct.DatabaseIf.html#method.table_schema).
+    ///
+    /// Column types and defaults are not compared. A unique constraint is
+    /// there if `actual` has a unique constraint or primary key on the same
+    /// columns, in any order. A foreign key is there if `actual` has one from
+    /// the same columns to the same remote table.
+    ///
+    /// If `columns_only` only missing columns are reported, e.g. for a view.
+    ///
+    pub fn mismatches(
+        &self,
+        actual: &TableSchema,
+        columns_only: bool,
+    ) -> Vec<SchemaMismatch> {
+        let mut result = Vec::new();
+        for col in &self.columns {
+            match actual.columns.iter().find(|c| c.name == col.name) {
+                Some(act) => {
+                    if !columns_only
+                        && act.db_type.is_nullable()
+                            != col.db_type.is_nullable()
+                    {
+                        result.push(SchemaMismatch::Nullability {
+                            table: self.name.clone(),
+                            column: col.name.clone(),
+                            nullable: col.db_type.is_nullable(),
+                        });
+                    }
+                }
+                None => result.push(SchemaMismatch::MissingColumn {
+                    table: self.name.clone(),
+                    column: col.name.clone(),
+                }),
+            }
+        }
+        if columns_only {
+            return result;
+        }
+        if !self.primary_key.is_empty()
+            && !same_columns(&self.primary_key, &actual.primary_key)
+        {
+            result.push(SchemaMismatch::MissingPrimaryKey {
+                table: self.name.clone(),
+                columns: self.primary_key.clone(),
+            });
+        }
+        for unique in &self.uniques {
+            if !same_columns(unique, &actual.primary_key)
+                && !actual.uniques.iter().any(|u| same_columns(unique, u))
+            {
+                result.push(SchemaMismatch::MissingUnique {
+                    table: self.name.clone(),
+                    columns: unique.clone(),
+                });
+            }
+        }
+        for fk in &self.foreign_keys {
+            if !fk.is_in(actual) {
+                result.push(fk.missing());
+            }
+        }
+        result
+    }
+
+    /// The [`mismatches()`](#method.mismatches) with the table in `db`.
+    ///
+    pub fn verify(
+        &self,
+        db: DatabaseIf,
+        columns_only: bool,
+    ) -> Vec<SchemaMismatch> {
+        match db.table_schema(&self.name) {
+            Ok(Some(actual)) => self.mismatches(&actual, columns_only),
+            Ok(None) => vec![SchemaMismatch::MissingTable {
+                table: self.name.clone(),
+            }],
+            Err(error) => vec![SchemaMismatch::Unverifiable {
+                table: self.name.clone(),
+                error,
+            }],
+        }
+    }
+
+    /// The names of other tables that this table has foreign keys to.
+    ///
+    pub fn dependencies(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for fk in &self.foreign_keys {
+            if fk.remote_table != self.name
+                && !result.contains(&fk.remote_table.as_str())
+            {
+                result.push(&fk.remote_table);
+            }
+        }
+        result
+    }
+}
+
+/// `CREATE TABLE` statements for `tables` in `dialect`.
+///
+/// A table is created after the tables it has foreign keys to, if possible.
+/// Otherwise, e.g. if there is a cycle, the order of `tables` is kept.
+///
+/// If more than one table has the same name, only the first one is used.
+/// This is the normal case for the join table of a many-to-many association
+/// declared in both models.
+///
+pub fn create_schema_sql(
+    tables: &[TableSchema],
+    dialect: SqlDialect,
+) -> String {
+    let mut remaining: Vec<&TableSchema> = Vec::new();
+    for table in tables {
+        if !remaining.iter().any(|t| t.name == table.name) {
+            remaining.push(table);
+        }
+    }
+    let mut result = String::new();
+    let mut created: Vec<&str> = Vec::new();
+    while !remaining.is_empty() {
+        let ix = remaining
+            .iter()
+            .position(|t| {
+                t.dependencies().iter().all(|dep| {
+                    created.contains(dep)
+                        || !remaining.iter().any(|r| r.name == *dep)
+                })
+            })
+            .unwrap_or(0);
+        let table = remaining.remove(ix);
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        result.push_str(&table.create_table_sql(dialect));
+        created.push(&table.name);
+    }
+    result
+}
+
+/// Define a function returning the SQL to create the tables storing the
+/// given models.
+///
+/// `$model` are the models, e.g. `crate::models::MyModel, ...`. Models that
+/// are [`readonly()`
+/// ](active_record/trait.ActiveRecord.html#tymethod.readonly) are ignored.
+///
+/// The generated function:
+///
+/// ##### `pub fn schema_sql(dialect: `[`SqlDialect`](schema/enum.SqlDialect.html)`) -> String`
+///
+/// Returns [`create_schema_sql()`](schema/fn.create_schema_sql.html) for the
+/// [`table_schema()`
+/// ](active_record/trait.ActiveRecord.html#tymethod.table_schema) and
+/// [`join_table_schemas()`
+/// ](active_record/trait.ActiveRecord.html#tymethod.join_table_schemas) of
+/// the `$model`s, so that the tables are created in dependency order.
+///
+#[macro_export]
+macro_rules! define_schema {
+    ( $( $model:ty ),+ $( , )? ) => {
+        pub fn schema_sql(dialect: ::vicocomo::SqlDialect) -> String {
+            let mut tables = Vec::new();
+        $(
+            if !<$model as ::vicocomo::ActiveRecord>::readonly() {
+                tables.push(
+                    <$model as ::vicocomo::ActiveRecord>::table_schema()
+                );
+                tables.extend(
+                    <$model as ::vicocomo::ActiveRecord>::join_table_schemas()
+                );
+            }
+        )*
+            ::vicocomo::schema::create_schema_sql(&tables, dialect)
+        }
+    };
+}
+
+/// Verify that the tables storing the given models match the models, see
+/// [`ActiveRecord::verify_schema()`
+/// ](active_record/trait.ActiveRecord.html#method.verify_schema).
+///
+/// `$db` is a [`DatabaseIf`](database/struct.DatabaseIf.html), `$model` are
+/// the models, e.g. `crate::models::MyModel, ...`.
+///
+/// Evaluates to `Result<(), Vec<`[`SchemaMismatch`
+/// ](schema/enum.SchemaMismatch.html)`>>`, with each mismatch reported once
+/// even if e.g. a join table is verified for two models.
+///
+#[macro_export]
+macro_rules! verify_all {
+    ( $db:expr, $( $model:ty ),+ $( , )? ) => {{
+        let db: ::vicocomo::DatabaseIf = $db;
+        let mut mismatches: Vec<::vicocomo::SchemaMismatch> = Vec::new();
+    $(
+        if let Err(found) =
+            <$model as ::vicocomo::ActiveRecord>::verify_schema(db.clone())
+        {
+            for mismatch in found {
+                if !mismatches.contains(&mismatch) {
+                    mismatches.push(mismatch);
+                }
+            }
+        }
+    )+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }};
+}
+
+// --- private ---------------------------------------------------------------
+
+fn column_sql(col: &ColumnSchema, dialect: SqlDialect) -> String {
+    if col.auto_increment {
+        return match dialect {
+            SqlDialect::Postgres => {
+                format!("{} BIGSERIAL PRIMARY KEY", col.name)
+            }
+            SqlDialect::Sqlite => {
+                format!("{} INTEGER PRIMARY KEY AUTOINCREMENT", col.name)
+            }
+        };
+    }
+    let (typ, not_null) = match col.db_type {
+        DbType::Blob => (blob_sql(dialect), true),
+        DbType::Bool => ("BOOLEAN", true),
+        DbType::Date => ("DATE", true),
+        DbType::DateTime => ("TIMESTAMP", true),
+        DbType::Float => ("FLOAT(53)", true),
+        DbType::Int => ("BIGINT", true),
+        DbType::Text => ("TEXT", true),
+        DbType::Time => ("TIME", true),
+        DbType::NulBlob => (blob_sql(dialect), false),
+        DbType::NulBool => ("BOOLEAN", false),
+        DbType::NulDate => ("DATE", false),
+        DbType::NulDateTime => ("TIMESTAMP", false),
+        DbType::NulFloat => ("FLOAT(53)", false),
+        DbType::NulInt => ("BIGINT", false),
+        DbType::NulText => ("TEXT", false),
+        DbType::NulTime => ("TIME", false),
+    };
+    let mut result = format!("{} {}", col.name, typ);
+    if not_null {
+        result.push_str(" NOT NULL");
+    }
+    if let Some(default) = col.default.as_ref() {
+        result.push_str(" DEFAULT ");
+        result.push_str(default);
+    }
+    result
+}
+
+fn blob_sql(dialect: SqlDialect) -> &'static str {
+    match dialect {
+        SqlDialect::Postgres => "BYTEA",
+        SqlDialect::Sqlite => "BLOB",
+    }
+}
+
+// Whether the column lists have the same columns, in any order.
+fn same_columns(cols1: &[String], cols2: &[String]) -> bool {
+    cols1.len() == cols2.len() && cols1.iter().all(|c| cols2.contains(c))
+}
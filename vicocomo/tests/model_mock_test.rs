@@ -0,0 +1,10 @@
+#[test]
+fn test_model_mock() {
+    ljumvall_test_utils::test_crate(
+        "../vicocomo/examples/model/mock",
+        &["run"],
+        false,
+        false,
+        ljumvall_test_utils::TestCommandOutput::Whatever,
+    );
+}
@@ -95,10 +95,27 @@ pub(crate) fn belongs_to_impl(
                 )
             }
         };
+        // the fk of obj, skipping obj if None
+        let obj_fk_expr: Expr = if bel_fld.dbt.nul() {
+            parse_quote!(
+                match obj.#fk_id {
+                    Some(ref fk) => fk,
+                    None => continue,
+                }
+            )
+        } else {
+            parse_quote!(obj.#fk_id)
+        };
+        let fk_col = &bel_fld.col;
+        let remote_pk_col =
+            LitStr::new(&remote_pk.to_string(), Span::call_site());
         let assoc_snake = assoc_name.to_snake();
         let all_belonging_to_id =
             format_ident!("all_belonging_to_{}", assoc_snake);
         let get_id = format_ident!("{}", assoc_snake);
+        let load_grouped_id =
+            format_ident!("load_grouped_by_{}", assoc_snake);
+        let preload_id = format_ident!("preload_{}", assoc_snake);
         let set_id = format_ident!("set_{}", assoc_snake);
         let siblings_id = format_ident!("{}_siblings", assoc_snake);
         struct_fn.push(parse_quote!(
@@ -124,6 +141,87 @@ pub(crate) fn belongs_to_impl(
                 #remote_type::find(db.clone(), #fk_expr_opt)
             }
         ));
+        struct_fn.push(parse_quote!(
+            pub fn #load_grouped_id(
+                db: ::vicocomo::DatabaseIf,
+                remotes: &[#remote_type],
+            ) -> Result<
+                ::std::collections::HashMap<
+                    <#remote_type as ::vicocomo::ActiveRecord>::PkType,
+                    Vec<Self>,
+                >,
+                ::vicocomo::Error,
+            > {
+                use ::vicocomo::ActiveRecord;
+                let mut result = ::std::collections::HashMap::new();
+                let mut values = Vec::new();
+                for remote in remotes {
+                    if let Some(pk) = remote.pk_value() {
+                        if !result.contains_key(&pk) {
+                            values.push(pk.clone().into());
+                            result.insert(pk, Vec::new());
+                        }
+                    }
+                }
+                if values.is_empty() {
+                    return Ok(result);
+                }
+                for obj in Self::query(
+                    db,
+                    &::vicocomo::QueryBld::new()
+                        .col(#fk_col)
+                        .in_list(&values)
+                        .query()
+                        .unwrap(),
+                )? {
+                    let fk: <#remote_type as ActiveRecord>::PkType =
+                        #obj_fk_expr.clone().into();
+                    if let Some(objs) = result.get_mut(&fk) {
+                        objs.push(obj);
+                    }
+                }
+                Ok(result)
+            }
+        ));
+        struct_fn.push(parse_quote!(
+            pub fn #preload_id(
+                db: ::vicocomo::DatabaseIf,
+                objs: &[Self],
+            ) -> Result<
+                ::std::collections::HashMap<
+                    <#remote_type as ::vicocomo::ActiveRecord>::PkType,
+                    #remote_type,
+                >,
+                ::vicocomo::Error,
+            > {
+                use ::vicocomo::ActiveRecord;
+                let mut result = ::std::collections::HashMap::new();
+                let mut seen = ::std::collections::HashSet::new();
+                let mut values = Vec::new();
+                for obj in objs {
+                    let fk: ::vicocomo::DbValue = #obj_fk_expr.clone().into();
+                    if seen.insert(fk.sql_value()) {
+                        values.push(fk);
+                    }
+                }
+                if values.is_empty() {
+                    return Ok(result);
+                }
+                for remote in <#remote_type as ActiveRecord>::query(
+                    db,
+                    &::vicocomo::QueryBld::new()
+                        .col(#remote_pk_col)
+                        .in_list(&values)
+                        .query()
+                        .unwrap(),
+                )? {
+                    if let Some(pk) = remote.pk_value() {
+                        result.insert(pk, remote);
+                    }
+                }
+                Ok(result)
+            }
+        ));
         struct_fn.push(parse_quote!(
             pub fn #set_id(&mut self, remote: &#remote_type)
                 -> Result<(), ::vicocomo::Error>
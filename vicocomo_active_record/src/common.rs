@@ -46,23 +46,28 @@ pub(crate) fn common(
         }
     }
 
+    // The name of the first restricting association that has children of
+    // the object with primary key pk, counting soft deleted children only if
+    // with_deleted.
     struct_fn.push(parse_quote!(
         fn __vicocomo__first_that_has_children(
             db: ::vicocomo::DatabaseIf,
             pk: <Self as ::vicocomo::ActiveRecord>::PkType,
+            with_deleted: bool,
         ) -> Option<String> {
             use ::vicocomo::ActiveRecord;
 
             let mut result = None;
         #(
             if result.is_none() {
+                let mut bld = ::vicocomo::QueryBld::new()
+                    .filter(#filter, &[Some(pk.clone().into())]);
+                if with_deleted {
+                    bld = bld.with_deleted();
+                }
                 if let Ok(found) = #child_type::query(
                     db.clone(),
-                    ::vicocomo::QueryBld::new()
-                        .filter(#filter, &[Some(pk.clone().into())])
-                        .query()
-                        .as_ref()
-                        .unwrap(),
+                    bld.query().as_ref().unwrap(),
                 ) {
                     if !found.is_empty() {
                         result = Some(#assoc_lit.to_string());
@@ -118,9 +123,15 @@ pub(crate) fn common(
     let pk_exists_conversion: Expr = if model.pk_fields().is_empty() {
         parse_quote!(())
     } else {
+        // a soft deleted row also occupies the primary key
+        let find_fn: Ident = if model.soft_delete_field().is_some() {
+            parse_quote!(find_with_deleted)
+        } else {
+            parse_quote!(find)
+        };
         parse_quote!(if !update {
             if let Some(pk_val) = self.pk_value() {
-                if Self::find(db.clone(), &pk_val).is_some() {
+                if Self::#find_fn(db.clone(), &pk_val).is_some() {
                     return Some(Self::__vicocomo__pk_error(
                         ::vicocomo::ModelErrorKind::CannotSave,
                         self.pk_value(),
@@ -144,6 +155,8 @@ pub(crate) fn common(
             find_by_id,
             find_eq_id: _,
             find_self_args,
+            upsert_by_id: _,
+            upsert_batch_by_id: _,
         } in &model.uniques
         {
             find_args.push(find_self_args.clone());
@@ -223,6 +236,36 @@ pub(crate) fn common(
             )
         };
 
+    let mut col_lit = Vec::new();
+    let mut fld_lit = Vec::new();
+    for fld in &model.fields {
+        col_lit.push(fld.col.clone());
+        fld_lit.push(LitStr::new(&fld.id.to_string(), Span::call_site()));
+    }
+    let table_lit = LitStr::new(&model.table_name, Span::call_site());
+    let constraint_violation_conversion: Expr = parse_quote!({
+        let kind = if err.is_not_null_violation() {
+            Some((::vicocomo::ModelErrorKind::NotNull, "not-null-violation"))
+        } else if err.is_check_violation() {
+            Some((::vicocomo::ModelErrorKind::CheckFailed, "check-violation"))
+        } else {
+            None
+        };
+        if let Some((error, general)) = kind {
+            return Some(::vicocomo::Error::Model(::vicocomo::ModelError {
+                error,
+                model: #struct_lit.to_string(),
+                general: Some(general.to_string()),
+                field_errors: [ #( (#fld_lit, #col_lit) ),* ]
+                    .iter()
+                    .filter(|(_, col)| err.mentions_column(#table_lit, col))
+                    .map(|(fld, _)| (fld.to_string(), Vec::new()))
+                    .collect(),
+                assoc_errors: Vec::new(),
+            }));
+        }
+    });
+
     struct_fn.push(parse_quote!(
         #[doc(hidden)]
         fn __vicocomo__conv_save_error(
@@ -234,6 +277,7 @@ pub(crate) fn common(
             use ::vicocomo::ActiveRecord;
             #foreign_key_violation_conversion;
             #unique_violation_conversion;
+            #constraint_violation_conversion;
             None
         }
     ));
@@ -293,4 +337,44 @@ pub(crate) fn common(
             })
         }
     ));
+
+    // --- __vicocomo__missing_error
+
+    if model.readonly || model.pk_fields().is_empty() {
+        return;
+    }
+    let stale_check: Expr = if model.lock_field().is_some() {
+        parse_quote!(
+            if let Some(pk) = pk.as_ref() {
+                if Self::find(db, pk).is_some() {
+                    return ::vicocomo::Error::Model(::vicocomo::ModelError {
+                        error: ::vicocomo::ModelErrorKind::StaleObject,
+                        model: #struct_lit.to_string(),
+                        general: None,
+                        field_errors: Vec::new(),
+                        assoc_errors: Vec::new(),
+                    });
+                }
+            }
+        )
+    } else {
+        parse_quote!(drop(db))
+    };
+
+    // Return the error to use when no row was updated or deleted: If the
+    // model has a vicocomo_lock_version field and the row still exists,
+    // ModelErrorKind::StaleObject, else as __vicocomo__pk_error().
+    struct_fn.push(parse_quote!(
+        #[doc(hidden)]
+        fn __vicocomo__missing_error(
+            &self,
+            db: ::vicocomo::DatabaseIf,
+            kind: ::vicocomo::ModelErrorKind,
+        ) -> ::vicocomo::Error {
+            use ::vicocomo::ActiveRecord;
+            let pk = self.pk_value();
+            #stale_check;
+            Self::__vicocomo__pk_error(kind, pk, true)
+        }
+    ));
 }
@@ -1,9 +1,9 @@
-use crate::model::Model;
+use crate::model::{Model, OnDelete};
 use ::syn::ItemFn;
 
 pub(crate) fn delete_impl(
     model: &Model,
-    _struct_fn: &mut Vec<ItemFn>,
+    struct_fn: &mut Vec<ItemFn>,
     trait_fn: &mut Vec<ItemFn>,
 ) {
     use ::syn::parse_quote;
@@ -12,19 +12,33 @@ pub(crate) fn delete_impl(
     let pk_len = pk_fields.len();
 
     use ::proc_macro2::Span;
-    use ::syn::{Expr, LitStr};
+    use ::syn::{Expr, Ident, LitStr};
 
     let Model {
         struct_id,
         ref table_name,
-        has_many: _,
+        ref has_many,
+        after_delete,
+        after_save: _,
         before_delete,
         before_save: _,
+        delete_batch_hooks,
         readonly,
+        validate: _,
+        original: _,
         fields: _,
         uniques: _,
     } = model;
 
+    if let Some(sdl) = model.soft_delete_field() {
+        let sdl_col = sdl.col.value();
+        trait_fn.push(parse_quote!(
+            fn soft_delete_column() -> Option<String> {
+                Some(#sdl_col.to_string())
+            }
+        ));
+    }
+
     if *readonly || pk_len == 0 {
         trait_fn.push(parse_quote!(
             fn delete(
@@ -44,17 +58,17 @@ pub(crate) fn delete_impl(
             }
         ));
     } else {
+        let pk_cols = pk_fields
+            .iter()
+            .map(|pk| pk.col.value())
+            .collect::<Vec<_>>()
+            .join(", ");
         let batch_expr = model.pk_batch_expr("batch").unwrap();
         let batch_sql_format = LitStr::new(
             format!(
                 // "DELETE FROM tbl WHERE (pk1, pk2) IN (($1, $2), ($3, $4))"
                 "DELETE FROM {} WHERE ({}) IN ({{}})",
-                table_name,
-                &pk_fields
-                    .iter()
-                    .map(|pk| pk.col.value())
-                    .collect::<Vec<_>>()
-                    .join(", "),
+                table_name, &pk_cols,
             )
             .as_str(),
             Span::call_site(),
@@ -63,43 +77,156 @@ pub(crate) fn delete_impl(
             parse_quote!(batch.len()),
             parse_quote!(#pk_len),
         );
-        let before_delete_expr: Expr = if *before_delete {
-            parse_quote!({
-                ::vicocomo::BeforeDelete::before_delete(
-                    &mut self,
-                    db.clone(),
-                )?
-            })
+        let before_delete_expr = |obj: Expr| -> Expr {
+            if *before_delete {
+                parse_quote!(
+                    ::vicocomo::BeforeDelete::before_delete(#obj, db.clone())?
+                )
+            } else {
+                parse_quote!(())
+            }
+        };
+        let after_delete_expr = |obj: Expr| -> Expr {
+            if *after_delete {
+                parse_quote!(
+                    ::vicocomo::AfterDelete::after_delete(#obj, db.clone())?
+                )
+            } else {
+                parse_quote!(())
+            }
+        };
+        // the body of delete() given the variable pk, calling the hooks
+        // around delete_expr, in the same transaction if there is an
+        // AfterDelete hook
+        let delete_body = |delete_expr: Expr| -> Expr {
+            let before_delete_expr =
+                before_delete_expr(parse_quote!(&mut self));
+            let after_delete_expr =
+                after_delete_expr(parse_quote!(&mut self));
+            Model::transaction_expr(
+                *after_delete,
+                parse_quote!({
+                    #before_delete_expr;
+                    #delete_expr?;
+                    #after_delete_expr;
+                    Ok(())
+                }),
+            )
+        };
+        // with vicocomo_delete_batch_hooks, delete_batch() calls the hooks
+        // and the batch function without hooks, which is also used by
+        // delete(), is renamed
+        let batch_fn: Ident = if *delete_batch_hooks {
+            parse_quote!(__vicocomo__delete_batch)
         } else {
-            parse_quote!(())
+            parse_quote!(delete_batch)
         };
+        let pk_none_expr: Expr =
+            parse_quote!(Err(Self::__vicocomo__pk_error(
+                ::vicocomo::ModelErrorKind::CannotDelete,
+                None,
+                true,
+            )));
         let struct_lit =
             LitStr::new(&struct_id.to_string(), Span::call_site());
-        trait_fn.push(parse_quote!(
-            fn delete(
-                mut self,
+        // with a vicocomo_lock_version field, delete only if the version is
+        // unchanged
+        let delete_expr = |batch_fn: Expr| -> Expr {
+            match model.lock_field() {
+                Some(lck) => {
+                    let lck_id = &lck.id;
+                    let pk_db_values = model.pk_db_values();
+                    let del_sql = LitStr::new(
+                        &format!(
+                            "DELETE FROM {} WHERE {} AND {} = ${}",
+                            table_name,
+                            model.pk_select().value(),
+                            lck.col.value(),
+                            pk_len + 1,
+                        ),
+                        Span::call_site(),
+                    );
+                    parse_quote!({
+                        let mut del_pars = #pk_db_values;
+                        del_pars.push(self.#lck_id.clone().into());
+                        match db.clone().exec(#del_sql, &del_pars) {
+                            Ok(1) => Ok(()),
+                            Ok(_) => Err(self.__vicocomo__missing_error(
+                                db,
+                                ::vicocomo::ModelErrorKind::CannotDelete,
+                            )),
+                            Err(err) => {
+                                Err(Self::__vicocomo__conv_delete_error(
+                                    db,
+                                    &[pk],
+                                    err,
+                                ))
+                            }
+                        }
+                    })
+                }
+                None => {
+                    parse_quote!(#batch_fn(db.clone(), &[pk]).map(|_| ()))
+                }
+            }
+        };
+
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            fn __vicocomo__restricted_error(
+                assoc: String,
+            ) -> ::vicocomo::Error {
+                ::vicocomo::Error::Model(::vicocomo::ModelError {
+                    error: ::vicocomo::ModelErrorKind::CannotDelete,
+                    model: #struct_lit.to_string(),
+                    general: Some("foreign-key-violation".to_string()),
+                    field_errors: Vec::new(),
+                    assoc_errors: vec![(
+                        assoc,
+                        vec!["restricted".to_string()],
+                    )],
+                })
+            }
+        ));
+
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            fn __vicocomo__conv_delete_error(
                 db: ::vicocomo::DatabaseIf,
-            ) -> Result<(), ::vicocomo::Error> {
-                match self.pk_value() {
-                    Some(pk) => {
-                        #before_delete_expr;
-                        Self::delete_batch(db.clone(), &[pk]).map(|_| ())
+                batch: &[<Self as ::vicocomo::ActiveRecord>::PkType],
+                err: ::vicocomo::Error,
+            ) -> ::vicocomo::Error {
+                if err.is_foreign_key_violation() {
+                    for pk in batch {
+                        if let Some(assoc) =
+                            Self::__vicocomo__first_that_has_children(
+                                db.clone(),
+                                pk.clone(),
+                                true,
+                            )
+                        {
+                            return Self::__vicocomo__restricted_error(assoc);
+                        }
                     }
-                    None => Err(Self::__vicocomo__pk_error(
-                        ::vicocomo::ModelErrorKind::CannotDelete,
-                        None,
-                        true,
-                    )),
                 }
+                err
             }
         ));
 
+        // Without a soft delete field this is delete_batch(), with one it
+        // is used by purge().
+        let find_fn: Ident = if model.soft_delete_field().is_some() {
+            parse_quote!(find_with_deleted)
+        } else {
+            parse_quote!(find)
+        };
         #[allow(non_snake_case)]
-        trait_fn.push(parse_quote!(
+        let hard_delete_batch: ItemFn = parse_quote!(
             fn delete_batch(
                 db: ::vicocomo::DatabaseIf,
-                batch: &[Self::PkType],
+                batch: &[<Self as ::vicocomo::ActiveRecord>::PkType],
             ) -> Result<usize, ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
                 if batch.is_empty() {
                     return Ok(0);
                 }
@@ -111,9 +238,11 @@ pub(crate) fn delete_impl(
                         if deleted_count == batch.len() {
                             Ok(deleted_count)
                         } else {
-                            let mut missing_pk: Option<Self::PkType> = None;
+                            let mut missing_pk: Option<
+                                <Self as ActiveRecord>::PkType,
+                            > = None;
                             for pk in batch {
-                                if Self::find(db.clone(), pk).is_none() {
+                                if Self::#find_fn(db.clone(), pk).is_none() {
                                     missing_pk = Some(pk.clone());
                                     break;
                                 }
@@ -126,40 +255,563 @@ pub(crate) fn delete_impl(
                         }
                     }
                     Err(err) => {
-                        if err.is_foreign_key_violation() {
-                            for pk in batch {
-                                if let Some(assoc) =
-                                    Self::__vicocomo__first_that_has_children(
-                                        db.clone(),
-                                        pk.clone(),
-                                    )
-                                {
-                                    return Err(::vicocomo::Error::Model(
-                                        ::vicocomo::ModelError {
-                                            error: ::vicocomo::ModelErrorKind
-                                                ::CannotDelete,
-                                            model: #struct_lit.to_string(),
-                                            general:
-                                                Some(
-                                                    "foreign-key-violation"
-                                                        .to_string(),
-                                                ),
-                                            field_errors: Vec::new(),
-                                            assoc_errors: vec![(
-                                                assoc,
-                                                vec![
-                                                    "restricted".to_string()
-                                                ],
-                                            )],
-                                        }
-                                    ));
+                        Err(Self::__vicocomo__conv_delete_error(
+                            db, batch, err,
+                        ))
+                    }
+                }
+            }
+        );
+
+        let with_hooks: Option<ItemFn> = if *delete_batch_hooks {
+            let before_delete_expr = before_delete_expr(parse_quote!(obj));
+            let after_delete_expr = after_delete_expr(parse_quote!(obj));
+            Some(parse_quote!(
+                fn delete_batch(
+                    db: ::vicocomo::DatabaseIf,
+                    batch: &[Self::PkType],
+                ) -> Result<usize, ::vicocomo::Error> {
+                    db.clone().transaction(|db| {
+                        let mut objs = Vec::new();
+                        for pk in batch {
+                            match Self::find(db.clone(), pk) {
+                                Some(obj) => objs.push(obj),
+                                None => {
+                                    return Err(Self::__vicocomo__pk_error(
+                                        ::vicocomo::ModelErrorKind
+                                            ::CannotDelete,
+                                        Some(pk.clone()),
+                                        true,
+                                    ))
                                 }
                             }
                         }
-                        Err(err)
+                        for obj in objs.iter_mut() {
+                            #before_delete_expr;
+                        }
+                        let deleted_count =
+                            Self::#batch_fn(db.clone(), batch)?;
+                        for obj in objs.iter_mut() {
+                            #after_delete_expr;
+                        }
+                        Ok(deleted_count)
+                    })
+                }
+            ))
+        } else {
+            None
+        };
+
+        let sdl = match model.soft_delete_field() {
+            Some(sdl) => sdl,
+            None => {
+                let delete_body =
+                    delete_body(delete_expr(parse_quote!(Self::#batch_fn)));
+                trait_fn.push(parse_quote!(
+                    fn delete(
+                        mut self,
+                        db: ::vicocomo::DatabaseIf,
+                    ) -> Result<(), ::vicocomo::Error> {
+                        match self.pk_value() {
+                            Some(pk) => #delete_body,
+                            None => #pk_none_expr,
+                        }
+                    }
+                ));
+                push_delete_batch(
+                    hard_delete_batch,
+                    with_hooks,
+                    struct_fn,
+                    trait_fn,
+                );
+                return;
+            }
+        };
+
+        // --- soft delete ---------------------------------------------------
+
+        let sdl_id = &sdl.id;
+        let sdl_col = sdl.col.value();
+        let now_value = Model::timestamp_value_expr(sdl, parse_quote!(now));
+        let deleted_value: Expr = if sdl.nat {
+            parse_quote!(::vicocomo::NativeField(#now_value).into())
+        } else {
+            parse_quote!(#now_value.into())
+        };
+
+        let mut hard_delete_batch = hard_delete_batch;
+        hard_delete_batch.sig.ident = parse_quote!(__vicocomo__purge_batch);
+        hard_delete_batch.attrs.push(parse_quote!(#[doc(hidden)]));
+        struct_fn.push(hard_delete_batch);
+
+        // children of restricting associations prevent soft deleting, and
+        // children of cascading one-to-many associations, which must be soft
+        // delete models, are soft deleted at the same time as the parent
+        let mut cascade_type = Vec::new();
+        let mut cascade_fk_col = Vec::new();
+        for hm in has_many {
+            if hm.on_delete == OnDelete::Cascade && hm.many_to_many.is_none()
+            {
+                cascade_type.push(&hm.remote_type);
+                cascade_fk_col.push(hm.remote_fk_col.clone());
+            }
+        }
+        let cascade_expr: Expr = if cascade_type.is_empty() {
+            parse_quote!(())
+        } else {
+            parse_quote!({
+                use ::vicocomo::ActiveRecord;
+                let values: Vec<::vicocomo::DbValue> =
+                    batch.iter().map(|pk| pk.clone().into()).collect();
+            #(
+                let mut children = Vec::new();
+                for child in #cascade_type::query(
+                    db.clone(),
+                    &::vicocomo::QueryBld::new()
+                        .col(#cascade_fk_col)
+                        .in_list(&values)
+                        .query()
+                        .unwrap(),
+                )? {
+                    if let Some(pk) = child.pk_value() {
+                        children.push(pk);
+                    }
+                }
+                #cascade_type::delete_batch(db.clone(), &children)?;
+            )*
+            })
+        };
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            fn __vicocomo__soft_delete_children(
+                db: ::vicocomo::DatabaseIf,
+                batch: &[<Self as ::vicocomo::ActiveRecord>::PkType],
+            ) -> Result<(), ::vicocomo::Error> {
+                for pk in batch {
+                    if let Some(assoc) =
+                        Self::__vicocomo__first_that_has_children(
+                            db.clone(),
+                            pk.clone(),
+                            false,
+                        )
+                    {
+                        return Err(Self::__vicocomo__restricted_error(assoc));
+                    }
+                }
+                #cascade_expr;
+                Ok(())
+            }
+        ));
+
+        // When cascading, the time is truncated to whole seconds and fixed
+        // for the children, so that restore() finds them by comparing the
+        // times also if the columns have different precision. It is moved
+        // past the deletion time of any child that is already soft deleted,
+        // so that restore() finds only those deleted by the cascade.
+        let fix_now = |pks: Expr| -> Option<::syn::Stmt> {
+            if cascade_type.is_empty() {
+                return None;
+            }
+            Some(parse_quote!(
+                let db = {
+                    let now = db.now();
+                    let mut at = now - ::std::time::Duration::from_nanos(
+                        now.timestamp_subsec_nanos().into(),
+                    );
+                    let parents: Vec<::vicocomo::DbValue> =
+                        #pks.iter().map(|pk| pk.clone().into()).collect();
+                    let mut last: Option<i64> = None;
+                #(
+                    last = last.max(
+                        #cascade_type::__vicocomo__last_deleted(
+                            db.clone(),
+                            #cascade_fk_col,
+                            &parents,
+                        )?,
+                    );
+                )*
+                    if let Some(last) = last {
+                        if last >= at.timestamp() {
+                            at = at + ::std::time::Duration::from_secs(
+                                (last - at.timestamp() + 1) as u64,
+                            );
+                        }
+                    }
+                    db.clone().with_clock(::std::sync::Arc::new(
+                        ::vicocomo::FixedClock::new(at),
+                    ))
+                };
+            ))
+        };
+
+        // with a vicocomo_lock_version field, delete only if the version is
+        // unchanged, and increment it
+        let soft_delete_expr: Expr = match model.lock_field() {
+            Some(lck) => {
+                let lck_id = &lck.id;
+                let lck_col = lck.col.value();
+                let pk_db_values = model.pk_db_values();
+                let del_sql = LitStr::new(
+                    &format!(
+                        "UPDATE {} SET {} = ${}, {} = {} + 1 \
+                        WHERE {} AND {} = ${} AND {} IS NULL",
+                        table_name,
+                        sdl_col,
+                        pk_len + 2,
+                        lck_col,
+                        lck_col,
+                        model.pk_select().value(),
+                        lck_col,
+                        pk_len + 1,
+                        sdl_col,
+                    ),
+                    Span::call_site(),
+                );
+                let fix_now =
+                    fix_now(parse_quote!(::std::slice::from_ref(&pk)));
+                parse_quote!({
+                    #fix_now
+                    let now = db.now();
+                    let mut del_pars = #pk_db_values;
+                    del_pars.push(self.#lck_id.clone().into());
+                    del_pars.push(#deleted_value);
+                    db.clone().transaction(|db| {
+                        Self::__vicocomo__soft_delete_children(
+                            db.clone(),
+                            &[pk],
+                        )?;
+                        match db.clone().exec(#del_sql, &del_pars)? {
+                            1 => Ok(()),
+                            _ => Err(self.__vicocomo__missing_error(
+                                db,
+                                ::vicocomo::ModelErrorKind::CannotDelete,
+                            )),
+                        }
+                    })
+                })
+            }
+            None => parse_quote!(
+                Self::#batch_fn(db.clone(), &[pk]).map(|_| ())
+            ),
+        };
+        let delete_body = delete_body(soft_delete_expr);
+        trait_fn.push(parse_quote!(
+            fn delete(
+                mut self,
+                db: ::vicocomo::DatabaseIf,
+            ) -> Result<(), ::vicocomo::Error> {
+                match self.pk_value() {
+                    Some(pk) => #delete_body,
+                    None => #pk_none_expr,
+                }
+            }
+        ));
+
+        let soft_batch_sql_format = LitStr::new(
+            &format!(
+                // "UPDATE tbl SET deleted = $5
+                //  WHERE (pk1, pk2) IN (($1, $2), ($3, $4))
+                //  AND deleted IS NULL"
+                "UPDATE {} SET {} = ${{}} WHERE ({}) IN ({{}}) \
+                AND {} IS NULL",
+                table_name, sdl_col, &pk_cols, sdl_col,
+            ),
+            Span::call_site(),
+        );
+        let fix_now = fix_now(parse_quote!(batch));
+        let soft_delete_batch: ItemFn = parse_quote!(
+            fn delete_batch(
+                db: ::vicocomo::DatabaseIf,
+                batch: &[<Self as ::vicocomo::ActiveRecord>::PkType],
+            ) -> Result<usize, ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
+                if batch.is_empty() {
+                    return Ok(0);
+                }
+                #fix_now
+                let now = db.now();
+                let mut values: Vec<::vicocomo::DbValue> =
+                    (#batch_expr).to_vec();
+                values.push(#deleted_value);
+                db.clone().transaction(|db| {
+                    Self::__vicocomo__soft_delete_children(
+                        db.clone(),
+                        batch,
+                    )?;
+                    let deleted_count = db.clone().exec(
+                        &format!(
+                            #soft_batch_sql_format,
+                            values.len(),
+                            #batch_placeholders,
+                        ),
+                        &values,
+                    )?;
+                    if deleted_count == batch.len() {
+                        Ok(deleted_count)
+                    } else {
+                        let mut missing_pk: Option<
+                            <Self as ActiveRecord>::PkType,
+                        > = None;
+                        for pk in batch {
+                            if Self::find(db.clone(), pk).is_none() {
+                                missing_pk = Some(pk.clone());
+                                break;
+                            }
+                        }
+                        Err(Self::__vicocomo__pk_error(
+                            ::vicocomo::ModelErrorKind::CannotDelete,
+                            missing_pk,
+                            true,
+                        ))
                     }
+                })
+            }
+        );
+        push_delete_batch(soft_delete_batch, with_hooks, struct_fn, trait_fn);
+
+        // --- purge and restore ---------------------------------------------
+
+        let purge_expr =
+            delete_expr(parse_quote!(Self::__vicocomo__purge_batch));
+        struct_fn.push(parse_quote!(
+            pub fn purge(
+                self,
+                db: ::vicocomo::DatabaseIf,
+            ) -> Result<(), ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
+                match self.pk_value() {
+                    Some(pk) => #purge_expr,
+                    None => #pk_none_expr,
                 }
             }
         ));
+
+        let (restore_sql, push_lock_par, bump_lock): (LitStr, Expr, Expr) =
+            match model.lock_field() {
+                Some(lck) => {
+                    let lck_id = &lck.id;
+                    let lck_col = lck.col.value();
+                    (
+                        LitStr::new(
+                            &format!(
+                                "UPDATE {} SET {} = NULL, {} = {} + 1 \
+                                WHERE {} AND {} = ${}",
+                                table_name,
+                                sdl_col,
+                                lck_col,
+                                lck_col,
+                                model.pk_select().value(),
+                                lck_col,
+                                pk_len + 1,
+                            ),
+                            Span::call_site(),
+                        ),
+                        parse_quote!(
+                            pars.push(self.#lck_id.clone().into())
+                        ),
+                        parse_quote!(self.#lck_id += 1),
+                    )
+                }
+                None => (
+                    LitStr::new(
+                        &format!(
+                            "UPDATE {} SET {} = NULL WHERE {}",
+                            table_name,
+                            sdl_col,
+                            model.pk_select().value(),
+                        ),
+                        Span::call_site(),
+                    ),
+                    parse_quote!(()),
+                    parse_quote!(()),
+                ),
+            };
+        // restore also the children soft deleted by the same cascade, i.e.
+        // at the time self was deleted
+        let (deleted_at, restore_children): (Option<::syn::Stmt>, Expr) =
+            if cascade_type.is_empty() {
+                (None, parse_quote!(()))
+            } else {
+                let deleted_at: Expr =
+                    if Model::timestamp_utc(&sdl.ty).unwrap() {
+                        parse_quote!(self.#sdl_id)
+                    } else {
+                        parse_quote!(self.#sdl_id.map(|t| t.and_utc()))
+                    };
+                (
+                    Some(parse_quote!(let deleted_at = #deleted_at;)),
+                    parse_quote!(if let Some(at) = deleted_at {
+                        let db = db.clone().with_clock(::std::sync::Arc::new(
+                            ::vicocomo::FixedClock::new(at),
+                        ));
+                        let parents: Vec<::vicocomo::DbValue> =
+                            vec![pk.clone().into()];
+                        #(
+                            #cascade_type::__vicocomo__restore_cascaded(
+                                db.clone(),
+                                #cascade_fk_col,
+                                &parents,
+                            )?;
+                        )*
+                    }),
+                )
+            };
+        let restore_expr = Model::transaction_expr(
+            !cascade_type.is_empty(),
+            parse_quote!(match db.clone().exec(#restore_sql, &pars)? {
+                1 => {
+                    #restore_children;
+                    self.#sdl_id = None;
+                    #bump_lock;
+                    Ok(())
+                }
+                _ => Err(
+                    match Self::find_with_deleted(db, &pk) {
+                        Some(_) => ::vicocomo::Error::Model(
+                            ::vicocomo::ModelError {
+                                error: ::vicocomo::ModelErrorKind
+                                    ::StaleObject,
+                                model: #struct_lit.to_string(),
+                                general: None,
+                                field_errors: Vec::new(),
+                                assoc_errors: Vec::new(),
+                            }
+                        ),
+                        None => Self::__vicocomo__pk_error(
+                            ::vicocomo::ModelErrorKind::CannotSave,
+                            Some(pk),
+                            true,
+                        ),
+                    }
+                ),
+            }),
+        );
+        let pk_db_values = model.pk_db_values();
+        struct_fn.push(parse_quote!(
+            pub fn restore(
+                &mut self,
+                db: ::vicocomo::DatabaseIf,
+            ) -> Result<(), ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
+                let pk = match self.pk_value() {
+                    Some(pk) => pk,
+                    None => {
+                        return Err(Self::__vicocomo__pk_error(
+                            ::vicocomo::ModelErrorKind::CannotSave,
+                            None,
+                            true,
+                        ))
+                    }
+                };
+                let mut pars = #pk_db_values;
+                #push_lock_par;
+                #deleted_at
+                #restore_expr
+            }
+        ));
+
+        // the latest deletion time, in whole seconds since the epoch, of the
+        // objects referring to parents by fk_col and, recursively, of their
+        // cascaded children, used by the parent's delete()
+        let obj_deleted_at: Expr = if Model::timestamp_utc(&sdl.ty).unwrap() {
+            parse_quote!(obj.#sdl_id.map(|t| t.timestamp()))
+        } else {
+            parse_quote!(obj.#sdl_id.map(|t| t.and_utc().timestamp()))
+        };
+        let last_deleted_children: Expr = if cascade_type.is_empty() {
+            parse_quote!(())
+        } else {
+            parse_quote!({
+                let pks: Vec<::vicocomo::DbValue> = objs
+                    .iter()
+                    .filter_map(|obj| obj.pk_value().map(|pk| pk.into()))
+                    .collect();
+            #(
+                last = last.max(#cascade_type::__vicocomo__last_deleted(
+                    db.clone(),
+                    #cascade_fk_col,
+                    &pks,
+                )?);
+            )*
+            })
+        };
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            pub fn __vicocomo__last_deleted(
+                db: ::vicocomo::DatabaseIf,
+                fk_col: &str,
+                parents: &[::vicocomo::DbValue],
+            ) -> Result<Option<i64>, ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
+                let query = ::vicocomo::QueryBld::new()
+                    .col(fk_col)
+                    .in_list(parents)
+                    .with_deleted()
+                    .query()
+                    .ok_or_else(|| {
+                        ::vicocomo::Error::this_cannot_happen("last_deleted")
+                    })?;
+                let objs = Self::query(db.clone(), &query)?;
+                let mut last: Option<i64> = None;
+                for obj in objs.iter() {
+                    last = last.max(#obj_deleted_at);
+                }
+                #last_deleted_children;
+                Ok(last)
+            }
+        ));
+
+        // restore the objects referring to parents by fk_col that were soft
+        // deleted at db.now(), used by the parent's restore()
+        let restore_cascaded_filter =
+            LitStr::new(&format!("{} = $1", sdl_col), Span::call_site());
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            pub fn __vicocomo__restore_cascaded(
+                db: ::vicocomo::DatabaseIf,
+                fk_col: &str,
+                parents: &[::vicocomo::DbValue],
+            ) -> Result<(), ::vicocomo::Error> {
+                use ::vicocomo::ActiveRecord;
+                let now = db.now();
+                let query = ::vicocomo::QueryBld::new()
+                    .col(fk_col)
+                    .in_list(parents)
+                    .filter(#restore_cascaded_filter, &[Some(#deleted_value)])
+                    .with_deleted()
+                    .query()
+                    .ok_or_else(|| {
+                        ::vicocomo::Error::this_cannot_happen(
+                            "restore_cascaded",
+                        )
+                    })?;
+                for mut obj in Self::query(db.clone(), &query)? {
+                    obj.restore(db.clone())?;
+                }
+                Ok(())
+            }
+        ));
+    }
+}
+
+// Push delete_batch to trait_fn, or if with_hooks is Some, push it renamed to
+// __vicocomo__delete_batch to struct_fn and with_hooks to trait_fn.
+//
+fn push_delete_batch(
+    mut delete_batch: ItemFn,
+    with_hooks: Option<ItemFn>,
+    struct_fn: &mut Vec<ItemFn>,
+    trait_fn: &mut Vec<ItemFn>,
+) {
+    use ::syn::parse_quote;
+
+    match with_hooks {
+        Some(with_hooks) => {
+            delete_batch.sig.ident = parse_quote!(__vicocomo__delete_batch);
+            delete_batch.attrs.push(parse_quote!(#[doc(hidden)]));
+            struct_fn.push(delete_batch);
+            trait_fn.push(with_hooks);
+        }
+        None => trait_fn.push(delete_batch),
     }
 }
@@ -6,8 +6,12 @@ pub(crate) fn find_impl(
     struct_fn: &mut Vec<ItemFn>,
     trait_fn: &mut Vec<ItemFn>,
 ) {
+    use ::case::CaseExt;
+    use ::proc_macro2::Span;
     use ::quote::format_ident;
-    use ::syn::{parse_quote, punctuated::Punctuated, token::Comma, Expr};
+    use ::syn::{
+        parse_quote, punctuated::Punctuated, token::Comma, Expr, LitStr, Type,
+    };
 
     let table_name = &model.table_name;
     let all_cols = model
@@ -20,24 +24,157 @@ pub(crate) fn find_impl(
 
     // == general functions ==================================================
 
-    let default_order = model.default_order();
+    let default_order = model.default_order("");
     let all_cols_join = all_cols.join(", ");
 
-    // SELECT <all> FROM <table> [ ORDER BY <default> ]
-    let load_sql = format!(
-        "SELECT {} FROM {} {}",
-        &all_cols_join, table_name, default_order,
-    );
-    let load_models = model.rows_to_models_expr(
-        parse_quote!(db.clone().query(#load_sql, &[], &[ #( #db_types ),* ])?),
-    );
+    // SELECT <all> FROM <table> [ WHERE <not deleted> ] [ ORDER BY <dflt> ]
+    let load_sql = |with_deleted: bool| {
+        format!(
+            "SELECT {} FROM {} {} {}",
+            &all_cols_join,
+            table_name,
+            if with_deleted {
+                String::new()
+            } else {
+                model.not_deleted_sql("WHERE ", "")
+            },
+            default_order,
+        )
+    };
+    let load_models = |with_deleted: bool| {
+        let sql = load_sql(with_deleted);
+        model.rows_to_models_expr(parse_quote!(
+            db.clone().query(#sql, &[], &[ #( #db_types ),* ])?
+        ))
+    };
 
     // SELECT <all> FROM <table>
     // [ WHERE ... ] [ ORDER BY ... ] [ LIMIT ... ] [ OFFSET ... ]
-    let query_sql = format!(
-        "SELECT {} FROM {} {{}} {{}} {{}} {{}}",
-        &all_cols_join, &table_name,
+    let select_sql =
+        format!("SELECT {} FROM {}", &all_cols_join, &table_name);
+
+    // SELECT <all qualified> [ <row numbers> ] FROM <table> AS this <joins>
+    // [ WHERE ... ] [ ORDER BY ... ] [ LIMIT ... ] [ OFFSET ... ]
+    let join_select_fmt = format!(
+        "SELECT {}{{}} FROM {} AS this {{}}",
+        all_cols
+            .iter()
+            .map(|c| format!("this.{}", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        &table_name,
+    );
+    // Joining a has-many association may yield several rows per object.
+    // Rather than SELECT DISTINCT, which would require the ORDER BY columns
+    // to be selected, the rows are numbered per object and in the requested
+    // order, and the object is placed by its first row in that order.
+    let pk_partition = pk_fields
+        .iter()
+        .map(|f| format!("this.{}", f.col.value()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // , ROW_NUMBER() OVER (PARTITION BY <pk> <order>) AS __vicocomo__n
+    // , ROW_NUMBER() OVER (<order>) AS __vicocomo__ix
+    let row_numbers_fmt = LitStr::new(
+        &format!(
+            ", ROW_NUMBER() OVER (PARTITION BY {} {{}}) AS __vicocomo__n, \
+            ROW_NUMBER() OVER ({{}}) AS __vicocomo__ix",
+            pk_partition,
+        ),
+        Span::call_site(),
     );
+    // SELECT <all> FROM ( <join select> ) AS __vicocomo__rows
+    // WHERE __vicocomo__n = 1 ORDER BY __vicocomo__ix
+    // [ LIMIT ... ] [ OFFSET ... ]
+    let first_rows_fmt = LitStr::new(
+        &format!(
+            "SELECT {} FROM ({{}} {{}}) AS __vicocomo__rows \
+            WHERE __vicocomo__n = 1 ORDER BY __vicocomo__ix {{}} {{}}",
+            &all_cols_join,
+        ),
+        Span::call_site(),
+    );
+    let join_order = model.default_order("this.");
+    // JOIN clause expression, excluding soft deleted remote objects unless
+    // query.with_deleted()
+    let join_expr = |name: &str, remote_type: &Type, join_fmt: LitStr| {
+        let deleted_fmt = LitStr::new(
+            &format!(" AND {}.{{}} IS NULL", name),
+            Span::call_site(),
+        );
+        let expr: Expr = parse_quote!(format!(
+            #join_fmt,
+            <#remote_type as ::vicocomo::ActiveRecord>::table(),
+            match <#remote_type as ::vicocomo::ActiveRecord>::
+                soft_delete_column()
+            {
+                Some(col) if !query.with_deleted() => {
+                    format!(#deleted_fmt, col)
+                }
+                _ => String::new(),
+            },
+        ));
+        expr
+    };
+    // (name, JOIN clause expression, to many)
+    let mut joins: Vec<(LitStr, Expr, bool)> = Vec::new();
+    for fld in model.fields.iter() {
+        if let Some(ref fk) = fld.fk {
+            let name = fk.assoc_name.to_snake();
+            let remote_type = &fk.remote_type;
+            let join_fmt = LitStr::new(
+                &format!(
+                    " JOIN {{}} AS {} ON {}.{} = this.{}{{}}",
+                    name,
+                    name,
+                    fk.remote_pk,
+                    fld.col.value(),
+                ),
+                Span::call_site(),
+            );
+            joins.push((
+                LitStr::new(&name, Span::call_site()),
+                join_expr(&name, remote_type, join_fmt),
+                false,
+            ));
+        }
+    }
+    for has_many in &model.has_many {
+        let name = format!("{}s", has_many.assoc_name.to_snake());
+        let pk_col = model.pk_fields()[0].col.value();
+        let remote_type = &has_many.remote_type;
+        let join_fmt = LitStr::new(
+            &match has_many.many_to_many {
+                Some(ref mtm) => format!(
+                    " JOIN {} AS {}__join ON {}__join.{} = this.{} \
+                    JOIN {{}} AS {} ON {}.{} = {}__join.{}{{}}",
+                    mtm.join_table_name,
+                    name,
+                    name,
+                    has_many.remote_fk_col,
+                    pk_col,
+                    name,
+                    name,
+                    mtm.remote_pk_col,
+                    name,
+                    mtm.join_fk_col,
+                ),
+                None => format!(
+                    " JOIN {{}} AS {} ON {}.{} = this.{}{{}}",
+                    name, name, has_many.remote_fk_col, pk_col,
+                ),
+            },
+            Span::call_site(),
+        );
+        joins.push((
+            LitStr::new(&name, Span::call_site()),
+            join_expr(&name, remote_type, join_fmt),
+            true,
+        ));
+    }
+    let join_names = joins.iter().map(|j| &j.0).collect::<Vec<_>>();
+    let join_exprs = joins.iter().map(|j| &j.1).collect::<Vec<_>>();
+    let join_to_many = joins.iter().map(|j| j.2).collect::<Vec<_>>();
     let found_models = model.rows_to_models_expr(parse_quote!(found_rows));
     let pk_len = pk_fields.len();
     let find_model = model.rows_to_models_expr(parse_quote!(outp));
@@ -57,14 +194,11 @@ pub(crate) fn find_impl(
             }
         ));
     } else {
-        let find_pk_sql = model.find_sql(
-            model
-                .pk_fields()
-                .iter()
-                .map(|f| f.col.value())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        );
+        let pk_cols = model
+            .pk_fields()
+            .iter()
+            .map(|f| f.col.value())
+            .collect::<Vec<_>>();
         let mut pk_db_values: Punctuated<Expr, Comma> = Punctuated::new();
         if pk_len == 1 {
             pk_db_values.push(parse_quote!(pk.clone().into()));
@@ -74,10 +208,9 @@ pub(crate) fn find_impl(
             }
         }
 
-        trait_fn.push(parse_quote!(
-            fn find(db: ::vicocomo::DatabaseIf, pk: &Self::PkType)
-                -> Option<Self>
-            {
+        let find_pk_expr = |with_deleted: bool| {
+            let find_pk_sql = model.find_sql(&pk_cols, with_deleted);
+            let expr: Expr = parse_quote!(
                 match db.clone().query(
                     #find_pk_sql,
                     &[ #pk_db_values ],
@@ -93,8 +226,28 @@ pub(crate) fn find_impl(
                     },
                     _ => None,
                 }
+            );
+            expr
+        };
+        let find_pk = find_pk_expr(false);
+        trait_fn.push(parse_quote!(
+            fn find(db: ::vicocomo::DatabaseIf, pk: &Self::PkType)
+                -> Option<Self>
+            {
+                #find_pk
             }
         ));
+        if model.soft_delete_field().is_some() {
+            let find_pk = find_pk_expr(true);
+            struct_fn.push(parse_quote!(
+                pub fn find_with_deleted(
+                    db: ::vicocomo::DatabaseIf,
+                    pk: &<Self as ::vicocomo::ActiveRecord>::PkType,
+                ) -> Option<Self> {
+                    #find_pk
+                }
+            ));
+        }
         trait_fn.push(parse_quote!(
             fn find_equal(&self, db: ::vicocomo::DatabaseIf)
                 -> Option<Self>
@@ -105,22 +258,52 @@ pub(crate) fn find_impl(
             }
         ));
     }
+    let load = load_models(false);
     trait_fn.push(parse_quote!(
         fn load(
             db: ::vicocomo::DatabaseIf,
         ) -> Result<Vec<Self>, ::vicocomo::Error> {
-            #load_models
+            #load
         }
     ));
-    trait_fn.push(parse_quote!(
-        fn query(
-            db: ::vicocomo::DatabaseIf,
-            query: &::vicocomo::Query
-        ) -> Result<Vec<Self>, ::vicocomo::Error> {
-            let filter = match query.filter() {
-                Some(f) => format!("WHERE {}", f),
-                None => String::new(),
+    if model.soft_delete_field().is_some() {
+        let load = load_models(true);
+        struct_fn.push(parse_quote!(
+            pub fn load_with_deleted(
+                db: ::vicocomo::DatabaseIf,
+            ) -> Result<Vec<Self>, ::vicocomo::Error> {
+                #load
+            }
+        ));
+    }
+    let row_model = model.row_to_model_expr(parse_quote!(row));
+    let filter_expr: Expr = if model.soft_delete_field().is_some() {
+        let not_deleted = model.not_deleted_sql("", "");
+        let this_not_deleted = model.not_deleted_sql("", "this.");
+        parse_quote!({
+            let not_deleted = if query.joins().is_empty() {
+                #not_deleted
+            } else {
+                #this_not_deleted
             };
+            match query.filter() {
+                Some(f) if query.with_deleted() => format!("WHERE {}", f),
+                Some(f) => format!("WHERE ({}) AND {}", f, not_deleted),
+                None if query.with_deleted() => String::new(),
+                None => format!("WHERE {}", not_deleted),
+            }
+        })
+    } else {
+        parse_quote!(match query.filter() {
+            Some(f) => format!("WHERE {}", f),
+            None => String::new(),
+        })
+    };
+    struct_fn.push(parse_quote!(
+        fn __vicocomo__query_sql(
+            query: &::vicocomo::Query
+        ) -> Result<(String, Vec<::vicocomo::DbValue>), ::vicocomo::Error> {
+            let filter = #filter_expr;
             let limit = match query.limit() {
                 Some(l) => format!("LIMIT {}", l),
                 // E.g. SQLite requires LIMIT if OFFSET
@@ -133,11 +316,30 @@ pub(crate) fn find_impl(
                 Some(l) => format!("OFFSET {}", l),
                 None => String::new(),
             };
+            let mut to_many = false;
+            let mut joins = String::new();
+            for join in query.joins() {
+                match join.as_str() {
+                    #(
+                        #join_names => {
+                            joins.push_str(&#join_exprs);
+                            to_many = to_many || #join_to_many;
+                        }
+                    )*
+                    _ => return Err(::vicocomo::Error::invalid_input(
+                        &format!("active-record--unknown-join--{}", join),
+                    )),
+                }
+            }
+            let default_order = if query.joins().is_empty() {
+                #default_order
+            } else {
+                #join_order
+            };
             let order = match query.order() {
                 ::vicocomo::Order::Custom(ord) =>
                     format!("ORDER BY {}", ord),
-                ::vicocomo::Order::Dflt =>
-                    #default_order.to_string(),
+                ::vicocomo::Order::Dflt => default_order.to_string(),
                 ::vicocomo::Order::NoOrder => String::new(),
             };
             let mut values: Vec<::vicocomo::DbValue> = Vec::new();
@@ -149,12 +351,269 @@ pub(crate) fn find_impl(
                     )),
                 }
             }
-            let sql = format!(#query_sql, filter, order, limit, offset);
+            let sql = if query.joins().is_empty() {
+                format!(
+                    "{} {} {} {} {}",
+                    #select_sql, filter, order, limit, offset,
+                )
+            } else if to_many {
+                let row_numbers = format!(#row_numbers_fmt, order, order);
+                format!(
+                    #first_rows_fmt,
+                    format!(#join_select_fmt, row_numbers, joins),
+                    filter,
+                    limit,
+                    offset,
+                )
+            } else {
+                format!(
+                    "{} {} {} {} {}",
+                    format!(#join_select_fmt, "", joins),
+                    filter,
+                    order,
+                    limit,
+                    offset,
+                )
+            };
+            Ok((sql, values))
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn query(
+            db: ::vicocomo::DatabaseIf,
+            query: &::vicocomo::Query
+        ) -> Result<Vec<Self>, ::vicocomo::Error> {
+            let (sql, values) = Self::__vicocomo__query_sql(query)?;
             let mut found_rows =
                 db.clone().query(&sql, &values, &[ #( #db_types ),* ])?;
             #found_models
         }
     ));
+    trait_fn.push(parse_quote!(
+        fn for_each<F>(
+            db: ::vicocomo::DatabaseIf,
+            query: &::vicocomo::Query,
+            mut f: F,
+        ) -> Result<usize, ::vicocomo::Error>
+        where
+            F: FnMut(Self) -> Result<(), ::vicocomo::Error>,
+        {
+            let (sql, values) = Self::__vicocomo__query_sql(query)?;
+            db.query_each(&sql, &values, &[ #( #db_types ),* ], |row| {
+                f(#row_model?)
+            })
+        }
+    ));
+
+    // == aggregate functions ================================================
+
+    let fld_names = model
+        .fields
+        .iter()
+        .map(|f| f.id.to_string().trim_start_matches("r#").to_string())
+        .collect::<Vec<_>>();
+    struct_fn.push(parse_quote!(
+        fn __vicocomo__field_col(
+            field: &str,
+        ) -> Result<(&'static str, ::vicocomo::DbType), ::vicocomo::Error> {
+            match field {
+                #( #fld_names => Ok((#all_cols, #db_types)), )*
+                _ => Err(::vicocomo::Error::invalid_input(&format!(
+                    "active-record--unknown-field--{}",
+                    field,
+                ))),
+            }
+        }
+    ));
+    struct_fn.push(parse_quote!(
+        fn __vicocomo__aggregate(
+            db: ::vicocomo::DatabaseIf,
+            select: &str,
+            group: &str,
+            query: &::vicocomo::Query,
+            types: &[::vicocomo::DbType],
+        ) -> Result<Vec<Vec<::vicocomo::DbValue>>, ::vicocomo::Error>
+        {
+            // the order matters only if there is a limit or an offset
+            let query = if query.limit().is_none() && query.offset().is_none()
+            {
+                query.clone().builder().no_order().query().ok_or_else(
+                    || ::vicocomo::Error::this_cannot_happen("no_order"),
+                )?
+            } else {
+                query.clone()
+            };
+            let (sql, values) = Self::__vicocomo__query_sql(&query)?;
+            db.query(
+                &format!(
+                    "SELECT {} FROM ({}) AS __vicocomo__q {}",
+                    select, sql, group,
+                ),
+                &values,
+                types,
+            )
+        }
+    ));
+    struct_fn.push(parse_quote!(
+        fn __vicocomo__aggregate_value(
+            db: ::vicocomo::DatabaseIf,
+            select: &str,
+            query: &::vicocomo::Query,
+            typ: ::vicocomo::DbType,
+        ) -> Result<Option<::vicocomo::DbValue>, ::vicocomo::Error> {
+            Ok(Self::__vicocomo__aggregate(db, select, "", query, &[typ])?
+                .first()
+                .and_then(|row| row.first())
+                .and_then(|val| val.to_option()))
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn count(
+            db: ::vicocomo::DatabaseIf,
+            query: &::vicocomo::Query,
+        ) -> Result<usize, ::vicocomo::Error> {
+            match Self::__vicocomo__aggregate_value(
+                db,
+                "COUNT(*)",
+                query,
+                ::vicocomo::DbType::Int,
+            )? {
+                Some(::vicocomo::DbValue::Int(n)) => Ok(n as usize),
+                _ => Err(::vicocomo::Error::this_cannot_happen("count")),
+            }
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn exists(
+            db: ::vicocomo::DatabaseIf,
+            query: &::vicocomo::Query,
+        ) -> Result<bool, ::vicocomo::Error> {
+            Self::__vicocomo__aggregate(
+                db,
+                "CAST(1 AS BIGINT)",
+                "LIMIT 1",
+                query,
+                &[::vicocomo::DbType::Int],
+            )
+            .map(|rows| !rows.is_empty())
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn avg(
+            db: ::vicocomo::DatabaseIf,
+            field: &str,
+            query: &::vicocomo::Query,
+        ) -> Result<Option<::vicocomo::DbValue>, ::vicocomo::Error> {
+            use ::vicocomo::DbType;
+            match Self::__vicocomo__field_col(field)? {
+                (
+                    col,
+                    DbType::Float
+                    | DbType::Int
+                    | DbType::NulFloat
+                    | DbType::NulInt,
+                ) => Self::__vicocomo__aggregate_value(
+                    db,
+                    &format!("CAST(AVG({}) AS DOUBLE PRECISION)", col),
+                    query,
+                    DbType::NulFloat,
+                ),
+                _ => Err(::vicocomo::Error::invalid_input(&format!(
+                    "active-record--not-numeric--{}",
+                    field,
+                ))),
+            }
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn group_by(
+            db: ::vicocomo::DatabaseIf,
+            field: &str,
+            query: &::vicocomo::Query,
+        ) -> Result<Vec<(::vicocomo::DbValue, i64)>, ::vicocomo::Error>
+        {
+            let (col, typ) = Self::__vicocomo__field_col(field)?;
+            let mut result = Vec::new();
+            for mut row in Self::__vicocomo__aggregate(
+                db,
+                &format!("{}, COUNT(*)", col),
+                &format!("GROUP BY {} ORDER BY {}", col, col),
+                query,
+                &[typ, ::vicocomo::DbType::Int],
+            )? {
+                match (row.pop(), row.pop()) {
+                    (Some(::vicocomo::DbValue::Int(n)), Some(val)) => {
+                        result.push((val, n))
+                    }
+                    _ => {
+                        return Err(::vicocomo::Error::this_cannot_happen(
+                            "group_by",
+                        ))
+                    }
+                }
+            }
+            Ok(result)
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn max(
+            db: ::vicocomo::DatabaseIf,
+            field: &str,
+            query: &::vicocomo::Query,
+        ) -> Result<Option<::vicocomo::DbValue>, ::vicocomo::Error> {
+            let (col, typ) = Self::__vicocomo__field_col(field)?;
+            Self::__vicocomo__aggregate_value(
+                db,
+                &format!("MAX({})", col),
+                query,
+                typ.nullable(),
+            )
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn min(
+            db: ::vicocomo::DatabaseIf,
+            field: &str,
+            query: &::vicocomo::Query,
+        ) -> Result<Option<::vicocomo::DbValue>, ::vicocomo::Error> {
+            let (col, typ) = Self::__vicocomo__field_col(field)?;
+            Self::__vicocomo__aggregate_value(
+                db,
+                &format!("MIN({})", col),
+                query,
+                typ.nullable(),
+            )
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn sum(
+            db: ::vicocomo::DatabaseIf,
+            field: &str,
+            query: &::vicocomo::Query,
+        ) -> Result<Option<::vicocomo::DbValue>, ::vicocomo::Error> {
+            use ::vicocomo::DbType;
+            let (col, sql_type, typ) =
+                match Self::__vicocomo__field_col(field)? {
+                    (col, DbType::Float | DbType::NulFloat) => {
+                        (col, "DOUBLE PRECISION", DbType::NulFloat)
+                    }
+                    (col, DbType::Int | DbType::NulInt) => {
+                        (col, "BIGINT", DbType::NulInt)
+                    }
+                    _ => {
+                        return Err(::vicocomo::Error::invalid_input(
+                            &format!("active-record--not-numeric--{}", field),
+                        ))
+                    }
+                };
+            Self::__vicocomo__aggregate_value(
+                db,
+                &format!("CAST(SUM({}) AS {})", col, sql_type),
+                query,
+                typ,
+            )
+        }
+    ));
 
     // == unique field functions =============================================
 
@@ -189,7 +648,7 @@ pub(crate) fn find_impl(
 
         // -- finding --------------------------------------------------------
 
-        let find_uni_sql = model.find_sql(&uni_cols);
+        let find_uni_sql = model.find_sql(&uni_cols, false);
 
         struct_fn.push(parse_quote!(
             // -- find_by_field1_and_field3(db, v1, v3) ------------------
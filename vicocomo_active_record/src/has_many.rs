@@ -77,6 +77,7 @@ pub(crate) fn has_many_impl(
         let disconnect_from_fn =
             format_ident!("disconnect_from_{}", assoc_snake);
         let get_fn = format_ident!("{}s", assoc_snake);
+        let preload_fn = format_ident!("preload_{}s", assoc_snake);
         let save_fn = format_ident!("save_{}s", assoc_snake);
 
         if many_to_many.is_some() {
@@ -135,11 +136,178 @@ pub(crate) fn has_many_impl(
                     Err(::vicocomo::Error::nyi())
                 }
             ));
+            let pk_col = &pk.col;
+            let remote_pk_col_lit =
+                LitStr::new(&remote_pk_col, Span::call_site());
+            let join_sql_fmt = LitStr::new(
+                &format!(
+                    "SELECT {}, {} FROM {} WHERE {} IN ({{}})",
+                    remote_fk_col,
+                    join_fk_col,
+                    join_table_name,
+                    remote_fk_col,
+                ),
+                Span::call_site(),
+            );
+            let remote_filter_fmt = LitStr::new(
+                &format!(
+                    "{} IN (SELECT {} FROM {} WHERE {} IN ({{}}))",
+                    remote_pk_col,
+                    join_fk_col,
+                    join_table_name,
+                    remote_fk_col,
+                ),
+                Span::call_site(),
+            );
+            struct_fn.push(parse_quote!(
+                pub fn #preload_fn(
+                    db: ::vicocomo::DatabaseIf,
+                    objs: &[Self],
+                ) -> Result<
+                    ::std::collections::HashMap<
+                        <Self as ::vicocomo::ActiveRecord>::PkType,
+                        Vec<#remote_type>,
+                    >,
+                    ::vicocomo::Error,
+                > {
+                    use ::vicocomo::ActiveRecord;
+                    let mut result = ::std::collections::HashMap::new();
+                    let mut values = Vec::new();
+                    for obj in objs {
+                        if let Some(pk) = obj.pk_value() {
+                            if !result.contains_key(&pk) {
+                                values.push(pk.clone().into());
+                                result.insert(pk, Vec::new());
+                            }
+                        }
+                    }
+                    if values.is_empty() {
+                        return Ok(result);
+                    }
+                    let params = (1..=values.len())
+                        .map(|ix| format!("${}", ix))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    // the owners of each remote, keyed by the SQL value of
+                    // the remote primary key
+                    let mut owners: ::std::collections::HashMap<
+                        String,
+                        Vec<<Self as ActiveRecord>::PkType>,
+                    > = ::std::collections::HashMap::new();
+                    for mut pair in db.clone().query(
+                        &format!(#join_sql_fmt, params),
+                        &values,
+                        &[
+                            Self::col_type(#pk_col)
+                                .unwrap_or(::vicocomo::DbType::Int),
+                            <#remote_type as ActiveRecord>::col_type(
+                                #remote_pk_col_lit,
+                            )
+                            .unwrap_or(::vicocomo::DbType::Int),
+                        ],
+                    )? {
+                        let remote_key = pair.remove(1).sql_value();
+                        owners
+                            .entry(remote_key)
+                            .or_default()
+                            .push(pair.remove(0).try_into()?);
+                    }
+                    let values =
+                        values.drain(..).map(Some).collect::<Vec<_>>();
+                    for remote in <#remote_type as ActiveRecord>::query(
+                        db,
+                        &::vicocomo::QueryBld::new()
+                            .filter(
+                                &format!(#remote_filter_fmt, params),
+                                &values,
+                            )
+                            .query()
+                            .unwrap(),
+                    )? {
+                        let remote_key: ::vicocomo::DbValue =
+                            match remote.pk_value() {
+                                Some(pk) => pk.into(),
+                                None => continue,
+                            };
+                        for owner in owners
+                            .get(&remote_key.sql_value())
+                            .into_iter()
+                            .flatten()
+                        {
+                            if let Some(remotes) = result.get_mut(owner) {
+                                remotes.push(remote.clone());
+                            }
+                        }
+                    }
+                    Ok(result)
+                }
+            ));
         } else {
             let remote_set_fn =
                 format_ident!("set_{}", remote_assoc.to_snake());
             let remote_all_fn =
                 format_ident!("all_belonging_to_{}", remote_assoc.to_snake());
+            let remote_fk_col_lit =
+                LitStr::new(remote_fk_col, Span::call_site());
+            struct_fn.push(parse_quote!(
+                pub fn #preload_fn(
+                    db: ::vicocomo::DatabaseIf,
+                    objs: &[Self],
+                ) -> Result<
+                    ::std::collections::HashMap<
+                        <Self as ::vicocomo::ActiveRecord>::PkType,
+                        Vec<#remote_type>,
+                    >,
+                    ::vicocomo::Error,
+                > {
+                    use ::vicocomo::ActiveRecord;
+                    let mut result = ::std::collections::HashMap::new();
+                    // the primary keys, keyed by their SQL value
+                    let mut owners = ::std::collections::HashMap::new();
+                    let mut values = Vec::new();
+                    for obj in objs {
+                        if let Some(pk) = obj.pk_value() {
+                            if !result.contains_key(&pk) {
+                                let value: ::vicocomo::DbValue =
+                                    pk.clone().into();
+                                owners.insert(value.sql_value(), pk.clone());
+                                values.push(value);
+                                result.insert(pk, Vec::new());
+                            }
+                        }
+                    }
+                    if values.is_empty() {
+                        return Ok(result);
+                    }
+                    let fk_ix = match <#remote_type as ActiveRecord>::
+                        columns()
+                        .iter()
+                        .position(|col| col == #remote_fk_col_lit)
+                    {
+                        Some(ix) => ix,
+                        None => return Err(
+                            ::vicocomo::Error::this_cannot_happen(
+                                #remote_fk_col_lit,
+                            )),
+                    };
+                    for remote in <#remote_type as ActiveRecord>::query(
+                        db,
+                        &::vicocomo::QueryBld::new()
+                            .col(#remote_fk_col_lit)
+                            .in_list(&values)
+                            .query()
+                            .unwrap(),
+                    )? {
+                        let fk = remote.values()?.swap_remove(fk_ix);
+                        if let Some(pk) = owners.get(&fk.sql_value()) {
+                            if let Some(remotes) = result.get_mut(pk) {
+                                remotes.push(remote);
+                            }
+                        }
+                    }
+                    Ok(result)
+                }
+            ));
             struct_fn.push(parse_quote!(
                 pub fn #save_fn(
                     &self,
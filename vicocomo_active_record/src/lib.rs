@@ -9,7 +9,9 @@ mod find;
 mod has_many;
 mod model;
 mod save;
+mod schema;
 mod to_fro_sql;
+mod validation;
 
 /// Derive the [`ActiveRecord`
 /// ](../vicocomo/active_record/trait.ActiveRecord.html) trait for a `struct`
@@ -37,6 +39,7 @@ mod to_fro_sql;
 ///     primary: Option<i64>,         // Should be declared PRIMARY to DBMS
 ///     #[vicocomo_column = "db_col"] // different name of DB column
 ///     #[vicocomo_unique = "un1"]    // "un1" labels fields w unique comb.
+///     #[vicocomo_validate(length(max = 80))] // checked before saving
 ///     not_null: String,             // TEXT NOT NULL
 ///     #[vicocomo_order_by(2)]       // precedence 2, see opt_null below
 ///     nullable: Option<String>,     // TEXT, None -> NULL
@@ -61,6 +64,35 @@ mod to_fro_sql;
 ///
 /// See above [example](#example).
 ///
+/// ### `vicocomo_after_delete`
+///
+/// See [`AfterDelete`](../vicocomo/active_record/trait.AfterDelete.html).
+/// If present, the generated [`ActiveRecord::delete()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete)
+/// requires the model to implement [`AfterDelete`
+/// ](../vicocomo/active_record/trait.AfterDelete.html) and calls
+/// [`after_delete()`
+/// ](../vicocomo/active_record/trait.AfterDelete.html#tymethod.after_delete)
+/// after deleting the row, in the same transaction. See also
+/// [`vicocomo_delete_batch_hooks`](#vicocomo_delete_batch_hooks).
+///
+/// ### `vicocomo_after_save`
+///
+/// See [`AfterSave`](../vicocomo/active_record/trait.AfterSave.html). If
+/// present, the generated [`ActiveRecord::insert_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert_batch)
+/// and [`ActiveRecord::update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update)
+/// methods, and thereby [`insert()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert) and
+/// [`save()`](../vicocomo/active_record/trait.ActiveRecord.html#method.save),
+/// require the model to implement [`AfterSave`
+/// ](../vicocomo/active_record/trait.AfterSave.html) and call
+/// [`after_save()`
+/// ](../vicocomo/active_record/trait.AfterSave.html#tymethod.after_save)
+/// for each saved object, in the same transaction as the save. So do the
+/// [upsert functions](#for-each-vicocomo_unique-label).
+///
 /// ### `vicocomo_before_delete`
 ///
 /// See [`BeforeDelete`](../vicocomo/active_record/trait.BeforeDelete.html).
@@ -85,6 +117,16 @@ mod to_fro_sql;
 /// [`before_save()`
 /// ](../vicocomo/active_record/trait.BeforeSave.html#tymethod.before_save).
 ///
+/// ### `vicocomo_delete_batch_hooks`
+///
+/// Requires [`vicocomo_before_delete`](#vicocomo_before_delete) or
+/// [`vicocomo_after_delete`](#vicocomo_after_delete). If present, the
+/// generated [`ActiveRecord::delete_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete_batch)
+/// finds each object in the batch and calls the hooks for each object as
+/// `delete()` does, all in one transaction. If not, `delete_batch()` does
+/// not call the hooks.
+///
 /// ### `vicocomo_has_many(` ... `)`
 ///
 /// Self has a {one,many}-to-many association. There should be one
@@ -161,6 +203,26 @@ mod to_fro_sql;
 ///
 /// Optional, default the snake cased struct name with a plural 's'.
 ///
+/// ### `vicocomo_validate`
+///
+/// See [`Validate`](../vicocomo/active_record/trait.Validate.html). If
+/// present, the methods that call [`before_save()`
+/// ](../vicocomo/active_record/trait.BeforeSave.html#tymethod.before_save)
+/// as described for [`vicocomo_before_save`](#vicocomo_before_save) require
+/// the model to implement [`Validate`
+/// ](../vicocomo/active_record/trait.Validate.html) and call [`validate()`
+/// ](../vicocomo/active_record/trait.Validate.html#tymethod.validate) before
+/// `before_save()`.
+///
+/// On the struct the attribute takes no arguments. With a list of rules it
+/// is a field attribute, see [`vicocomo_validate(` ... `)`
+/// ](#vicocomo_validate--).
+///
+/// The errors from `validate()` are collected in the same [`ModelError`
+/// ](../vicocomo/error/struct.ModelError.html) as the errors from
+/// [`vicocomo_required`](#vicocomo_required) and [`vicocomo_validate(`
+/// ... `)`](#vicocomo_validate--) fields, and returned together.
+///
 /// ## Field attributes
 ///
 /// See above [example](#example).
@@ -198,12 +260,89 @@ mod to_fro_sql;
 ///
 /// Optional, default the snake cased field name.
 ///
+/// Note that the aggregate functions [`avg()`, `group_by()`, `max()`,
+/// `min()`, and `sum()`](../vicocomo/active_record/trait.ActiveRecord.html)
+/// take the field name, not the column name.
+///
+/// ### `vicocomo_created_at`
+///
+/// The field is a `NaiveDateTime` or `DateTime<Utc>`, possibly in an
+/// `Option`, that the generated [`ActiveRecord::insert_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert_batch)
+/// (and hence [`insert()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert)) and
+/// upsert methods set to the present time before inserting. An upsert that
+/// updates an existing row does not change it.
+///
+/// The time is taken from the [`Clock`
+/// ](../vicocomo/database/trait.Clock.html) of the [`DatabaseIf`
+/// ](../vicocomo/database/struct.DatabaseIf.html), see [`with_clock()`
+/// ](../vicocomo/database/struct.DatabaseIf.html#method.with_clock).
+/// Timestamps are left as they are if the `DatabaseIf` is
+/// [`without_timestamps()`
+/// ](../vicocomo/database/struct.DatabaseIf.html#method.without_timestamps),
+/// e.g. when restoring data. Note that [`try_from_csv()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.try_from_csv)
+/// and [`try_from_sql()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.try_from_sql)
+/// do not use the generated methods and always keep the original values.
+///
+/// The field can be `vicocomo_native`, but cannot have any of the attributes
+/// `vicocomo_belongs_to`, `vicocomo_primary`, `vicocomo_serialize`, or
+/// `vicocomo_unique`.
+///
 /// ### `vicocomo_db_value = "`*DbValue variant as str*`"`
 ///
 /// The field has a locally defined type that has implemented `Into<DbValue>`
 /// and `TryFrom<DbValue>`, e.g. using the macro [`db_value_convert`
 /// ](../vicocomo_db_macros/macro.db_value_convert.html)
 ///
+/// ### `vicocomo_lock_version`
+///
+/// Optimistic locking. The field should be a non-nullable integer, e.g.
+/// `i64`, stored in a column like `BIGINT NOT NULL DEFAULT 0`. At most one
+/// field can have this attribute, and it cannot be combined with
+/// `vicocomo_belongs_to`, `vicocomo_optional`, `vicocomo_primary`,
+/// `vicocomo_random`, `vicocomo_serialize`, or `vicocomo_unique`.
+///
+/// The generated [`ActiveRecord::update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update),
+/// [`ActiveRecord::update_columns()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update_columns),
+/// and [`ActiveRecord::delete()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete)
+/// methods only affect the row if its version column still has the value
+/// of the field, and the update methods increment it. If the row exists but
+/// the version differs, they return an [`Error::Model`
+/// ](../vicocomo/error/enum.Error.html#variant.Model) with [`ModelErrorKind
+/// ::StaleObject`](../vicocomo/error/enum.ModelErrorKind.html). The same
+/// goes for an [upsert](#for-each-vicocomo_unique-label) that finds an
+/// existing row.
+///
+/// Note that [`ActiveRecord::delete_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete_batch)
+/// takes only primary keys and does not check the version.
+///
+/// To carry the version through an HTML form, use a [hidden input
+/// ](../vicocomo/html/input/trait.HtmlForm.html#optimistic-locking).
+///
+/// ### `vicocomo_native`
+///
+/// The field is a `bool`, `NaiveDate`, `NaiveDateTime`, `DateTime<Utc>`, or
+/// `NaiveTime`, or an `Option` of one of those, that is stored using the
+/// native database type, e.g. PostgreSQL `BOOLEAN`, `DATE`, `TIMESTAMP`, or
+/// `TIME`. The
+/// generated code wraps it in a [`NativeField`
+/// ](../vicocomo/database/struct.NativeField.html) when converting to and
+/// from [`DbValue`](../vicocomo/database/enum.DbValue.html).
+///
+/// Without this attribute such a field is stored as an integer, see
+/// [`NativeField`](../vicocomo/database/struct.NativeField.html).
+///
+/// Currently a field that has this attribute cannot have any of the
+/// attributes `vicocomo_belongs_to`, `vicocomo_primary`, `vicocomo_random`,
+/// `vicocomo_required`, `vicocomo_serialize`, or `vicocomo_unique`.
+///
 /// ### `vicocomo_optional`
 ///
 /// The field should be a Rust `Option`, and a `None` value is never sent to
@@ -215,6 +354,42 @@ mod to_fro_sql;
 /// Defines a default ordering when retrieving model objects. Direction is
 /// optional and either `ASC` (default) or `DESC`.
 ///
+/// ### `vicocomo_original`
+///
+/// Change tracking. The field is of type [`Original`
+/// ](../vicocomo/active_record/struct.Original.html) and does not correspond
+/// to a database column. At most one field can have this attribute, and it
+/// cannot have any other `vicocomo_` attribute.
+///
+/// Objects loaded from the database, e.g. by [`find()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.find) or
+/// [`query()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.query), or
+/// returned from saving them, remember their column values in the field.
+/// Initialize it with `Original::default()` when creating an object.
+///
+/// The generated `changed_columns()` (see below) reports the columns that
+/// differ from the remembered values, and [`ActiveRecord::update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.update), and
+/// so [`save()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.save), sends
+/// only those to the database. If none of them changed, the hooks
+/// [`vicocomo_validate`](#vicocomo_validate) and [`vicocomo_before_save`
+/// ](#vicocomo_before_save) are still called, since they may change the
+/// object, but nothing is written and [`vicocomo_after_save`
+/// ](#vicocomo_after_save) is not called.
+///
+/// ```text
+/// #[derive(ActiveRecord)]
+/// struct Example {
+///     #[vicocomo_primary]
+///     id: u32,
+///     name: String,
+///     #[vicocomo_original]
+///     original: Original,
+/// }
+/// ```
+///
 /// ### `vicocomo_primary`
 ///
 /// The field corresponds to a primary key in the database. A primary key
@@ -243,14 +418,17 @@ mod to_fro_sql;
 /// - Fields that convert to [`DbValue::Text`
 ///   ](../vicocomo/database/enum.DbValue.html#variant.Text): Not only
 ///   whitespace
+/// - Fields that convert to [`DbValue::Blob`
+///   ](../vicocomo/database/enum.DbValue.html#variant.Blob): Not empty
 ///
 /// The generated [`ActiveRecord::insert_batch()`
 /// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert_batch)
 /// and [`ActiveRecord::update()`
 /// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update)
-/// methods return an appropriate [`Error::Model`
-/// ](../vicocomo/error/enum.Error.html#variant.Model) if this requirement is
-/// not met.
+/// methods return an [`Error::Model`
+/// ](../vicocomo/error/enum.Error.html#variant.Model) with the field error
+/// `"required"` for each field that does not meet this requirement, see also
+/// [`vicocomo_validate(` ... `)`](#vicocomo_validate--).
 ///
 /// ### `vicocomo_serialize`
 ///
@@ -263,6 +441,69 @@ mod to_fro_sql;
 /// attributes `vicocomo_belongs_to`, `vicocomo_primary`, `vicocomo_random`,
 /// or `vicocomo_unique`.
 ///
+/// ### `vicocomo_soft_delete`
+///
+/// Soft delete. The field is an `Option<NaiveDateTime>` or
+/// `Option<DateTime<Utc>>` holding the time the object was deleted, or
+/// `None` if it is not deleted. At most one field can have this attribute,
+/// and it can be `vicocomo_native` but cannot have any other attribute
+/// except `vicocomo_column`.
+///
+/// The generated [`ActiveRecord::delete()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete) and
+/// [`ActiveRecord::delete_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete_batch)
+/// set the column to [the present time
+/// ](../vicocomo/database/struct.DatabaseIf.html#method.now) instead of
+/// deleting the row, and fail as if the row was missing if it is already
+/// soft deleted.
+///
+/// Soft deleted objects are excluded by [`find()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.find),
+/// [`load()`](../vicocomo/active_record/trait.ActiveRecord.html#method.load),
+/// [`query()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.query) and the
+/// other functions taking a [`Query`
+/// ](../vicocomo/active_record/struct.Query.html), the association getters,
+/// and the [`find_by_`*...*](#for-each-vicocomo_unique-label) functions. They
+/// are also excluded when another model joins this model's table. To include
+/// them, use [`QueryBld::with_deleted()`
+/// ](../vicocomo/active_record/struct.QueryBld.html#method.with_deleted) or
+/// the functions [generated for soft deleted models
+/// ](#for-a-vicocomo_soft_delete-attributed-field). Backups made by e.g.
+/// [`try_to_csv()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.try_to_csv)
+/// include them.
+///
+/// Soft deleting an object
+/// - fails as if the database had a foreign key violation if a
+///   `vicocomo_has_many` association with `on_delete = "restrict"` has
+///   children that are not soft deleted,
+/// - soft deletes the children of each `vicocomo_has_many` association with
+///   `on_delete = "cascade"` using their `delete_batch()`, all in one
+///   transaction, failing with the error from the children's
+///   `delete_batch()` if that fails, and
+/// - leaves the children of a `vicocomo_has_many` association with
+///   `on_delete = "forget"` as they are, so that [`restore()`
+///   ](#for-a-vicocomo_soft_delete-attributed-field) reconnects them.
+///
+/// The remote type of a `vicocomo_has_many` association with `on_delete =
+/// "cascade"` must also be a soft delete model, or the generated code does
+/// not compile. The object and its cascaded children get the same deletion
+/// time, truncated to whole seconds, so that `restore()` can find them. If
+/// needed, the time is moved forward past the deletion time of any child
+/// that was soft deleted before, so that `restore()` leaves that child
+/// deleted.
+///
+/// ### `vicocomo_updated_at`
+///
+/// Like [`vicocomo_created_at`](#vicocomo_created_at), but also set by the
+/// generated [`ActiveRecord::update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update) and,
+/// unless the column is given explicitly, [`ActiveRecord::update_columns()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update_columns),
+/// and by an upsert that updates an existing row.
+///
 /// ### `vicocomo_unique = "`*a label*`"`
 ///
 /// The tuple of fields whith the same label should be unique in the database.
@@ -280,6 +521,58 @@ mod to_fro_sql;
 ///
 /// Primary keys do not need this attrbute.
 ///
+/// ### `vicocomo_validate(` ... `)`
+///
+/// Declarative validation, checked together with [`vicocomo_required`
+/// ](#vicocomo_required) before saving. A field may have several rules, in
+/// one or more attributes, separated by commas. The rules are checked only
+/// if the field has a value, i.e. not if an `Option` field is `None`. For
+/// each rule that is not met, the field error text given below is added.
+///
+/// Without arguments the attribute belongs on the struct, see
+/// [`vicocomo_validate`](#vicocomo_validate).
+///
+/// - `length(min = `*int*`, max = `*int*`)`: The number of characters of a
+///   text field or bytes of a blob field. `min` and `max` are optional.
+///   Error `"too-short"` or `"too-long"`.
+///
+/// - `range(min = `*number*`, max = `*number*`)`: The value of a numeric
+///   field. `min` and `max` are optional. Error `"too-small"` or
+///   `"too-large"`.
+///
+/// - `regex = "`*a regular expression*`"`: The value of a text field should
+///   match. Error `"invalid-format"`. The generated code uses the crate
+///   `regex`.
+///
+/// - `email`: The value of a text field should look like an email address.
+///   Error `"invalid-email"`. The generated code uses the crate `regex`.
+///
+/// - `one_of = [`*literal*`, ...]`: The value of a text or numeric field
+///   should be one of the literals. Error `"not-one-of"`.
+///
+/// - `custom = "`*path to a function*`"`: The function is called with a
+///   reference to the value and should return `Result<(), String>`. The
+///   `Err` string is the error text.
+///
+/// Example:
+/// ```text
+/// #[vicocomo_validate(length(min = 1, max = 80), regex = "^[a-z]")]
+/// name: String,
+/// #[vicocomo_validate(range(min = -1, max = 1.5))]
+/// value: Option<f64>,
+/// ```
+///
+/// The generated [`ActiveRecord::insert_batch()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.insert_batch)
+/// and [`ActiveRecord::update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update)
+/// methods return an [`Error::Model`
+/// ](../vicocomo/error/enum.Error.html#variant.Model) with the kind
+/// `CannotSave` and all the field errors found, so that e.g. the error text
+/// for `"too-long"` in the field `name` of the model `Example` is localized
+/// by the key `error--Model-CannotSave--Example--name--too-long`, see
+/// [`Error`](../vicocomo/error/enum.Error.html).
+///
 /// ## Referential Integrity
 ///
 /// <b>At present, the attriubutes `vicocomo_belongs_to` and
@@ -299,8 +592,63 @@ mod to_fro_sql;
 ///   key declarations referring to the primary keys of the tables storing the
 ///   `Self` and remote types that ensure cascading on-delete behavior.
 ///
-/// The intention is to use the attributes to generate referential integrity
-/// tests and/or automatic schema generation in future releases.
+/// The generated [`table_schema()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.table_schema)
+/// and [`join_table_schemas()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.join_table_schemas)
+/// describe such declarations, see [Schema](#schema) below, and
+/// [`verify_schema()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.verify_schema)
+/// checks that they are in the database, e.g. at application startup.
+///
+/// ## Schema
+///
+/// The tables needed to store the model can be generated from the field
+/// types and attributes, see [`create_table_sql()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.create_table_sql)
+/// and [`define_schema!()`](../vicocomo/macro.define_schema.html).
+///
+/// - The column type is derived from the field type, `NOT NULL` unless the
+///   field is an `Option` that is not `vicocomo_optional`.
+///
+/// - A single `vicocomo_optional` integer primary key is auto incremented.
+///   Other primary keys get a `PRIMARY KEY` constraint.
+///
+/// - Other `vicocomo_optional` fields that are not nullable get the zero or
+///   empty value of the type as `DEFAULT`, except `vicocomo_serialize` fields
+///   that get no default. Edit the SQL if you need other defaults.
+///
+/// - Each `vicocomo_unique` label gets a `UNIQUE` constraint.
+///
+/// - Each `vicocomo_belongs_to` field gets a `FOREIGN KEY` constraint with
+///   the `on_delete` behavior of the corresponding `vicocomo_has_many` in the
+///   remote type, if any.
+///
+/// - Each many-to-many `vicocomo_has_many` gets a join table with cascading
+///   foreign keys to both tables.
+///
+/// - All foreign keys are `DEFERRABLE INITIALLY DEFERRED`.
+///
+/// `CHECK` constraints and indexes are not generated.
+///
+/// ## Other Constraints
+///
+/// If the database throws a `NOT NULL` or `CHECK` violation error when
+/// saving, the generated code transforms it to an [`Error::Model`
+/// ](../vicocomo/error/enum.Error.html#variant.Model) with `error` [`NotNull`
+/// ](../vicocomo/error/enum.ModelErrorKind.html#variant.NotNull) or
+/// [`CheckFailed`
+/// ](../vicocomo/error/enum.ModelErrorKind.html#variant.CheckFailed) and
+/// `general` `"not-null-violation"` or `"check-violation"`, respectively.
+///
+/// For a `NOT NULL` violation there is a field error for the field whose
+/// column the database reports, see [`DatabaseError`
+/// ](../vicocomo/error/struct.DatabaseError.html). For a `CHECK` violation
+/// there is a field error if the constraint is named
+/// `<`*table*`>_<`*column*`>_check`, which is what PostgreSQL names a
+/// `CHECK` constraint declared on a column. With SQLite the constraint must
+/// be named explicitly, e.g. `CONSTRAINT scores_points_check CHECK (points
+/// >= 0)`.
 ///
 /// ## Generated code
 ///
@@ -309,12 +657,11 @@ mod to_fro_sql;
 ///
 /// Note that while the implementation of [`delete()`
 /// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete)
-/// does acknowledge the attribute `vicocomo_before_delete` and does call
-/// [`before_delete()`
-/// ](../vicocomo/active_record/trait.BeforeDelete.html#tymethod.before_delete),
-/// the implementation of [`delete_batch()`
+/// does acknowledge the attributes `vicocomo_before_delete` and
+/// `vicocomo_after_delete`, the implementation of [`delete_batch()`
 /// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete_batch)
-/// does *not*!
+/// does so only if the model also has the attribute
+/// [`vicocomo_delete_batch_hooks`](#vicocomo_delete_batch_hooks)!
 ///
 /// ### For each `vicocomo_belongs_to` attributed field
 ///
@@ -322,6 +669,9 @@ mod to_fro_sql;
 /// "*name*" means the `name` value if given, or the last segment of
 /// `remote_type` if not, snake cased.
 ///
+/// A query may join *Remote*'s table by [`QueryBld::join("`*name*`")`
+/// ](../vicocomo/active_record/struct.QueryBld.html#method.join).
+///
 /// ##### `pub fn all_belonging_to_`*name*`(db: DatabaseIf, remote: &`*Remote*`) -> Result<Vec<Self>, Error>`
 ///
 /// Retrieve all objects in the database belonging to an instance of *Remote*.
@@ -352,6 +702,35 @@ mod to_fro_sql;
 ///   matching the field value, or
 /// - because of some other database error.
 ///
+/// ##### `pub fn load_grouped_by_`*name*`(db: DatabaseIf, remotes: &[`*Remote*`]) -> Result<HashMap<`*Remote*`::PkType, Vec<Self>>, Error>`
+///
+/// Retrieve all objects in the database belonging to any of `remotes` using
+/// a single query, grouped by the primary key of the *Remote*. Avoids one
+/// `all_belonging_to_`*name*`()` query per remote object.
+///
+/// Each object in `remotes` that has a primary key gets an entry, maybe with
+/// an empty vector.
+///
+/// <b>Errors</b>
+///
+/// [`Error::Database`](../vicocomo/error/enum.Error.html#variant.Database)
+/// return if there is some database error.
+///
+/// ##### `pub fn preload_`*name*`(db: DatabaseIf, objs: &[Self]) -> Result<HashMap<`*Remote*`::PkType, `*Remote*`>, Error>`
+///
+/// Retrieve the objects on the remote side of the relationship for all of
+/// `objs` using a single query, keyed by their primary key, i.e. the value
+/// of the association field in `objs`. Avoids one *name*`()` query per
+/// object.
+///
+/// There is no entry for an association field that is `None` or a value
+/// that is not in the remote table.
+///
+/// <b>Errors</b>
+///
+/// [`Error::Database`](../vicocomo/error/enum.Error.html#variant.Database)
+/// return if there is some database error.
+///
 /// ##### `pub fn set_`*name*`(&mut self, remote: &`*Remote*`) -> Result<(), Error>`
 ///
 /// Set the reference to an object on the remote side of the relationship.
@@ -387,6 +766,9 @@ mod to_fro_sql;
 /// "*name*" means the `name` value if given, or the last segment of
 /// `remote_type` if not, snake cased.
 ///
+/// A query may join *Remote*'s table by [`QueryBld::join("`*name*`s")`
+/// ](../vicocomo/active_record/struct.QueryBld.html#method.join).
+///
 /// ##### `pub fn `*name*`s(&self, db: DatabaseIf, filter: Option<&Query>) -> Result<Vec<`*Remote*`>, Error>`
 ///
 /// Find items related to `self` by the association, filtered by `filter`.
@@ -396,6 +778,21 @@ mod to_fro_sql;
 /// `filter`, see [`QueryBld`](model/struct.QueryBld.html). A condition to
 /// select only among the associated objects is automatically added.
 ///
+/// ##### `pub fn preload_`*name*`s(db: DatabaseIf, objs: &[Self]) -> Result<HashMap<Self::PkType, Vec<`*Remote*`>>, Error>`
+///
+/// Retrieve the items related by the association to any of `objs` using a
+/// single query for one-to-many associations and two for many-to-many,
+/// grouped by the primary key of the object in `objs`. Avoids one
+/// *name*`s()` query per object.
+///
+/// Each object in `objs` that has a primary key gets an entry, maybe with an
+/// empty vector. The items are ordered as by *name*`s()`.
+///
+/// <b>Errors</b>
+///
+/// [`Error::Database`](../vicocomo/error/enum.Error.html#variant.Database)
+/// return if there is some database error.
+///
 /// ##### `pub fn save_`*name*`s(&self, db: DatabaseIf, remotes: &[`*Remote*`]) -> Result<(), Error>`
 ///
 /// Set and [`save()`
@@ -455,6 +852,69 @@ mod to_fro_sql;
 /// Delete the join table row connecting `self` to `remote`. *Returns `Ok(0)`
 /// if they are not connected*.
 ///
+/// ### For a `vicocomo_original` attributed field
+///
+/// ##### `pub fn changed_columns(&self) -> Vec<String>`
+///
+/// The names of the columns whose values in `self` differ from those
+/// remembered in the [`vicocomo_original`](#vicocomo_original) field, in the
+/// order of [`columns()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#tymethod.columns).
+/// All columns if `self` was not loaded from the database.
+///
+/// A [`vicocomo_optional`](#vicocomo_optional) or [`vicocomo_random`
+/// ](#vicocomo_random) field that is `None` is never reported, since it is
+/// not sent to the database.
+///
+/// ### For a `vicocomo_soft_delete` attributed field
+///
+/// Below, *deleted* means the [`vicocomo_soft_delete`
+/// ](#vicocomo_soft_delete) field. The functions that need a primary key are
+/// not generated if there is none or the model is [`vicocomo_readonly`
+/// ](#vicocomo_readonly).
+///
+/// ##### `pub fn find_with_deleted(db: DatabaseIf, pk: &Self::PkType) -> Option<Self>`
+///
+/// Like [`find()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.find), but
+/// also finds a soft deleted object.
+///
+/// ##### `pub fn load_with_deleted(db: DatabaseIf) -> Result<Vec<Self>, Error>`
+///
+/// Like [`load()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.load), but
+/// also returns the soft deleted objects.
+///
+/// ##### `pub fn purge(self, db: DatabaseIf) -> Result<(), Error>`
+///
+/// Delete the database row, soft deleted or not, as [`delete()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.delete) would
+/// without the `vicocomo_soft_delete` attribute, except that
+/// [`before_delete()`
+/// ](../vicocomo/active_record/trait.BeforeDelete.html#tymethod.before_delete)
+/// is not called. Referential integrity is left to the database, so e.g. the
+/// children of a `vicocomo_has_many` association with `on_delete =
+/// "restrict"` prevent purging also if they are soft deleted.
+///
+/// ##### `pub fn restore(&mut self, db: DatabaseIf) -> Result<(), Error>`
+///
+/// Set *deleted* to `None` in the database and in `self`, and restore the
+/// children that were soft deleted by `on_delete = "cascade"` when `self`
+/// was, i.e. at the time in *deleted*, all in one transaction. Children that
+/// were soft deleted separately are left as they are.
+///
+/// Handles a [`vicocomo_lock_version`](#vicocomo_lock_version) field like
+/// [`update()`
+/// ](../vicocomo/active_record/trait.ActiveRecord.html#method.update).
+///
+/// <b>Errors</b>
+///
+/// Returns an [`Error::Model`
+/// ](../vicocomo/error/enum.Error.html#variant.Model) with
+/// [`ModelErrorKind::CannotSave`
+/// ](../vicocomo/error/enum.ModelErrorKind.html) if `self` has no primary
+/// key or there is no such row, soft deleted or not.
+///
 /// ### For each `vicocomo_unique` label
 ///
 /// Given the struct declaration
@@ -488,24 +948,75 @@ mod to_fro_sql;
 ///
 /// `db` is the [database connection](../vicocomo/struct.DatabaseIf.html).
 ///
+/// ##### `pub fn upsert_by_un1_and_un2(&mut self, db: DatabaseIf) -> Result<(), Error>`
+///
+/// Insert `self`, or update the object in the database that has the same
+/// values for the unique fields, in one `INSERT ... ON CONFLICT (un_1, un_2)
+/// DO UPDATE` statement. See `upsert_batch_by_un1_and_un2()`.
+///
+/// ##### `pub fn upsert_batch_by_un1_and_un2(db: DatabaseIf, data: &mut [Self]) -> Result<(), Error>`
+///
+/// Insert or update each object in `data` as `upsert_by_un1_and_un2()`, and
+/// update the objects from the database, e.g. with a database generated
+/// primary key. There is one statement for each object, so that the
+/// returned row is known to belong to it, and they are all done in one
+/// transaction.
+///
+/// An existing row is updated with the inserted columns except the primary
+/// key and the unique fields, so a `vicocomo_optional` field that is `None`
+/// keeps its value. Consequently, if `data` has a primary key value that
+/// differs from the existing row's, the object is updated with the latter.
+///
+/// [`before_save()`
+/// ](../vicocomo/active_record/trait.BeforeSave.html#tymethod.before_save)
+/// and [`after_save()`
+/// ](../vicocomo/active_record/trait.AfterSave.html#tymethod.after_save)
+/// are called for each object if the struct has the attribute
+/// [`vicocomo_before_save`](#vicocomo_before_save) and
+/// [`vicocomo_after_save`](#vicocomo_after_save), respectively.
+///
+/// <b>Errors</b>
+///
+/// Returns [`Error::InvalidInput`
+/// ](../vicocomo/error/enum.Error.html#variant.InvalidInput) if two objects
+/// in `data` have the same values for the unique fields, and an
+/// [`Error::Model`](../vicocomo/error/enum.Error.html#variant.Model) if an
+/// unwrapped unique field is `None` or, if the struct has a
+/// [`vicocomo_lock_version`](#vicocomo_lock_version) field, an existing row
+/// has another version. Database errors are handled as by `insert_batch()`.
+///
+/// Always returns [`Err(Error::Other("not-available")`
+/// ](../vicocomo/error/enum.Error.html#variant.Other) if the model has the
+/// attribute [`vicocomo_readonly`](#vicocomo_readonly).
+///
 #[proc_macro_derive(
     ActiveRecord,
     attributes(
+        vicocomo_after_delete,
+        vicocomo_after_save,
         vicocomo_before_delete,
         vicocomo_before_save,
         vicocomo_belongs_to,
         vicocomo_column,
+        vicocomo_created_at,
         vicocomo_db_value,
+        vicocomo_delete_batch_hooks,
         vicocomo_has_many,
+        vicocomo_lock_version,
+        vicocomo_native,
         vicocomo_optional,
         vicocomo_order_by,
+        vicocomo_original,
         vicocomo_primary,
         vicocomo_random,
         vicocomo_readonly,
         vicocomo_required,
         vicocomo_serialize,
+        vicocomo_soft_delete,
         vicocomo_table_name,
         vicocomo_unique,
+        vicocomo_updated_at,
+        vicocomo_validate,
     )
 )]
 pub fn active_record_derive(input: TokenStream) -> TokenStream {
@@ -524,6 +1035,7 @@ pub fn active_record_derive(input: TokenStream) -> TokenStream {
         has_many::has_many_impl(&model, &mut struct_fn, &mut trait_fn);
     }
     save::save_impl(&model, &mut struct_fn, &mut trait_fn);
+    schema::schema_impl(&model, &mut trait_fn);
     to_fro_sql::to_fro_sql_impl(&model, &mut trait_fn);
 
     let struct_id = &model.struct_id;
@@ -1,4 +1,5 @@
 // TODO: -> Vec<&Field> => -> Filter
+use crate::validation::Validation;
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use std::collections::HashMap;
@@ -13,10 +14,21 @@ const ATTR_BELONGS_TO_ERROR: &'static str =
     "expected #[vicocomo_belongs_to( ... )]";
 const ATTR_COLUMN_ERROR: &'static str =
     "expected #[vicocomo_column = \"column_name\"]";
+const ATTR_CREATED_AT_ERROR: &'static str = "expected #[vicocomo_created_at]";
 const ATTR_DB_VALUE_ERROR: &'static str =
     "expected #[vicocomo_db_value = \"<DbValue variant as str>\"]";
+const ATTR_DELETE_BATCH_HOOKS_ERROR: &'static str =
+    "#[vicocomo_delete_batch_hooks] requires #[vicocomo_before_delete] or \
+    #[vicocomo_after_delete]";
 const ATTR_SERIALIZE_ERROR: &'static str = "expected #[vicocomo_serialize]";
+const ATTR_LOCK_VERSION_ERROR: &'static str =
+    "expected #[vicocomo_lock_version] on an integer field that is not \
+    nullable";
+const ATTR_NATIVE_ERROR: &'static str = "expected #[vicocomo_native]";
 const ATTR_OPTIONAL_ERROR: &'static str = "expected #[vicocomo_optional]";
+const ATTR_ORIGINAL_ERROR: &'static str =
+    "expected at most one #[vicocomo_original] field, of type Original and \
+    with no other vicocomo attributes";
 const ATTR_ORDER_ERROR: &'static str =
     "expected #[vicocomo_order_by(<int>, <\"ASC\"/\"DESC\">)]";
 const ATTR_PRIMARY_ERROR: &'static str = "expected #[vicocomo_primary]";
@@ -24,8 +36,20 @@ const ATTR_RANDOM_ERROR: &'static str =
     "expected #[vicocomo_random] on an Option<i64> field";
 const ATTR_REQUIRED_ERROR: &'static str =
     "expected #[vicocomo_required] on a field that is not nullable";
+const ATTR_SOFT_DELETE_ERROR: &'static str =
+    "expected #[vicocomo_soft_delete] on an Option<NaiveDateTime> or \
+    Option<DateTime<Utc>> field that is not a primary or foreign key, \
+    serialized, a timestamp, or part of a unique field tuple";
+const ATTR_TIMESTAMP_ERROR: &'static str =
+    "a timestamp field should be a NaiveDateTime or DateTime<Utc>, possibly \
+    in an Option, and cannot be a primary or foreign key, serialized, or \
+    part of a unique field tuple";
+const ATTR_UPDATED_AT_ERROR: &'static str = "expected #[vicocomo_updated_at]";
 const ATTR_UNIQUE_ERROR: &'static str =
     "expected #[vicocomo_unique = \"label\"]";
+const ATTR_VALIDATE_ERROR: &'static str =
+    "expected #[vicocomo_validate] without arguments on the struct, rules \
+    like #[vicocomo_validate(length(max = <int>))] belong on the fields";
 
 // determines what to send to the database when inserting or updating if the
 // field is an Option and the value is None
@@ -51,6 +75,13 @@ impl OnNone {
     }
 }
 
+// which timestamp a field holds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Timestamp {
+    CreatedAt, // set when inserting
+    UpdatedAt, // set when inserting or updating
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum Order {
     Asc(u32),
@@ -85,10 +116,20 @@ pub(crate) struct Field {
     pub(crate) dbt: DbType,
     // indictates that the field is serialized
     pub(crate) ser: bool,
+    // indicates that the field is stored as a native bool, date, or time
+    pub(crate) nat: bool,
     // indictates that the field is (part of) the primary key
     pub(crate) pri: bool,
     // indicates that the field must not have a zero or empty value
     pub(crate) req: bool,
+    // indicates that the field holds the optimistic locking version
+    pub(crate) lck: bool,
+    // indicates that the field holds a timestamp set by the generated code
+    pub(crate) tsp: Option<Timestamp>,
+    // indicates that the field holds the time the row was soft deleted
+    pub(crate) sdl: bool,
+    // rules from vicocomo_validate attributes
+    pub(crate) vld: Vec<Validation>,
     pub(crate) ord: Option<Order>,
     pub(crate) onn: OnNone,
     pub(crate) fk: Option<ForKey>,
@@ -142,6 +183,10 @@ pub(crate) struct UniqueFieldSet {
     pub(crate) find_eq_id: Ident,
     // the Expr-s unwrap optional fields in the set - beware!
     pub(crate) find_self_args: Vec<Expr>,
+    // upsert_by_...
+    pub(crate) upsert_by_id: Ident,
+    // upsert_batch_by_...
+    pub(crate) upsert_batch_by_id: Ident,
 }
 
 #[derive(Clone, Debug)]
@@ -151,12 +196,22 @@ pub(crate) struct Model {
     // Database table name
     pub(crate) table_name: String,
     pub(crate) has_many: Vec<HasMany>,
+    // indicates presence of the vicocomo_after_delete attribute
+    pub(crate) after_delete: bool,
+    // indicates presence of the vicocomo_after_save attribute
+    pub(crate) after_save: bool,
     // indicates presence of the vicocomo_before_delete attribute
     pub(crate) before_delete: bool,
     // indicates presence of the vicocomo_before_save attribute
     pub(crate) before_save: bool,
+    // indicates presence of the vicocomo_delete_batch_hooks attribute
+    pub(crate) delete_batch_hooks: bool,
     // indicates presence of the vicocomo_readonly attribute
     pub(crate) readonly: bool,
+    // indicates presence of the vicocomo_validate attribute on the struct
+    pub(crate) validate: bool,
+    // the vicocomo_original field, which is not a column
+    pub(crate) original: Option<Ident>,
     pub(crate) fields: Vec<Field>,
     pub(crate) uniques: Vec<UniqueFieldSet>,
 }
@@ -190,27 +245,71 @@ impl Model {
             get_string_from_attr(&attrs, "table_name", &struct_id, |id| {
                 format!("{}s", id).to_snake()
             });
-        let before_delete: bool = attrs
-            .iter()
-            .any(|a| a.path.is_ident("vicocomo_before_delete"));
-        let before_save: bool = attrs
-            .iter()
-            .any(|a| a.path.is_ident("vicocomo_before_save"));
-        let readonly: bool =
-            attrs.iter().any(|a| a.path.is_ident("vicocomo_readonly"));
+        let has_attr =
+            |name: &str| attrs.iter().any(|a| a.path.is_ident(name));
+        let after_delete = has_attr("vicocomo_after_delete");
+        let after_save = has_attr("vicocomo_after_save");
+        let before_delete = has_attr("vicocomo_before_delete");
+        let before_save = has_attr("vicocomo_before_save");
+        let delete_batch_hooks = has_attr("vicocomo_delete_batch_hooks");
+        if delete_batch_hooks && !before_delete && !after_delete {
+            panic!("{}", ATTR_DELETE_BATCH_HOOKS_ERROR);
+        }
+        let readonly = has_attr("vicocomo_readonly");
+        // on the struct a bare hook switch, on a field a list of rules
+        let validate = has_attr("vicocomo_validate");
+        if attrs.iter().any(|a| {
+            a.path.is_ident("vicocomo_validate") && !a.tokens.is_empty()
+        }) {
+            panic!("{}", ATTR_VALIDATE_ERROR);
+        }
         let has_many: Vec<HasMany> =
             Self::get_has_many(attrs, &struct_id.to_string());
+        let mut original = None;
         let mut fields = Vec::new();
         let mut unis: HashMap<String, Vec<Field>> = HashMap::new();
         for field in named_fields {
             let id = field.ident.expect("expected field identifier").clone();
+            if field
+                .attrs
+                .iter()
+                .any(|a| a.path.is_ident("vicocomo_original"))
+            {
+                let vicocomo_attrs = field.attrs.iter().filter(|a| {
+                    a.path.segments.first().map_or(false, |seg| {
+                        seg.ident.to_string().starts_with("vicocomo_")
+                    })
+                });
+                let orig_type = match &field.ty {
+                    Type::Path(type_path) => type_path
+                        .path
+                        .segments
+                        .last()
+                        .map(|seg| seg.ident.to_string()),
+                    _ => None,
+                };
+                assert!(
+                    original.is_none()
+                        && vicocomo_attrs.count() == 1
+                        && orig_type.as_deref() == Some("Original"),
+                    "{}",
+                    ATTR_ORIGINAL_ERROR,
+                );
+                original = Some(id);
+                continue;
+            }
             let ty = field.ty.clone();
             let mut col =
                 LitStr::new(id.to_string().as_str(), Span::call_site());
             let mut dbt = None;
             let mut ser = false;
+            let mut nat = false;
             let mut pri = false;
             let mut req = false;
+            let mut lck = false;
+            let mut tsp = None;
+            let mut sdl = false;
+            let mut vld = Vec::new();
             let mut uni = None;
             let mut ord = None;
             let mut onn = OnNone::Null;
@@ -307,6 +406,13 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                                 _ => panic!("{}", ATTR_COLUMN_ERROR),
                             };
                     }
+                    "vicocomo_created_at" => {
+                        match attr.parse_meta().expect(ATTR_CREATED_AT_ERROR)
+                        {
+                            Meta::Path(_) => tsp = Some(Timestamp::CreatedAt),
+                            _ => panic!("{}", ATTR_CREATED_AT_ERROR),
+                        };
+                    }
                     "vicocomo_db_value" => {
                         dbt = match attr
                             .parse_meta()
@@ -315,9 +421,11 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                             Meta::NameValue(value) => match value.lit {
                                 Lit::Str(var_lit) => {
                                     match var_lit.value().as_str() {
+                                        "Blob" => Some(DbType::Blob),
                                         "Float" => Some(DbType::Float),
                                         "Int" => Some(DbType::Int),
                                         "Text" => Some(DbType::Text),
+                                        "NulBlob" => Some(DbType::NulBlob),
                                         "NulFloat" => Some(DbType::NulFloat),
                                         "NulInt" => Some(DbType::NulInt),
                                         "NulText" => Some(DbType::NulText),
@@ -337,6 +445,21 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                             _ => panic!("{}", ATTR_SERIALIZE_ERROR),
                         };
                     }
+                    "vicocomo_lock_version" => {
+                        match attr
+                            .parse_meta()
+                            .expect(ATTR_LOCK_VERSION_ERROR)
+                        {
+                            Meta::Path(_) => lck = true,
+                            _ => panic!("{}", ATTR_LOCK_VERSION_ERROR),
+                        };
+                    }
+                    "vicocomo_native" => {
+                        match attr.parse_meta().expect(ATTR_NATIVE_ERROR) {
+                            Meta::Path(_) => nat = true,
+                            _ => panic!("{}", ATTR_NATIVE_ERROR),
+                        };
+                    }
                     "vicocomo_optional" => {
                         match attr.parse_meta().expect(ATTR_OPTIONAL_ERROR) {
                             Meta::Path(_) => onn = OnNone::Ignore,
@@ -425,6 +548,20 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                             _ => panic!("{}", ATTR_REQUIRED_ERROR),
                         };
                     }
+                    "vicocomo_soft_delete" => {
+                        match attr.parse_meta().expect(ATTR_SOFT_DELETE_ERROR)
+                        {
+                            Meta::Path(_) => sdl = true,
+                            _ => panic!("{}", ATTR_SOFT_DELETE_ERROR),
+                        };
+                    }
+                    "vicocomo_updated_at" => {
+                        match attr.parse_meta().expect(ATTR_UPDATED_AT_ERROR)
+                        {
+                            Meta::Path(_) => tsp = Some(Timestamp::UpdatedAt),
+                            _ => panic!("{}", ATTR_UPDATED_AT_ERROR),
+                        };
+                    }
                     "vicocomo_unique" => {
                         let label =
                             match attr.parse_meta().expect(ATTR_UNIQUE_ERROR)
@@ -437,6 +574,9 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                             };
                         uni = Some(label);
                     }
+                    "vicocomo_validate" => {
+                        vld.extend(Validation::from_attr(&attr));
+                    }
                     _ => (),
                 }
             }
@@ -444,7 +584,6 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
             static DB_TYPES: OnceLock<HashMap<String, DbType>> =
                 OnceLock::new();
             DB_TYPES.get_or_init(|| {
-                use ::quote::format_ident;
                 let mut map = HashMap::new();
                 for (typ_str, var_str) in &[
                     ("bool", "Int"),
@@ -458,14 +597,16 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                     ("NaiveDate", "Int"),
                     ("NaiveDateTime", "Int"),
                     ("NaiveTime", "Int"),
+                    ("DateTime<Utc>", "Int"),
                     ("String", "Text"),
+                    ("Vec<u8>", "Blob"),
                 ] {
-                    let typ_id = format_ident!("{}", typ_str);
-                    let typ: Type = parse_quote!(#typ_id);
-                    let opt: Type = parse_quote!(Option<#typ_id>);
+                    let typ: Type = ::syn::parse_str(typ_str).unwrap();
+                    let opt: Type = parse_quote!(Option<#typ>);
                     map.insert(
                         tokens_to_string(&typ),
                         match *var_str {
+                            "Blob" => DbType::Blob,
                             "Float" => DbType::Float,
                             "Int" => DbType::Int,
                             "Text" => DbType::Text,
@@ -475,6 +616,7 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                     map.insert(
                         tokens_to_string(&opt),
                         match *var_str {
+                            "Blob" => DbType::NulBlob,
                             "Float" => DbType::NulFloat,
                             "Int" => DbType::NulInt,
                             "Text" => DbType::NulText,
@@ -496,6 +638,40 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                 );
                 dbt = Some(DbType::Text);
             }
+            if nat {
+                assert!(
+                    !(ser
+                        || req
+                        || onn == OnNone::Random
+                        || pri
+                        || uni.is_some()
+                        || fk.is_some()),
+                    "a native field cannot be serialized, required, a \
+                    primary or foreign key, or part of a unique field tuple",
+                );
+                let type_string = tokens_to_string(effective_type);
+                let (typ_str, nul) = match Model::strip_option(effective_type)
+                {
+                    Some(t) => (tokens_to_string(t), true),
+                    None => (type_string.clone(), false),
+                };
+                dbt = Some(match (typ_str.as_str(), nul) {
+                    ("bool", false) => DbType::Bool,
+                    ("NaiveDate", false) => DbType::Date,
+                    ("NaiveDateTime", false) => DbType::DateTime,
+                    ("DateTime < Utc >", false) => DbType::DateTime,
+                    ("NaiveTime", false) => DbType::Time,
+                    ("bool", true) => DbType::NulBool,
+                    ("NaiveDate", true) => DbType::NulDate,
+                    ("NaiveDateTime", true) => DbType::NulDateTime,
+                    ("DateTime < Utc >", true) => DbType::NulDateTime,
+                    ("NaiveTime", true) => DbType::NulTime,
+                    _ => panic!(
+                        "Type {} cannot be #[vicocomo_native]",
+                        type_string,
+                    ),
+                });
+            }
             let dbt = dbt.unwrap_or_else(|| {
                 let type_string = tokens_to_string(effective_type);
                 *DB_TYPES.get().unwrap().get(&type_string).unwrap_or_else(
@@ -509,14 +685,66 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                 )
             });
             assert!(!(req && dbt.nul()), "{}", ATTR_REQUIRED_ERROR);
+            if lck {
+                assert!(
+                    dbt == DbType::Int
+                        && onn == OnNone::Null
+                        && !(ser || pri || uni.is_some() || fk.is_some()),
+                    "{}",
+                    ATTR_LOCK_VERSION_ERROR,
+                );
+                assert!(
+                    !fields.iter().any(|f: &Field| f.lck),
+                    "at most one field can be #[vicocomo_lock_version]",
+                );
+            }
+            if tsp.is_some() {
+                assert!(
+                    Self::timestamp_utc(&ty).is_some()
+                        && !(ser
+                            || pri
+                            || lck
+                            || uni.is_some()
+                            || fk.is_some()),
+                    "{}",
+                    ATTR_TIMESTAMP_ERROR,
+                );
+            }
+            if sdl {
+                assert!(
+                    Self::timestamp_utc(&ty).is_some()
+                        && dbt.nul()
+                        && onn == OnNone::Null
+                        && !(ser
+                            || pri
+                            || lck
+                            || tsp.is_some()
+                            || uni.is_some()
+                            || fk.is_some()),
+                    "{}",
+                    ATTR_SOFT_DELETE_ERROR,
+                );
+                assert!(
+                    !fields.iter().any(|f: &Field| f.sdl),
+                    "at most one field can be #[vicocomo_soft_delete]",
+                );
+            }
+            for rule in &vld {
+                rule.check_type(dbt, ser, nat);
+            }
             let field = Field {
                 id,
                 ty,
                 col,
                 dbt,
                 ser,
+                nat,
                 pri,
                 req,
+                lck,
+                tsp,
+                sdl,
+                vld,
                 ord,
                 onn,
                 fk,
@@ -560,6 +788,14 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                     Span::call_site(),
                 ),
                 find_self_args,
+                upsert_by_id: Ident::new(
+                    &format!("upsert_by_{}", &uni_str),
+                    Span::call_site(),
+                ),
+                upsert_batch_by_id: Ident::new(
+                    &format!("upsert_batch_by_{}", &uni_str),
+                    Span::call_site(),
+                ),
             }
         }));
 
@@ -567,9 +803,14 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
             struct_id,
             table_name,
             has_many,
+            after_delete,
+            after_save,
             before_delete,
             before_save,
+            delete_batch_hooks,
             readonly,
+            validate,
+            original,
             fields,
             uniques,
         }
@@ -634,8 +875,65 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
         None
     }
 
+    // If ty is NaiveDateTime or DateTime<Utc>, possibly in one or two
+    // Option-s, Some(true iff DateTime<Utc>), else None.
+    pub(crate) fn timestamp_utc(ty: &Type) -> Option<bool> {
+        let mut ty = ty;
+        while let Some(t) = Self::strip_option(ty) {
+            ty = t;
+        }
+        match tokens_to_string(ty).as_str() {
+            "NaiveDateTime" => Some(false),
+            "DateTime < Utc >" => Some(true),
+            _ => None,
+        }
+    }
+
+    // An expression evaluating to the value to assign to the timestamp field
+    // fld, given now that should evaluate to a DateTime<Utc>.
+    pub(crate) fn timestamp_value_expr(fld: &Field, now: Expr) -> Expr {
+        let mut result: Expr = if Self::timestamp_utc(&fld.ty).unwrap() {
+            now
+        } else {
+            parse_quote!(#now.naive_utc())
+        };
+        if fld.dbt.nul() {
+            result = parse_quote!(Some(#result));
+        }
+        if fld.onn != OnNone::Null {
+            result = parse_quote!(Some(#result));
+        }
+        result
+    }
+
+    // If wrap, body (evaluating to a Result) in a transaction on the local
+    // variable db, so that an error from an after-hook in body rolls back the
+    // database changes made by body.
+    pub(crate) fn transaction_expr(wrap: bool, body: Expr) -> Expr {
+        if wrap {
+            parse_quote!(db.clone().transaction(|db| #body))
+        } else {
+            body
+        }
+    }
+
     // public methods with receiver  - - - - - - - - - - - - - - - - - - - - -
 
+    // Evaluates to (), but if there is an AfterSave hook, calls it on obj,
+    // returning if it fails.
+    //
+    pub(crate) fn after_save_expr(&self, obj: Expr) -> Expr {
+        if self.after_save {
+            parse_quote!(::vicocomo::AfterSave::after_save(#obj, db.clone())?)
+        } else {
+            parse_quote!(())
+        }
+    }
+
+    // Evaluates to (), but collects the errors from vicocomo_required and
+    // vicocomo_validate fields and a Validate hook, returning them if any,
+    // and then calls a BeforeSave hook, returning if it fails.
+    //
     pub(crate) fn before_save_expr(&self, obj: Ident) -> Expr {
         let req_chk: Vec<Expr> = self
             .fields
@@ -645,32 +943,39 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                 let fld_id = &f.id;
                 let fld_lit =
                     LitStr::new(&fld_id.to_string(), Span::call_site());
-                let struct_lit = LitStr::new(
-                    &self.struct_id.to_string(),
-                    Span::call_site(),
+                let err: Expr = parse_quote!(
+                    errors.add_field_error(#fld_lit, "required")
                 );
-                let err: Expr = parse_quote!(::vicocomo::model_error!(
-                    CannotSave,
-                    #struct_lit: "",
-                    #fld_lit: ["required"],
-                ));
-                if f.dbt.text() {
+                if f.dbt.blob() {
+                    if f.onn == OnNone::Null {
+                        parse_quote!(
+                            if #obj.#fld_id.is_empty() {
+                                #err;
+                            }
+                        )
+                    } else {
+                        parse_quote!(
+                            match #obj.#fld_id.as_ref() {
+                                Some(val) if val.is_empty() => #err,
+                                _ => (),
+                            }
+                        )
+                    }
+                } else if f.dbt.text() {
                     if f.onn == OnNone::Null {
                         parse_quote!(
                             if ::ljumvall_utils::blacken(&#obj.#fld_id)
                                 .is_empty()
                             {
-                                Err(#err)
-                            } else {
-                                Ok(())
+                                #err;
                             }
                         )
                     } else {
                         parse_quote!(
                             match #obj.#fld_id.as_ref() {
                                 Some(val) if ::ljumvall_utils::blacken(val)
-                                    .is_empty() => Err(#err),
-                                _ => Ok(()),
+                                    .is_empty() => #err,
+                                _ => (),
                             }
                         )
                     }
@@ -678,32 +983,62 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                     if f.onn == OnNone::Null {
                         parse_quote!(
                             if #obj.#fld_id == 0 {
-                                Err(#err)
-                            } else {
-                                Ok(())
+                                #err;
                             }
                         )
                     } else {
                         parse_quote!(
                             match #obj.#fld_id {
-                                Some(val) if val == 0 => Err(#err),
-                                None => Ok(()),
+                                Some(val) if val == 0 => #err,
+                                _ => (),
                             }
                         )
                     }
                 }
             })
             .collect();
-        if self.before_save {
-            parse_quote!({
-            #(  #req_chk?; )*
-                ::vicocomo::BeforeSave::before_save(#obj, db.clone())?;
-            })
+        let vld_chk: Vec<Expr> = self
+            .fields
+            .iter()
+            .filter_map(|f| Validation::field_expr(f, &obj))
+            .collect();
+        let validate_expr: Expr = if self.validate {
+            parse_quote!(::vicocomo::Validate::validate(
+                &*#obj,
+                db.clone(),
+                &mut errors,
+            ))
         } else {
-            parse_quote!({
-            #(  #req_chk?; )*
-            })
+            parse_quote!(())
+        };
+        let before_save_expr: Expr = if self.before_save {
+            parse_quote!(
+                ::vicocomo::BeforeSave::before_save(#obj, db.clone())?
+            )
+        } else {
+            parse_quote!(())
+        };
+        if req_chk.is_empty() && vld_chk.is_empty() && !self.validate {
+            return parse_quote!({ #before_save_expr; });
         }
+        let struct_lit =
+            LitStr::new(&self.struct_id.to_string(), Span::call_site());
+        parse_quote!({
+            let mut errors = ::vicocomo::ModelError {
+                error: ::vicocomo::ModelErrorKind::CannotSave,
+                model: #struct_lit.to_string(),
+                general: None,
+                field_errors: Vec::new(),
+                assoc_errors: Vec::new(),
+            };
+        #(  #req_chk; )*
+        #(  #vld_chk; )*
+            #validate_expr;
+            if errors.has_errors() {
+                return Err(::vicocomo::Error::Model(errors));
+            }
+            #before_save_expr;
+        })
     }
 
     pub(crate) fn belongs_to_fields(&self) -> Vec<&Field> {
@@ -718,7 +1053,8 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
         self.fields.iter().map(|f| f.dbt.path()).collect()
     }
 
-    pub(crate) fn default_order(&self) -> String {
+    // qualifier is e.g. "this." or ""
+    pub(crate) fn default_order(&self, qualifier: &str) -> String {
         if self.order_fields().is_empty() {
             String::new()
         } else {
@@ -728,7 +1064,8 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                     .iter()
                     .map(|f| {
                         format!(
-                            "{} {}",
+                            "{}{} {}",
+                            qualifier,
                             f.col.value(),
                             match f.ord.as_ref().unwrap() {
                                 Order::Asc(_) => "ASC",
@@ -764,9 +1101,14 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
     }
 
     // SELECT col1, col2, col3 FROM table WHERE col1 = $1 AND col3 = $2
-    pub(crate) fn find_sql(&self, uni_cols: &[String]) -> String {
+    // [ AND deleted IS NULL ] unless with_deleted
+    pub(crate) fn find_sql(
+        &self,
+        uni_cols: &[String],
+        with_deleted: bool,
+    ) -> String {
         format!(
-            "SELECT {} FROM {} WHERE {}",
+            "SELECT {} FROM {} WHERE {}{}",
             &self
                 .fields
                 .iter()
@@ -780,9 +1122,53 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                 .map(|(ix, col)| format!("{} = ${}", col, ix + 1))
                 .collect::<Vec<_>>()
                 .join(" AND "),
+            if with_deleted {
+                String::new()
+            } else {
+                self.not_deleted_sql(" AND ", "")
+            },
         )
     }
 
+    pub(crate) fn lock_field(&self) -> Option<&Field> {
+        self.fields.iter().find(|f| f.lck)
+    }
+
+    // An expression constructing a model instance from local variables named
+    // as the fields, setting the vicocomo_original field if any.
+    //
+    pub(crate) fn new_model_expr(&self, ids: &[&Ident]) -> Expr {
+        match self.original.as_ref() {
+            Some(orig) => {
+                let remember = self.remember_original_expr(parse_quote!(obj));
+                parse_quote!({
+                    let mut obj = Self {
+                        #( #ids, )*
+                        #orig: ::vicocomo::Original::default(),
+                    };
+                    #remember;
+                    obj
+                })
+            }
+            None => parse_quote!(Self { #( #ids ),* }),
+        }
+    }
+
+    // "<prefix><qualifier>deleted IS NULL" if there is a soft delete field,
+    // else an empty string.
+    pub(crate) fn not_deleted_sql(
+        &self,
+        prefix: &str,
+        qualifier: &str,
+    ) -> String {
+        match self.soft_delete_field() {
+            Some(sdl) => {
+                format!("{}{}{} IS NULL", prefix, qualifier, sdl.col.value())
+            }
+            None => String::new(),
+        }
+    }
+
     pub(crate) fn order_fields(&self) -> Vec<&Field> {
         let mut result = self
             .fields
@@ -880,10 +1266,25 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
         self.value(self.pk_fields().as_slice(), obj)
     }
 
+    // An expression setting the vicocomo_original field of obj to the
+    // present column values, noop if there is no such field.
+    //
+    pub(crate) fn remember_original_expr(&self, obj: Expr) -> Expr {
+        match self.original.as_ref() {
+            Some(orig) => parse_quote!(
+                #obj.#orig = ::vicocomo::Original::new(
+                    #obj.__vicocomo__column_values(),
+                )
+            ),
+            None => parse_quote!(()),
+        }
+    }
+
     // Return three vectors to simplify converting DbValues to field values.
     // 1) the identifiers of the fields
     // 2) the values of the fields
-    // 3) JsonField wrappers of serialized fields
+    // 3) JsonField wrappers of serialized fields and NativeField wrappers of
+    //    native fields
     // fields are the fields of interest
     pub(crate) fn row_to_value_expr<'a>(
         &'a self,
@@ -901,6 +1302,8 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
             });
             wraps.push(if f.ser {
                 parse_quote!(JsonField(vicocomo_local_val))
+            } else if f.nat {
+                parse_quote!(::vicocomo::NativeField(vicocomo_local_val))
             } else {
                 parse_quote!(vicocomo_local_val)
             });
@@ -912,6 +1315,7 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
     // returns Result<Vec<struct_id>, Error>
     pub(crate) fn rows_to_models_expr(&self, rows: Expr) -> Expr {
         let (ids, vals, wraps) = self.row_to_value_expr(self.fields());
+        let new_model = self.new_model_expr(ids.as_slice());
         parse_quote!({
             use ::vicocomo::JsonField;
             use ::std::convert::TryInto;
@@ -935,7 +1339,7 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
                         },
                     }
                 )*
-                models.push(Self { #( #ids ),* });
+                models.push(#new_model);
             }
             match error {
                 Some(err) => Err(err),
@@ -944,6 +1348,45 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
         })
     }
 
+    // row should be a Vec<DbValue>
+    // returns Result<struct_id, Error>
+    pub(crate) fn row_to_model_expr(&self, row: Expr) -> Expr {
+        let (ids, vals, wraps) = self.row_to_value_expr(self.fields());
+        let new_model = self.new_model_expr(ids.as_slice());
+        parse_quote!({
+            use ::vicocomo::JsonField;
+            use ::std::convert::TryInto;
+
+            let mut row: Vec<::vicocomo::DbValue> = #row;
+            let mut values = row.drain(..);
+            (|| -> Result<Self, ::vicocomo::Error> {
+                #(
+                    let #ids = match values.next().unwrap().try_into()? {
+                        #wraps => #vals,
+                    };
+                )*
+                Ok(#new_model)
+            })()
+        })
+    }
+
+    pub(crate) fn soft_delete_field(&self) -> Option<&Field> {
+        self.fields.iter().find(|f| f.sdl)
+    }
+
+    // The CreatedAt and UpdatedAt fields if inserting, else the UpdatedAt
+    // fields.
+    pub(crate) fn timestamp_fields(&self, inserting: bool) -> Vec<&Field> {
+        self.fields
+            .iter()
+            .filter(|f| match f.tsp {
+                Some(Timestamp::CreatedAt) => inserting,
+                Some(Timestamp::UpdatedAt) => true,
+                None => false,
+            })
+            .collect()
+    }
+
     pub(crate) fn upd_db_types(&self) -> Vec<Path> {
         self.fields
             .iter()
@@ -1199,33 +1642,77 @@ _ => panic!("{}", ATTR_BELONGS_TO_ERROR),
 // Mirror vicocomo::DbType
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum DbType {
+    Blob,
+    Bool,
+    Date,
+    DateTime,
     Float,
     Int,
     Text,
+    Time,
+    NulBlob,
+    NulBool,
+    NulDate,
+    NulDateTime,
     NulFloat,
     NulInt,
     NulText,
+    NulTime,
 }
 
 impl DbType {
+    pub(crate) fn blob(&self) -> bool {
+        matches!(self, Self::Blob | Self::NulBlob)
+    }
+
     pub(crate) fn path(&self) -> Path {
         match self {
+            Self::Blob => parse_quote!(::vicocomo::DbType::Blob),
+            Self::Bool => parse_quote!(::vicocomo::DbType::Bool),
+            Self::Date => parse_quote!(::vicocomo::DbType::Date),
+            Self::DateTime => parse_quote!(::vicocomo::DbType::DateTime),
             Self::Float => parse_quote!(::vicocomo::DbType::Float),
             Self::Int => parse_quote!(::vicocomo::DbType::Int),
             Self::Text => parse_quote!(::vicocomo::DbType::Text),
+            Self::Time => parse_quote!(::vicocomo::DbType::Time),
+            Self::NulBlob => parse_quote!(::vicocomo::DbType::NulBlob),
+            Self::NulBool => parse_quote!(::vicocomo::DbType::NulBool),
+            Self::NulDate => parse_quote!(::vicocomo::DbType::NulDate),
+            Self::NulDateTime => {
+                parse_quote!(::vicocomo::DbType::NulDateTime)
+            }
             Self::NulFloat => parse_quote!(::vicocomo::DbType::NulFloat),
             Self::NulInt => parse_quote!(::vicocomo::DbType::NulInt),
             Self::NulText => parse_quote!(::vicocomo::DbType::NulText),
+            Self::NulTime => parse_quote!(::vicocomo::DbType::NulTime),
         }
     }
 
+    pub(crate) fn float(&self) -> bool {
+        matches!(self, Self::Float | Self::NulFloat)
+    }
+
     pub(crate) fn nul(&self) -> bool {
         match self {
-            Self::NulFloat | Self::NulInt | Self::NulText => true,
+            Self::NulBlob
+            | Self::NulBool
+            | Self::NulDate
+            | Self::NulDateTime
+            | Self::NulFloat
+            | Self::NulInt
+            | Self::NulText
+            | Self::NulTime => true,
             _ => false,
         }
     }
 
+    pub(crate) fn numeric(&self) -> bool {
+        matches!(
+            self,
+            Self::Float | Self::Int | Self::NulFloat | Self::NulInt
+        )
+    }
+
     pub(crate) fn text(&self) -> bool {
         match self {
             Self::Text | Self::NulText => true,
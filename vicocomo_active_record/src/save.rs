@@ -1,5 +1,5 @@
-use crate::model::{Field, Model, OnNone};
-use ::syn::{parse_quote, Expr, ItemFn};
+use crate::model::{Field, Model, OnNone, Timestamp, UniqueFieldSet};
+use ::syn::{parse_quote, Expr, ItemFn, LitStr, Stmt};
 
 pub(crate) fn save_impl(
     model: &Model,
@@ -9,14 +9,19 @@ pub(crate) fn save_impl(
     use ::quote::format_ident;
 
     let Model {
-        struct_id: _,
+        ref struct_id,
         ref table_name,
         has_many: _,
+        after_delete: _,
+        after_save,
         before_delete: _,
         before_save: _,
+        delete_batch_hooks: _,
         readonly,
+        validate: _,
+        ref original,
         ref fields,
-        uniques: _,
+        ref uniques,
     } = model;
     let fields = fields.iter().collect::<Vec<_>>();
 
@@ -39,6 +44,13 @@ pub(crate) fn save_impl(
     let before_insert_expr =
         model.before_save_expr(format_ident!("data_itm"));
 
+    let after_insert_expr = model.after_save_expr(parse_quote!(itm));
+
+    let touch_insert_expr = touch_expr(
+        model.timestamp_fields(true).as_slice(),
+        parse_quote!(data_itm),
+    );
+
     #[allow(non_snake_case)]
     let push_expr__data_itm__none__insert_cols__itm_pars = push_expr(
         fields.as_slice(),
@@ -56,6 +68,36 @@ pub(crate) fn save_impl(
 
     let before_update_expr = model.before_save_expr(format_ident!("self"));
 
+    let after_update_expr = model.after_save_expr(parse_quote!(self));
+
+    let touch_update_expr = touch_expr(
+        model.timestamp_fields(false).as_slice(),
+        parse_quote!(self),
+    );
+
+    // update_columns() sets the UpdatedAt fields unless given in upd_cols
+    let push_timestamps_expr: Expr =
+        if model.timestamp_fields(false).is_empty() {
+            parse_quote!(())
+        } else {
+            let ts_fields = model.timestamp_fields(false);
+            let ts_cols = ts_fields.iter().map(|f| &f.col);
+            let push_ts = push_expr(
+                ts_fields.as_slice(),
+                parse_quote!(self),
+                Some(parse_quote!(par_ix)),
+                parse_quote!(upd_col_sql),
+                parse_quote!(upd_pars),
+            );
+            parse_quote!(if #touch_update_expr {
+            #(
+                if !upd_cols.iter().any(|(col, _)| *col == #ts_cols) {
+                    #push_ts
+                }
+            )*
+            })
+        };
+
     let upd_fields = model.upd_fields();
     let upd_fmt = format!(
         "UPDATE {} SET {{}} WHERE {{}} RETURNING {}",
@@ -66,9 +108,32 @@ pub(crate) fn save_impl(
             .collect::<Vec<_>>()
             .join(", "),
     );
-    let pk_select = model.pk_select();
     let pk_db_values = model.pk_db_values();
 
+    // with a vicocomo_lock_version field, update only if the version is
+    // unchanged, and increment it
+    let (upd_where, push_lock_par, lock_set): (LitStr, Expr, Expr) =
+        match model.lock_field() {
+            Some(lck) => {
+                let lck_id = &lck.id;
+                let lck_col = lck.col.value();
+                (
+                    LitStr::new(
+                        &format!(
+                            "{} AND {} = ${}",
+                            model.pk_select().value(),
+                            lck_col,
+                            model.pk_fields().len() + 1,
+                        ),
+                        ::proc_macro2::Span::call_site(),
+                    ),
+                    parse_quote!(upd_pars.push(self.#lck_id.clone().into())),
+                    parse_quote!(Some(format!("{0} = {0} + 1", #lck_col))),
+                )
+            }
+            None => (model.pk_select(), parse_quote!(()), parse_quote!(None)),
+        };
+
     let return_if_self_has_no_primary_key_expr: Expr =
         parse_quote!(if self.pk_value().is_none() {
             return Err(Self::__vicocomo__pk_error(
@@ -80,13 +145,61 @@ pub(crate) fn save_impl(
 
     #[allow(non_snake_case)]
     let push_expr__self__par_ix__upd_cols__upd_pars = push_expr(
-        upd_fields.as_slice(),
+        upd_fields
+            .iter()
+            .filter(|f| !f.lck)
+            .cloned()
+            .collect::<Vec<_>>()
+            .as_slice(),
         parse_quote!(self),
         Some(parse_quote!(par_ix)),
         parse_quote!(upd_cols),
         parse_quote!(upd_pars),
     );
 
+    // with a vicocomo_original field, update only the changed columns, and
+    // nothing at all if none of them changed
+    let (return_if_unchanged_expr, push_upd_exprs): (Expr, Vec<Expr>) =
+        match original {
+            Some(_) => {
+                let upd_cols = upd_fields
+                    .iter()
+                    .filter(|f| !f.lck)
+                    .map(|f| f.col.value())
+                    .collect::<Vec<_>>();
+                (
+                    parse_quote!(
+                        if !self.changed_columns().iter().any(|col| {
+                            [ #( #upd_cols ),* ].contains(&col.as_str())
+                        }) {
+                            return Ok(());
+                        }
+                    ),
+                    push_expr__self__par_ix__upd_cols__upd_pars
+                        .iter()
+                        .zip(upd_cols.iter())
+                        .map(|(push, col)| {
+                            parse_quote!(
+                                if changed.iter().any(|c| c == #col) {
+                                    #push
+                                }
+                            )
+                        })
+                        .collect(),
+                )
+            }
+            None => (
+                parse_quote!(()),
+                push_expr__self__par_ix__upd_cols__upd_pars,
+            ),
+        };
+    let changed_stmt: Option<Stmt> = original
+        .as_ref()
+        .map(|_| parse_quote!(let changed = self.changed_columns();));
+
+    let remember_original_expr =
+        model.remember_original_expr(parse_quote!(self));
+
     let upd_db_types = model.upd_db_types();
 
     if *readonly {
@@ -100,69 +213,77 @@ pub(crate) fn save_impl(
         ));
     } else {
         #[allow(non_snake_case)]
-        trait_fn.push(parse_quote!(
-            fn insert_batch(
-                db: ::vicocomo::DatabaseIf,
-                data: &mut [Self],
-            ) -> Result<Vec<Self>, ::vicocomo::Error> {
-                use ::vicocomo::JsonField;
-                let mut inserts: std::collections::HashMap<
-                    Vec<String>,
-                    Vec<Vec<::vicocomo::DbValue>>,
-                > = std::collections::HashMap::new();
-                for data_itm in data.iter_mut() {
-                    let mut insert_cols = Vec::new();
-                    let mut itm_pars: Vec<::vicocomo::DbValue> = Vec::new();
-                    #before_insert_expr;
-                    #( #push_expr__data_itm__none__insert_cols__itm_pars )*
-                    match inserts.get_mut(&insert_cols) {
-                        Some(ins_pars) => ins_pars.push(itm_pars),
-                        None => {
-                            inserts.insert(insert_cols, vec![itm_pars]);
-                        },
-                    }
+        let insert_body: Expr = parse_quote!({
+            let mut inserts: std::collections::HashMap<
+                Vec<String>,
+                Vec<Vec<::vicocomo::DbValue>>,
+            > = std::collections::HashMap::new();
+            for data_itm in data.iter_mut() {
+                let mut insert_cols = Vec::new();
+                let mut itm_pars: Vec<::vicocomo::DbValue> = Vec::new();
+                #before_insert_expr;
+                #touch_insert_expr;
+                #( #push_expr__data_itm__none__insert_cols__itm_pars )*
+                match inserts.get_mut(&insert_cols) {
+                    Some(ins_pars) => ins_pars.push(itm_pars),
+                    None => {
+                        inserts.insert(insert_cols, vec![itm_pars]);
+                    },
                 }
-                let mut error = None;
-                let mut result = Vec::new();
-                for (ins_cols, ins_pars) in inserts.iter() {
-                    let mut db_pars = Vec::new();
-                    for these_pars in ins_pars.iter() {
-                        db_pars.extend(these_pars.clone().drain(..));
+            }
+            let mut error = None;
+            let mut result = Vec::new();
+            for (ins_cols, ins_pars) in inserts.iter() {
+                let mut db_pars = Vec::new();
+                for these_pars in ins_pars.iter() {
+                    db_pars.extend(these_pars.clone().drain(..));
+                }
+                match db.clone().query(
+                    &format!(
+                        #ins_fmt,
+                        &ins_cols.join(", "),
+                        #ins_placeholders,
+                    ),
+                    &db_pars,
+                    &[ #( #db_types ),* ],
+                ) {
+                    Ok(rows) => {
+                        result.extend(#rows_to_models_expr__rows?);
                     }
-                    match db.clone().query(
-                        &format!(
-                            #ins_fmt,
-                            &ins_cols.join(", "),
-                            #ins_placeholders,
-                        ),
-                        &db_pars,
-                        &[ #( #db_types ),* ],
-                    ) {
-                        Ok(rows) => {
-                            result.extend(#rows_to_models_expr__rows?);
-                        }
-                        Err(err) => {
-                            error = Some(err);
-                            break;
-                        }
+                    Err(err) => {
+                        error = Some(err);
+                        break;
                     }
                 }
-                if let Some(err) = error {
-                    for data_itm in data {
-                        if let Some(mapped) =
-                            data_itm.__vicocomo__conv_save_error(
-                                db.clone(),
-                                &err,
-                                false
-                            )
-                        {
-                            return Err(mapped);
-                        }
+            }
+            if let Some(err) = error {
+                for data_itm in data {
+                    if let Some(mapped) =
+                        data_itm.__vicocomo__conv_save_error(
+                            db.clone(),
+                            &err,
+                            false
+                        )
+                    {
+                        return Err(mapped);
                     }
-                    Err(err)
-                } else {
-                    Ok(result)
                 }
+                Err(err)
+            } else {
+                for itm in result.iter_mut() {
+                    #after_insert_expr;
+                }
+                Ok(result)
+            }
+        });
+        let insert_body = Model::transaction_expr(*after_save, insert_body);
+        trait_fn.push(parse_quote!(
+            fn insert_batch(
+                db: ::vicocomo::DatabaseIf,
+                data: &mut [Self],
+            ) -> Result<Vec<Self>, ::vicocomo::Error> {
+                use ::vicocomo::JsonField;
+                #insert_body
             }
         ));
     }
@@ -214,12 +335,12 @@ pub(crate) fn save_impl(
                                 Err(err) => return(Err(err)),
                             }
                         )*
+                            #remember_original_expr;
                             Ok(())
                         } else {
-                            Err(Self::__vicocomo__pk_error(
+                            Err(self.__vicocomo__missing_error(
+                                db,
                                 ::vicocomo::ModelErrorKind::CannotSave,
-                                ::vicocomo::ActiveRecord::pk_value(self),
-                                true,
                             ))
                         }
                     })
@@ -227,6 +348,44 @@ pub(crate) fn save_impl(
         ));
 
         #[allow(non_snake_case)]
+        let update_body: Expr = parse_quote!({
+            #return_if_self_has_no_primary_key_expr
+            let mut upd_cols: Vec<String> = Vec::new();
+            let mut upd_pars = #pk_db_values;
+            #push_lock_par;
+            let mut par_ix = upd_pars.len();
+            #before_update_expr;
+            #return_if_unchanged_expr;
+            #touch_update_expr;
+            #changed_stmt
+            #( #push_upd_exprs )*
+            upd_cols.extend(#lock_set);
+            self.__vicocomo__handle_update_result(
+                db.clone(),
+                db.clone().query(
+                    &format!(
+                        #upd_fmt,
+                        &upd_cols.join(", "),
+                        #upd_where,
+                    ),
+                    &upd_pars,
+                    &[ #( #upd_db_types ),* ],
+                )
+                .map_err(|err| {
+                    match self.__vicocomo__conv_save_error(
+                        db.clone(),
+                        &err,
+                        true,
+                    ) {
+                        Some(mapped) => mapped,
+                        None => err,
+                    }
+                }),
+            )?;
+            #after_update_expr;
+            Ok(())
+        });
+        let update_body = Model::transaction_expr(*after_save, update_body);
         trait_fn.push(parse_quote!(
             fn update(&mut self, db: ::vicocomo::DatabaseIf)
                 -> Result<(), ::vicocomo::Error>
@@ -234,34 +393,7 @@ pub(crate) fn save_impl(
                 use ::std::convert::TryInto;
                 use ::vicocomo::JsonField;
 
-                #return_if_self_has_no_primary_key_expr
-                let mut upd_cols: Vec<String> = Vec::new();
-                let mut upd_pars = #pk_db_values;
-                let mut par_ix = upd_pars.len();
-                #before_update_expr;
-                #( #push_expr__self__par_ix__upd_cols__upd_pars )*
-                self.__vicocomo__handle_update_result(
-                    db.clone(),
-                    db.clone().query(
-                        &format!(
-                            #upd_fmt,
-                            &upd_cols.join(", "),
-                            #pk_select,
-                        ),
-                        &upd_pars,
-                        &[ #( #upd_db_types ),* ],
-                    )
-                    .map_err(|err| {
-                        match self.__vicocomo__conv_save_error(
-                            db.clone(),
-                            &err,
-                            true,
-                        ) {
-                            Some(mapped) => mapped,
-                            None => err,
-                        }
-                    }),
-                )
+                #update_body
             }
         ));
 
@@ -276,19 +408,22 @@ pub(crate) fn save_impl(
                 #return_if_self_has_no_primary_key_expr
                 let mut upd_col_sql: Vec<String> = Vec::new();
                 let mut upd_pars = #pk_db_values;
+                #push_lock_par;
                 let mut par_ix = upd_pars.len();
                 for (col, dbv) in upd_cols {
                     par_ix += 1;
                     upd_col_sql.push(format!("{} = ${}", col, par_ix));
                     upd_pars.push(dbv.clone());
                 };
+                #push_timestamps_expr;
+                upd_col_sql.extend(#lock_set);
                 self.__vicocomo__handle_update_result(
                     db.clone(),
                     db.clone().query(
                         &format!(
                             #upd_fmt,
                             &upd_col_sql.join(", "),
-                            #pk_select,
+                            #upd_where,
                         ),
                         &upd_pars,
                         &[ #( #upd_db_types ),* ],
@@ -297,10 +432,302 @@ pub(crate) fn save_impl(
             }
         ));
     }
+
+    // --- change tracking --------------------------------------------------
+
+    if let Some(orig) = original {
+        let cols = model.cols();
+        let values = fields.iter().map(|f| -> Expr {
+            let id = &f.id;
+            let wrap = |val: Expr| -> Expr {
+                if f.ser {
+                    parse_quote!(::vicocomo::JsonField(#val.clone()))
+                } else if f.nat {
+                    parse_quote!(::vicocomo::NativeField(#val.clone()))
+                } else {
+                    parse_quote!(#val.clone())
+                }
+            };
+            if f.onn == OnNone::Null {
+                let val = wrap(parse_quote!(self.#id));
+                parse_quote!(Some(#val.into()))
+            } else {
+                let val = wrap(parse_quote!(val));
+                parse_quote!(self.#id.as_ref().map(|val| #val.into()))
+            }
+        });
+        struct_fn.push(parse_quote!(
+            #[doc(hidden)]
+            fn __vicocomo__column_values(
+                &self,
+            ) -> Vec<Option<::vicocomo::DbValue>> {
+                vec![ #( #values ),* ]
+            }
+        ));
+        struct_fn.push(parse_quote!(
+            pub fn changed_columns(&self) -> Vec<String> {
+                let current = self.__vicocomo__column_values();
+                let original = self.#orig.values();
+                [ #( #cols ),* ]
+                    .iter()
+                    .enumerate()
+                    .filter(|(ix, _)| match (&current[*ix], original) {
+                        (None, _) => false,
+                        (Some(_), None) => true,
+                        (val, Some(orig)) => orig[*ix] != *val,
+                    })
+                    .map(|(_, col)| col.to_string())
+                    .collect()
+            }
+        ));
+    }
+
+    // --- upsert by unique fields -------------------------------------------
+
+    for unique in uniques {
+        let upsert_by_id = &unique.upsert_by_id;
+        let upsert_batch_by_id = &unique.upsert_batch_by_id;
+        struct_fn.push(parse_quote!(
+            pub fn #upsert_by_id(
+                &mut self,
+                db: ::vicocomo::DatabaseIf,
+            ) -> Result<(), ::vicocomo::Error> {
+                Self::#upsert_batch_by_id(db, ::std::slice::from_mut(self))
+            }
+        ));
+        if *readonly {
+            struct_fn.push(parse_quote!(
+                pub fn #upsert_batch_by_id(
+                    _db: ::vicocomo::DatabaseIf,
+                    _data: &mut [Self],
+                ) -> Result<(), ::vicocomo::Error> {
+                    Err(::vicocomo::Error::other("not-available"))
+                }
+            ));
+            continue;
+        }
+        let uni_cols = unique
+            .fields
+            .iter()
+            .map(|f| f.col.value())
+            .collect::<Vec<_>>();
+        let keep_cols = model
+            .pk_fields()
+            .iter()
+            .map(|f| f.col.value())
+            .chain(uni_cols.iter().cloned())
+            .chain(model.lock_field().map(|f| f.col.value()))
+            .chain(
+                model
+                    .fields
+                    .iter()
+                    .filter(|f| f.tsp == Some(Timestamp::CreatedAt))
+                    .map(|f| f.col.value()),
+            )
+            .collect::<Vec<_>>();
+        // with a vicocomo_lock_version field, update only if the version is
+        // unchanged, and increment it
+        let (upsert_lock_set, upsert_where, no_row_err): (
+            Expr,
+            String,
+            Expr,
+        ) = match model.lock_field() {
+            Some(lck) => {
+                let struct_lit = LitStr::new(
+                    &struct_id.to_string(),
+                    ::proc_macro2::Span::call_site(),
+                );
+                let lck_set = format!(
+                    "{0} = {1}.{0} + 1",
+                    lck.col.value(),
+                    table_name,
+                );
+                (
+                    parse_quote!(set.push(#lck_set.to_string())),
+                    format!(
+                        " WHERE {1}.{0} = excluded.{0}",
+                        lck.col.value(),
+                        table_name,
+                    ),
+                    parse_quote!(::vicocomo::Error::Model(
+                        ::vicocomo::ModelError {
+                            error: ::vicocomo::ModelErrorKind
+                                ::StaleObject,
+                            model: #struct_lit.to_string(),
+                            general: None,
+                            field_errors: Vec::new(),
+                            assoc_errors: Vec::new(),
+                        }
+                    )),
+                )
+            }
+            None => (
+                parse_quote!(()),
+                String::new(),
+                parse_quote!(::vicocomo::Error::this_cannot_happen("upsert")),
+            ),
+        };
+        let upsert_fmt = format!(
+            "INSERT INTO {} ({{}}) VALUES {{}} ON CONFLICT ({}) \
+            DO UPDATE SET {{}}{} RETURNING {}",
+            table_name,
+            uni_cols.join(", "),
+            upsert_where,
+            &model.cols().join(", "),
+        );
+        let noop_set = format!("{} = excluded.{}", uni_cols[0], uni_cols[0]);
+        let data_itm_key = unique_key_expr(struct_id, unique, "data_itm");
+        let upsert_placeholders = Model::placeholders_expr(
+            parse_quote!(1),
+            parse_quote!(ins_cols.len()),
+        );
+        let row_model = model.row_to_model_expr(parse_quote!(row));
+        #[allow(non_snake_case)]
+        let upsert_body: Expr = parse_quote!({
+            let mut upserts: Vec<(Vec<String>, Vec<::vicocomo::DbValue>)> =
+                Vec::new();
+            let mut keys = std::collections::HashSet::new();
+            for data_itm in data.iter_mut() {
+                let mut insert_cols = Vec::new();
+                let mut itm_pars: Vec<::vicocomo::DbValue> = Vec::new();
+                #before_insert_expr;
+                #touch_insert_expr;
+                #( #push_expr__data_itm__none__insert_cols__itm_pars )*
+                if !keys.insert(#data_itm_key) {
+                    return Err(::vicocomo::Error::invalid_input(
+                        "active-record--upsert--duplicate-key",
+                    ));
+                }
+                upserts.push((insert_cols, itm_pars));
+            }
+            // one statement for each object, so that the returned row is
+            // known to belong to it
+            for (ix, (ins_cols, ins_pars)) in upserts.iter().enumerate() {
+                let mut set = ins_cols
+                    .iter()
+                    .filter(|col| {
+                        ![ #( #keep_cols ),* ].contains(&col.as_str())
+                    })
+                    .map(|col| format!("{} = excluded.{}", col, col))
+                    .collect::<Vec<_>>();
+                #upsert_lock_set;
+                if set.is_empty() {
+                    // DO NOTHING would not return the existing row
+                    set.push(#noop_set.to_string());
+                }
+                let mut rows = match db.clone().query(
+                    &format!(
+                        #upsert_fmt,
+                        &ins_cols.join(", "),
+                        #upsert_placeholders,
+                        set.join(", "),
+                    ),
+                    ins_pars,
+                    &[ #( #db_types ),* ],
+                ) {
+                    Ok(rows) => rows,
+                    Err(err) => {
+                        return Err(
+                            match data[ix].__vicocomo__conv_save_error(
+                                db.clone(),
+                                &err,
+                                false,
+                            ) {
+                                Some(mapped) => mapped,
+                                None => err,
+                            },
+                        );
+                    }
+                };
+                match rows.pop() {
+                    Some(row) if rows.is_empty() => data[ix] = #row_model?,
+                    None => return Err(#no_row_err),
+                    _ => {
+                        return Err(::vicocomo::Error::this_cannot_happen(
+                            "upsert",
+                        ))
+                    }
+                }
+            }
+            for itm in data.iter_mut() {
+                #after_insert_expr;
+            }
+            Ok(())
+        });
+        // several statements, so always in a transaction
+        let upsert_body = Model::transaction_expr(true, upsert_body);
+        struct_fn.push(parse_quote!(
+            pub fn #upsert_batch_by_id(
+                db: ::vicocomo::DatabaseIf,
+                data: &mut [Self],
+            ) -> Result<(), ::vicocomo::Error> {
+                use ::vicocomo::JsonField;
+                #upsert_body
+            }
+        ));
+    }
 }
 
 // --- private ---------------------------------------------------------------
 
+// An expression evaluating to a String that identifies the values of the
+// unique fields of obj, returning an error if an optional field is None.
+//
+fn unique_key_expr(
+    struct_id: &::syn::Ident,
+    unique: &UniqueFieldSet,
+    obj: &str,
+) -> Expr {
+    let obj = ::quote::format_ident!("{}", obj);
+    let values = unique
+        .fields
+        .iter()
+        .map(|f| -> Expr {
+            let fld = &f.id;
+            if f.onn == OnNone::Null {
+                parse_quote!(#obj.#fld.clone())
+            } else {
+                let none_err = Model::field_none_err_expr(struct_id, fld);
+                parse_quote!(
+                    match #obj.#fld.as_ref() {
+                        Some(val) => val.clone(),
+                        None => return Err(#none_err),
+                    }
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+    parse_quote!({
+        let values: Vec<::vicocomo::DbValue> = vec![ #( #values.into() ),* ];
+        values
+            .iter()
+            .map(|v| v.sql_value())
+            .collect::<Vec<_>>()
+            .join(", ")
+    })
+}
+
+// Set the timestamp fields of obj to the present time according to the
+// clock of the local variable db. Evaluates to true iff there is a clock,
+// see DatabaseIf::without_timestamps().
+//
+fn touch_expr(fields: &[&Field], obj: Expr) -> Expr {
+    if fields.is_empty() {
+        return parse_quote!(false);
+    }
+    let ids = fields.iter().map(|f| &f.id);
+    let vals = fields
+        .iter()
+        .map(|f| Model::timestamp_value_expr(f, parse_quote!(now)));
+    parse_quote!(match db.timestamp() {
+        Some(now) => {
+            #( #obj.#ids = #vals; )*
+            true
+        }
+        None => false,
+    })
+}
+
 // Push to cols (String) and vals (DbValue) data for fields.
 //
 // If a field is vicocomo_optional, noop if None, the contained data is pushed
@@ -340,6 +767,8 @@ fn push_expr(
                 OnNone::Ignore => {
                     let value: Expr = if f.ser {
                         parse_quote!(JsonField(val.clone()))
+                    } else if f.nat {
+                        parse_quote!(::vicocomo::NativeField(val.clone()))
                     } else {
                         parse_quote!(val.clone())
                     };
@@ -357,6 +786,10 @@ fn push_expr(
                 OnNone::Null => {
                     let value: Expr = if f.ser {
                         parse_quote!(JsonField(#obj.#fld.clone()))
+                    } else if f.nat {
+                        parse_quote!(::vicocomo::NativeField(
+                            #obj.#fld.clone()
+                        ))
                     } else {
                         parse_quote!(#obj.#fld.clone())
                     };
@@ -0,0 +1,229 @@
+use crate::model::{DbType, Field, Model, OnDelete, OnNone};
+use proc_macro2::Span;
+use syn::{parse_quote, Expr, ItemFn, LitBool, LitStr, Path};
+
+pub(crate) fn schema_impl(model: &Model, trait_fn: &mut Vec<ItemFn>) {
+    let table = LitStr::new(&model.table_name, Span::call_site());
+    let pk_fields = model.pk_fields();
+    let auto_pk = pk_fields.len() == 1
+        && pk_fields[0].onn == OnNone::Ignore
+        && pk_fields[0].dbt == DbType::Int;
+    let pk_col: Vec<LitStr> =
+        pk_fields.iter().map(|f| f.col.clone()).collect();
+    let pk_db_type: Path = pk_fields
+        .first()
+        .map(|f| f.dbt.path())
+        .unwrap_or(parse_quote!(::vicocomo::DbType::Int));
+    let pk_cols: Expr = parse_quote!(vec![ #( #pk_col.to_string() ),* ]);
+
+    // --- table_schema
+
+    let mut column: Vec<Expr> = Vec::new();
+    for fld in &model.fields {
+        let name = &fld.col;
+        let db_type = fld.dbt.path();
+        let auto = auto_pk && fld.pri;
+        let default: Expr = match default_sql(fld).filter(|_| !auto) {
+            Some(sql) => {
+                let sql = LitStr::new(sql, Span::call_site());
+                parse_quote!(Some(#sql.to_string()))
+            }
+            None => parse_quote!(None),
+        };
+        let auto = LitBool::new(auto, Span::call_site());
+        column.push(parse_quote!(
+            ::vicocomo::ColumnSchema {
+                name: #name.to_string(),
+                db_type: #db_type,
+                default: #default,
+                auto_increment: #auto,
+            }
+        ));
+    }
+    let mut uni_col: Vec<Vec<LitStr>> = model
+        .uniques
+        .iter()
+        .map(|uni| uni.fields.iter().map(|f| f.col.clone()).collect())
+        .collect();
+    uni_col.sort_by_key(|cols: &Vec<LitStr>| {
+        cols.iter().map(|c| c.value()).collect::<Vec<_>>()
+    });
+    let mut fk_col = Vec::new();
+    let mut fk_rem_type = Vec::new();
+    let mut fk_rem_col = Vec::new();
+    for fld in model.belongs_to_fields() {
+        let fk = fld.fk.as_ref().unwrap();
+        fk_col.push(fld.col.clone());
+        fk_rem_type.push(fk.remote_type.clone());
+        fk_rem_col
+            .push(LitStr::new(&fk.remote_pk.to_string(), Span::call_site()));
+    }
+    trait_fn.push(parse_quote!(
+        fn table_schema() -> ::vicocomo::TableSchema {
+            ::vicocomo::TableSchema {
+                name: #table.to_string(),
+                columns: vec![ #( #column ),* ],
+                primary_key: #pk_cols,
+                uniques: vec![ #( vec![ #( #uni_col.to_string() ),* ] ),* ],
+                foreign_keys: vec![
+                #(
+                    ::vicocomo::ForeignKeySchema {
+                        table: #table.to_string(),
+                        columns: vec![#fk_col.to_string()],
+                        remote_table:
+                            <#fk_rem_type as ::vicocomo::ActiveRecord>
+                                ::table(),
+                        remote_columns: vec![#fk_rem_col.to_string()],
+                        on_delete:
+                            <#fk_rem_type as ::vicocomo::ActiveRecord>
+                                ::referencing_keys()
+                                .into_iter()
+                                .find(|k| {
+                                    k.table == #table
+                                        && k.columns == [#fk_col]
+                                })
+                                .map(|k| k.on_delete)
+                                .unwrap_or(
+                                    ::vicocomo::ForeignKeyAction::NoAction
+                                ),
+                    },
+                )*
+                ],
+            }
+        }
+    ));
+
+    // --- referencing_keys and join_table_schemas
+
+    let mut ref_table: Vec<Expr> = Vec::new();
+    let mut ref_col = Vec::new();
+    let mut ref_on_delete: Vec<Path> = Vec::new();
+    let mut join_table = Vec::new();
+    let mut join_self_col = Vec::new();
+    let mut join_rem_col = Vec::new();
+    let mut join_rem_type = Vec::new();
+    let mut join_rem_pk_col = Vec::new();
+    for hm in &model.has_many {
+        let remote_type = &hm.remote_type;
+        let remote_fk_col = LitStr::new(&hm.remote_fk_col, Span::call_site());
+        ref_col.push(remote_fk_col.clone());
+        match &hm.many_to_many {
+            Some(mtm) => {
+                let join_lit =
+                    LitStr::new(&mtm.join_table_name, Span::call_site());
+                ref_table.push(parse_quote!(#join_lit.to_string()));
+                ref_on_delete.push(parse_quote!(
+                    ::vicocomo::ForeignKeyAction::Cascade
+                ));
+                join_table.push(join_lit);
+                join_self_col.push(remote_fk_col);
+                join_rem_col
+                    .push(LitStr::new(&mtm.join_fk_col, Span::call_site()));
+                join_rem_type.push(remote_type.clone());
+                join_rem_pk_col
+                    .push(LitStr::new(&mtm.remote_pk_col, Span::call_site()));
+            }
+            None => {
+                ref_table.push(parse_quote!(
+                    <#remote_type as ::vicocomo::ActiveRecord>::table()
+                ));
+                ref_on_delete.push(match hm.on_delete {
+                    OnDelete::Cascade => {
+                        parse_quote!(::vicocomo::ForeignKeyAction::Cascade)
+                    }
+                    OnDelete::Forget => {
+                        parse_quote!(::vicocomo::ForeignKeyAction::SetNull)
+                    }
+                    OnDelete::Restrict => {
+                        parse_quote!(::vicocomo::ForeignKeyAction::NoAction)
+                    }
+                });
+            }
+        }
+    }
+    trait_fn.push(parse_quote!(
+        fn referencing_keys() -> Vec<::vicocomo::ForeignKeySchema> {
+            vec![
+            #(
+                ::vicocomo::ForeignKeySchema {
+                    table: #ref_table,
+                    columns: vec![#ref_col.to_string()],
+                    remote_table: #table.to_string(),
+                    remote_columns: #pk_cols,
+                    on_delete: #ref_on_delete,
+                },
+            )*
+            ]
+        }
+    ));
+    trait_fn.push(parse_quote!(
+        fn join_table_schemas() -> Vec<::vicocomo::TableSchema> {
+            vec![
+            #(
+                ::vicocomo::TableSchema {
+                    name: #join_table.to_string(),
+                    columns: vec![
+                        ::vicocomo::ColumnSchema {
+                            name: #join_self_col.to_string(),
+                            db_type: #pk_db_type,
+                            default: None,
+                            auto_increment: false,
+                        },
+                        ::vicocomo::ColumnSchema {
+                            name: #join_rem_col.to_string(),
+                            db_type:
+                                <#join_rem_type as ::vicocomo::ActiveRecord>
+                                    ::col_type(#join_rem_pk_col)
+                                    .unwrap_or(::vicocomo::DbType::Int),
+                            default: None,
+                            auto_increment: false,
+                        },
+                    ],
+                    primary_key: vec![
+                        #join_self_col.to_string(),
+                        #join_rem_col.to_string(),
+                    ],
+                    uniques: Vec::new(),
+                    foreign_keys: vec![
+                        ::vicocomo::ForeignKeySchema {
+                            table: #join_table.to_string(),
+                            columns: vec![#join_self_col.to_string()],
+                            remote_table: #table.to_string(),
+                            remote_columns: #pk_cols,
+                            on_delete: ::vicocomo::ForeignKeyAction::Cascade,
+                        },
+                        ::vicocomo::ForeignKeySchema {
+                            table: #join_table.to_string(),
+                            columns: vec![#join_rem_col.to_string()],
+                            remote_table:
+                                <#join_rem_type as ::vicocomo::ActiveRecord>
+                                    ::table(),
+                            remote_columns: vec![
+                                #join_rem_pk_col.to_string()
+                            ],
+                            on_delete: ::vicocomo::ForeignKeyAction::Cascade,
+                        },
+                    ],
+                },
+            )*
+            ]
+        }
+    ));
+}
+
+// The SQL default value of a vicocomo_optional field that is not nullable,
+// the zero value of the type. None for other fields and for serialized
+// fields, since there is no known zero value.
+fn default_sql(fld: &Field) -> Option<&'static str> {
+    if fld.onn != OnNone::Ignore || fld.dbt.nul() || fld.ser {
+        return None;
+    }
+    Some(match fld.dbt {
+        DbType::Bool => "FALSE",
+        DbType::Date => "'1970-01-01'",
+        DbType::DateTime => "'1970-01-01 00:00:00'",
+        DbType::Float | DbType::Int => "0",
+        DbType::Time => "'00:00:00'",
+        _ => "''",
+    })
+}
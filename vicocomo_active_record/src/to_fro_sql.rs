@@ -35,6 +35,8 @@ pub(crate) fn to_fro_sql_impl(model: &Model, trait_fn: &mut Vec<ItemFn>) {
             };
             if fld.ser {
                 parse_quote!(::vicocomo::JsonField(#val.clone()).into())
+            } else if fld.nat {
+                parse_quote!(::vicocomo::NativeField(#val.clone()).into())
             } else {
                 parse_quote!(#val.clone().into())
             }
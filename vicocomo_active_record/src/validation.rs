@@ -0,0 +1,332 @@
+use crate::model::{DbType, Field, OnNone};
+use ::proc_macro2::Span;
+use ::syn::{
+    bracketed, parenthesized,
+    parse::{self, Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    token, Attribute, Expr, Ident, Lit, LitFloat, LitStr, Path,
+};
+
+const ATTR_VALIDATE_ERROR: &'static str =
+    "expected #[vicocomo_validate(<rule>, ...)] on a field, <rule> being one \
+    of length(min = <int>, max = <int>), range(min = <number>, \
+    max = <number>), regex = \"<pattern>\", email, one_of = [<literal>, \
+    ...], or custom = \"<path to function>\"";
+const VALIDATE_TYPE_ERROR: &'static str =
+    "#[vicocomo_validate]: length requires a text or blob field, range a \
+    numeric field, regex and email a text field, and one_of a text or \
+    numeric field, none of them serialized or native";
+
+// the pattern used by the email rule
+const EMAIL_PATTERN: &'static str = r"^[^@\s]+@[^@\s]+\.[^@\s]+$";
+
+// A rule from a vicocomo_validate( ... ) field attribute.
+#[derive(Clone, Debug)]
+pub(crate) enum Validation {
+    Custom(Path),
+    Email,
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    OneOf(Vec<NumOrStr>),
+    Range {
+        min: Option<NumOrStr>,
+        max: Option<NumOrStr>,
+    },
+    Regex(LitStr),
+}
+
+impl Validation {
+    // The rules in a vicocomo_validate attribute.
+    //
+    pub(crate) fn from_attr(attr: &Attribute) -> Vec<Self> {
+        attr.parse_args_with(
+            Punctuated::<Self, token::Comma>::parse_terminated,
+        )
+        .expect(ATTR_VALIDATE_ERROR)
+        .into_iter()
+        .collect()
+    }
+
+    // Panic unless the rule can be applied to a field with the type dbt.
+    //
+    pub(crate) fn check_type(&self, dbt: DbType, ser: bool, nat: bool) {
+        let text = dbt.text();
+        let num = dbt.numeric();
+        let ok = match self {
+            Self::Custom(_) => true,
+            _ if ser || nat => false,
+            Self::Email | Self::Regex(_) => text,
+            Self::Length { .. } => text || dbt.blob(),
+            Self::OneOf(values) => values.iter().all(|v| match &v.lit {
+                Lit::Str(_) => text,
+                Lit::Int(_) => num,
+                Lit::Float(_) => dbt.float(),
+                _ => false,
+            }),
+            Self::Range { min, max } => {
+                num && min.iter().chain(max.iter()).all(|v| match &v.lit {
+                    Lit::Int(_) => true,
+                    Lit::Float(_) => dbt.float(),
+                    _ => false,
+                })
+            }
+        };
+        assert!(ok, "{}", VALIDATE_TYPE_ERROR);
+    }
+
+    // An expression that adds an error to the local variable errors (a
+    // ModelError) for each rule of fld that obj violates. None if fld has no
+    // rules.
+    //
+    // The rules are checked only if there is a value, i.e. not for None in
+    // an Option field.
+    //
+    pub(crate) fn field_expr(fld: &Field, obj: &Ident) -> Option<Expr> {
+        if fld.vld.is_empty() {
+            return None;
+        }
+        let fld_id = &fld.id;
+        let fld_lit = LitStr::new(&fld_id.to_string(), Span::call_site());
+        let value: Expr = match (fld.onn == OnNone::Null, fld.dbt.nul()) {
+            (true, false) => parse_quote!(Some(&#obj.#fld_id)),
+            (false, true) => {
+                parse_quote!(#obj.#fld_id.as_ref().and_then(|v| v.as_ref()))
+            }
+            _ => parse_quote!(#obj.#fld_id.as_ref()),
+        };
+        let add_err = |err: &str| -> Expr {
+            parse_quote!(errors.add_field_error(#fld_lit, #err))
+        };
+        let checks = fld.vld.iter().map(|rule| -> Expr {
+            match rule {
+                Self::Custom(path) => parse_quote!(
+                    if let Err(err) = #path(val) {
+                        errors.add_field_error(#fld_lit, &err);
+                    }
+                ),
+                Self::Email => Self::regex_expr(
+                    &LitStr::new(EMAIL_PATTERN, Span::call_site()),
+                    add_err("invalid-email"),
+                ),
+                Self::Length { min, max } => {
+                    let len: Expr = if fld.dbt.blob() {
+                        parse_quote!(val.len())
+                    } else {
+                        parse_quote!(val.chars().count())
+                    };
+                    let min_chk = Self::bound_expr(
+                        min.map(|m| parse_quote!(len < #m)),
+                        add_err("too-short"),
+                    );
+                    let max_chk = Self::bound_expr(
+                        max.map(|m| parse_quote!(len > #m)),
+                        add_err("too-long"),
+                    );
+                    parse_quote!({
+                        let len = #len;
+                        #min_chk;
+                        #max_chk;
+                    })
+                }
+                Self::OneOf(values) => {
+                    let float = fld.dbt.float();
+                    let values = values.iter().map(|v| v.expr(float));
+                    let err = add_err("not-one-of");
+                    if fld.dbt.text() {
+                        parse_quote!(
+                            if ![ #( #values ),* ].contains(&val.as_str()) {
+                                #err;
+                            }
+                        )
+                    } else {
+                        parse_quote!(
+                            if ![ #( #values ),* ].contains(val) {
+                                #err;
+                            }
+                        )
+                    }
+                }
+                Self::Range { min, max } => {
+                    let float = fld.dbt.float();
+                    let min_chk = Self::bound_expr(
+                        min.as_ref().map(|m| {
+                            let m = m.expr(float);
+                            parse_quote!(*val < #m)
+                        }),
+                        add_err("too-small"),
+                    );
+                    let max_chk = Self::bound_expr(
+                        max.as_ref().map(|m| {
+                            let m = m.expr(float);
+                            parse_quote!(*val > #m)
+                        }),
+                        add_err("too-large"),
+                    );
+                    parse_quote!({
+                        #min_chk;
+                        #max_chk;
+                    })
+                }
+                Self::Regex(pattern) => {
+                    Self::regex_expr(pattern, add_err("invalid-format"))
+                }
+            }
+        });
+        Some(parse_quote!(
+            if let Some(val) = #value {
+            #(  #checks; )*
+            }
+        ))
+    }
+
+    fn bound_expr(cond: Option<Expr>, err: Expr) -> Expr {
+        match cond {
+            Some(cond) => parse_quote!(if #cond { #err; }),
+            None => parse_quote!(()),
+        }
+    }
+
+    fn min_max(
+        input: ParseStream,
+    ) -> parse::Result<(Option<NumOrStr>, Option<NumOrStr>)> {
+        let content;
+        parenthesized!(content in input);
+        let mut min = None;
+        let mut max = None;
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            content.parse::<token::Eq>()?;
+            let value: NumOrStr = content.parse()?;
+            match key.to_string().as_str() {
+                "min" => min = Some(value),
+                "max" => max = Some(value),
+                _ => {
+                    return Err(parse::Error::new(
+                        key.span(),
+                        "expected min or max",
+                    ))
+                }
+            }
+            if !content.is_empty() {
+                content.parse::<token::Comma>()?;
+            }
+        }
+        Ok((min, max))
+    }
+
+    fn regex_expr(pattern: &LitStr, err: Expr) -> Expr {
+        parse_quote!({
+            static REGEX: ::std::sync::OnceLock<::regex::Regex> =
+                ::std::sync::OnceLock::new();
+            if !REGEX
+                .get_or_init(|| ::regex::Regex::new(#pattern).unwrap())
+                .is_match(val)
+            {
+                #err;
+            }
+        })
+    }
+}
+
+impl Parse for Validation {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let rule: Ident = input.parse()?;
+        match rule.to_string().as_str() {
+            "custom" => {
+                input.parse::<token::Eq>()?;
+                Ok(Self::Custom(input.parse::<LitStr>()?.parse()?))
+            }
+            "email" => Ok(Self::Email),
+            "length" => {
+                let (min, max) = Self::min_max(input)?;
+                let to_usize = |bound: Option<NumOrStr>| match bound {
+                    Some(NumOrStr {
+                        neg: false,
+                        lit: Lit::Int(int),
+                    }) => int.base10_parse().map(Some),
+                    Some(NumOrStr { lit, .. }) => Err(parse::Error::new(
+                        lit.span(),
+                        "expected a non-negative integer",
+                    )),
+                    None => Ok(None),
+                };
+                Ok(Self::Length {
+                    min: to_usize(min)?,
+                    max: to_usize(max)?,
+                })
+            }
+            "one_of" => {
+                input.parse::<token::Eq>()?;
+                let content;
+                bracketed!(content in input);
+                Ok(Self::OneOf(
+                    Punctuated::<NumOrStr, token::Comma>::parse_terminated(
+                        &content,
+                    )?
+                    .into_iter()
+                    .collect(),
+                ))
+            }
+            "range" => {
+                let (min, max) = Self::min_max(input)?;
+                Ok(Self::Range { min, max })
+            }
+            "regex" => {
+                input.parse::<token::Eq>()?;
+                let pattern: LitStr = input.parse()?;
+                if let Err(err) = ::regex::Regex::new(&pattern.value()) {
+                    return Err(parse::Error::new(
+                        pattern.span(),
+                        err.to_string(),
+                    ));
+                }
+                Ok(Self::Regex(pattern))
+            }
+            _ => Err(parse::Error::new(rule.span(), ATTR_VALIDATE_ERROR)),
+        }
+    }
+}
+
+// A literal that may be a negative number.
+#[derive(Clone, Debug)]
+pub(crate) struct NumOrStr {
+    neg: bool,
+    lit: Lit,
+}
+
+impl NumOrStr {
+    // An integer is made a float if float.
+    //
+    fn expr(&self, float: bool) -> Expr {
+        let lit = match &self.lit {
+            Lit::Int(int) if float => Lit::Float(LitFloat::new(
+                &format!("{}.0", int.base10_digits()),
+                int.span(),
+            )),
+            lit => lit.clone(),
+        };
+        if self.neg {
+            parse_quote!(-#lit)
+        } else {
+            parse_quote!(#lit)
+        }
+    }
+}
+
+impl Parse for NumOrStr {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let neg = input.parse::<Option<token::Sub>>()?.is_some();
+        let lit: Lit = input.parse()?;
+        match lit {
+            Lit::Int(_) | Lit::Float(_) => Ok(Self { neg, lit }),
+            Lit::Str(_) if !neg => Ok(Self { neg, lit }),
+            _ => Err(parse::Error::new(
+                lit.span(),
+                "expected a number or a string",
+            )),
+        }
+    }
+}
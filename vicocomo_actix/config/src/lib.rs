@@ -7,6 +7,21 @@ use proc_macro::TokenStream;
 /// ```text
 /// pub fn actix_main() -> std::io::Result<()>
 /// ```
+/// ### Database migrations
+///
+/// If the [`app_config`
+/// ](../vicocomo/http/server/struct.HttpServerIf.html#migrations) attribute
+/// `migrations` is defined, `actix_main()` applies any pending migrations
+/// before starting the server, and returns an error if that fails.
+///
+/// ### Query logging
+///
+/// If the [`app_config`
+/// ](../vicocomo/http/server/struct.HttpServerIf.html#query_log) attribute
+/// `query_log` or `slow_query_ms` is defined, the database connection is
+/// wrapped in a [`vicocomo::LoggingConn`
+/// ](../vicocomo/database/struct.LoggingConn.html).
+///
 /// ### Web sessions under Actix
 ///
 /// `config` accepts two adapter-specific [`app_config`
@@ -101,10 +116,15 @@ pub fn config(input: TokenStream) -> TokenStream {
     };
     use vicocomo::{Config, ConfigAttrVal, HttpHandler};
 
+    const ERROR_MIGRATIONS: &'static str =
+        "expected true, false, or a directory path";
+    const ERROR_QUERY_LOG: &'static str =
+        "expected true, false, or a file path";
     const ERROR_SESSION: &'static str =
         "expected None, Cookie, Database, or [Database, <max age>]";
     const ERROR_SESSION_MW: &'static str =
         "expected a tuple (actix_session::SessionMiddleware, <expression>";
+    const ERROR_SLOW_QUERY: &'static str = "expected milliseconds";
     const SESSION_DB_DEFAULT: &'static str = "8640000"; // 100 days
     const SESSION_DB_NONE: &'static str = "0";
 
@@ -133,7 +153,87 @@ pub fn config(input: TokenStream) -> TokenStream {
         }
         expr
     };
-    let (db_type, db_init) = plug_ins.get("DbConn").unwrap();
+    let (db_type, db_init): (Type, Expr) = {
+        let (typ, init) = plug_ins.get("DbConn").unwrap();
+        let slow = app_config.get("slow_query_ms").map(|val| {
+            LitInt::new(
+                &val.get_i64().expect(ERROR_SLOW_QUERY).to_string(),
+                Span::call_site(),
+            )
+        });
+        let sink: Option<Expr> = match app_config.get("query_log") {
+            Some(val) => {
+                if let Ok(flg) = val.get_bool() {
+                    flg.then(|| parse_quote!(::vicocomo::StderrLog))
+                } else {
+                    let path = val.get_litstr().expect(ERROR_QUERY_LOG);
+                    Some(parse_quote!(
+                        ::vicocomo::FileLog::new(
+                            ::std::path::Path::new(#path),
+                        )
+                        .expect("cannot open query log")
+                    ))
+                }
+            }
+            None => {
+                slow.as_ref().map(|_| parse_quote!(::vicocomo::StderrLog))
+            }
+        };
+        match sink {
+            Some(sink) => {
+                let mut init: Expr = parse_quote!(
+                    ::vicocomo::LoggingConn::new(
+                        #init,
+                        ::std::sync::Arc::new(#sink),
+                    )
+                );
+                if let Some(ms) = slow {
+                    init = parse_quote!(
+                        #init.slow_query_threshold(
+                            ::std::time::Duration::from_millis(#ms),
+                        )
+                    );
+                }
+                (parse_quote!(::vicocomo::LoggingConn<#typ>), init)
+            }
+            None => (typ.clone(), init.clone()),
+        }
+    };
+    let apply_migrations: Expr = {
+        let mut expr: Expr = parse_quote!(());
+        if let Some(val) = app_config.get("migrations") {
+            let dir = if let Ok(flg) = val.get_bool() {
+                flg.then(|| {
+                    app_config
+                        .get("resource_dir")
+                        .and_then(|dir| dir.get_string().ok())
+                        .unwrap_or_default()
+                        + "db/migrations"
+                })
+            } else {
+                Some(val.get_string().expect(ERROR_MIGRATIONS))
+            };
+            if let Some(dir) = dir {
+                let dir = LitStr::new(&dir, Span::call_site());
+                expr = parse_quote!(
+                    ::vicocomo::migration::migrate(
+                        ::vicocomo::DatabaseIf::new(
+                            database_ref.clone().into_inner(),
+                        ),
+                        ::std::path::Path::new(#dir),
+                    )
+                    .map_err(|e| {
+                        eprintln!("{}", e);
+                        ::std::io::Error::new(
+                            ::std::io::ErrorKind::Other,
+                            e.to_string(),
+                        )
+                    })?
+                );
+            }
+        }
+        expr
+    };
     let (has_session, db_session) = app_config
         .get("session")
         .map(|val| {
@@ -450,6 +550,7 @@ pub fn config(input: TokenStream) -> TokenStream {
             let conf_ref = ::actix_web::web::Data::new(conf);
             let stro_ref = ::actix_web::web::Data::new(stro);
             let database_ref = ::actix_web::web::Data::new(#db_init);
+            #apply_migrations;
             let templ_ref = ::actix_web::web::Data::new(#templ_init);
             let port_str = ::std::env::var("PORT").unwrap_or_default();
             ::actix_web::HttpServer::new(move || {
@@ -33,6 +33,7 @@ pub fn db_value_convert_impl(input: TokenStream) -> TokenStream {
         option_type = parse_quote!(#option_type_id);
     }
     let contained_type: Type = match variant.to_string().as_str() {
+        "Blob" => parse_quote!(Vec<u8>),
         "Float" => parse_quote!(f64),
         "Int" => parse_quote!(i64),
         "Text" => parse_quote!(String),
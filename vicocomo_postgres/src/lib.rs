@@ -1,33 +1,445 @@
 //! Implement `vicocomo::DbConn` by way of the `tokio-postgres` crate.
 
-use futures::executor::block_on;
-use vicocomo::{DatabaseError, DbConn, DbType, DbValue, Error};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use futures::{executor::block_on, pin_mut, StreamExt};
+use std::sync::{atomic::AtomicUsize, Arc, Mutex};
+use tokio_postgres::{error::SqlState, types::Type, Statement};
+use vicocomo::{
+    ColumnSchema, DatabaseError, DbConn, DbRow, DbType, DbValue, Error,
+    ForeignKeyAction, ForeignKeySchema, StatementCache, StatementCacheStats,
+    TableSchema,
+};
+
+/// The default number of prepared statements cached by a [`PgConn`
+/// ](struct.PgConn.html).
+///
+pub const STATEMENT_CACHE_CAPACITY: usize = 100;
 
 /// A wrapping of `tokio_postgres::Client` that implements `vicocomo::DbConn`.
 ///
-pub struct PgConn(tokio_postgres::Client);
+/// The prepared `tokio_postgres::Statement`s are cached, keyed by the SQL
+/// string, see [`set_statement_cache_capacity()`
+/// ](#method.set_statement_cache_capacity).
+///
+pub struct PgConn {
+    client: tokio_postgres::Client,
+    cache: Mutex<StatementCache<Statement>>,
+    // see DbConn::transaction_depth()
+    depth: AtomicUsize,
+}
 
 impl PgConn {
+    /// At most [`STATEMENT_CACHE_CAPACITY`
+    /// ](constant.STATEMENT_CACHE_CAPACITY.html) prepared statements are
+    /// cached.
+    ///
     pub fn new(client: tokio_postgres::Client) -> Self {
-        Self(client)
+        Self {
+            client,
+            cache: Mutex::new(StatementCache::new(STATEMENT_CACHE_CAPACITY)),
+            depth: AtomicUsize::new(0),
+        }
+    }
+
+    /// Set the maximum number of cached prepared statements. `0` disables
+    /// caching.
+    ///
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        self.cache.lock().unwrap().set_capacity(capacity);
+    }
+
+    // Run f with a prepared statement for sql, from the cache if possible.
+    // If the server says that a cached statement is stale, e.g. after ALTER
+    // TABLE, prepare it again and retry once.
+    //
+    fn with_statement<T, F>(&self, sql: &str, mut f: F) -> Result<T, Error>
+    where
+        F: FnMut(&Statement) -> Result<T, Error>,
+    {
+        let cached = self.cache.lock().unwrap().get(sql);
+        match cached {
+            Some(stmt) => match f(&stmt) {
+                Err(Error::Database(DatabaseError {
+                    sqlstate: Some(ref code),
+                    ..
+                })) if code == SqlState::FEATURE_NOT_SUPPORTED.code() => {
+                    self.cache.lock().unwrap().remove(sql);
+                    let stmt = self.prepare(sql)?;
+                    f(&stmt)
+                }
+                result => result,
+            },
+            None => f(&self.prepare(sql)?),
+        }
+    }
+
+    fn prepare(&self, sql: &str) -> Result<Statement, Error> {
+        let stmt =
+            block_on(self.client.prepare(sql)).map_err(|e| self.error(&e))?;
+        self.cache.lock().unwrap().insert(sql, stmt.clone());
+        Ok(stmt)
+    }
+
+    // Get the value in column ix from the column type.
+    //
+    // NUMERIC is converted to Float. Other types that have no DbValue
+    // variant are converted to Text, using the text form of the types whose
+    // binary form is text, e.g. JSON and enums, and of UUID and INTERVAL.
+    // Any other binary form is returned as a Blob rather than failing.
+    //
+    fn infer_value(
+        &self,
+        postgres_row: &tokio_postgres::Row,
+        ix: usize,
+        typ: &Type,
+    ) -> Result<DbValue, Error> {
+        macro_rules! get {
+            ($t:ty, $variant:ident, $nul_variant:ident) => {
+                get!($t, $variant, $nul_variant, |v| v)
+            };
+            ($t:ty, $variant:ident, $nul_variant:ident, $conv:expr) => {
+                match postgres_row.try_get::<_, Option<$t>>(ix) {
+                    Ok(Some(val)) => DbValue::$variant($conv(val)),
+                    Ok(None) => DbValue::$nul_variant(None),
+                    Err(e) => return Err(self.error(&e)),
+                }
+            };
+        }
+
+        Ok(match *typ {
+            Type::BOOL => get!(bool, Bool, NulBool),
+            Type::BYTEA => get!(Vec<u8>, Blob, NulBlob),
+            Type::DATE => get!(NaiveDate, Date, NulDate),
+            Type::FLOAT4 => get!(f32, Float, NulFloat, f64::from),
+            Type::FLOAT8 => get!(f64, Float, NulFloat),
+            Type::INT2 => get!(i16, Int, NulInt, i64::from),
+            Type::INT4 => get!(i32, Int, NulInt, i64::from),
+            Type::INT8 => get!(i64, Int, NulInt),
+            Type::TIME => get!(NaiveTime, Time, NulTime),
+            Type::TIMESTAMP => get!(NaiveDateTime, DateTime, NulDateTime),
+            Type::TIMESTAMPTZ => get!(
+                chrono::DateTime<chrono::Utc>,
+                DateTime,
+                NulDateTime,
+                |v: chrono::DateTime<chrono::Utc>| v.naive_utc()
+            ),
+            Type::NUMERIC => {
+                get!(RawValue, Float, NulFloat, |v: RawValue| {
+                    Self::numeric_to_f64(&v.0)
+                })
+            }
+            Type::UUID => get!(RawValue, Text, NulText, |v: RawValue| {
+                Self::uuid_to_string(&v.0)
+            }),
+            Type::INTERVAL => get!(RawValue, Text, NulText, |v: RawValue| {
+                Self::interval_to_string(&v.0)
+            }),
+            Type::JSONB => get!(RawValue, Text, NulText, |v: RawValue| {
+                // skip the version byte
+                String::from_utf8_lossy(v.0.get(1..).unwrap_or(&[]))
+                    .to_string()
+            }),
+            _ => match postgres_row.try_get::<_, Option<String>>(ix) {
+                Ok(Some(text)) => DbValue::Text(text),
+                Ok(None) => DbValue::NulText(None),
+                Err(_) => {
+                    match postgres_row.try_get::<_, Option<RawValue>>(ix) {
+                        Ok(Some(RawValue(bytes))) => {
+                            match String::from_utf8(bytes) {
+                                Ok(text) => DbValue::Text(text),
+                                Err(e) => DbValue::Blob(e.into_bytes()),
+                            }
+                        }
+                        Ok(None) => DbValue::NulText(None),
+                        Err(e) => return Err(self.error(&e)),
+                    }
+                }
+            },
+        })
+    }
+
+    // The binary form of INTERVAL as PostgreSQL's default text form, e.g.
+    // "1 year 2 mons 3 days 04:05:06.5".
+    fn interval_to_string(raw: &[u8]) -> String {
+        if raw.len() != 16 {
+            return String::new();
+        }
+        let micros = i64::from_be_bytes(raw[0..8].try_into().unwrap());
+        let days = i32::from_be_bytes(raw[8..12].try_into().unwrap());
+        let months = i32::from_be_bytes(raw[12..16].try_into().unwrap());
+        let mut parts = Vec::new();
+        for (count, unit) in
+            [(months / 12, "year"), (months % 12, "mon"), (days, "day")]
+        {
+            if count != 0 {
+                let plural = if count.abs() == 1 { "" } else { "s" };
+                parts.push(format!("{} {}{}", count, unit, plural));
+            }
+        }
+        if micros != 0 || parts.is_empty() {
+            let abs = micros.unsigned_abs();
+            let secs = abs / 1_000_000;
+            let mut time = format!(
+                "{}{:02}:{:02}:{:02}",
+                if micros < 0 { "-" } else { "" },
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60,
+            );
+            if abs % 1_000_000 != 0 {
+                let frac = format!("{:06}", abs % 1_000_000);
+                time.push('.');
+                time.push_str(frac.trim_end_matches('0'));
+            }
+            parts.push(time);
+        }
+        parts.join(" ")
+    }
+
+    // The binary form of NUMERIC as the nearest f64, NaN if it is not a
+    // number or malformed.
+    fn numeric_to_f64(raw: &[u8]) -> f64 {
+        let word = |ix: usize| -> Option<u16> {
+            raw.get(2 * ix..2 * ix + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        };
+        let (Some(ndigits), Some(weight), Some(sign)) =
+            (word(0), word(1), word(2))
+        else {
+            return f64::NAN;
+        };
+        let sign = match sign {
+            0x0000 => "",
+            0x4000 => "-",
+            0xD000 => return f64::INFINITY,
+            0xF000 => return f64::NEG_INFINITY,
+            _ => return f64::NAN,
+        };
+        let mut digits = String::new();
+        for ix in 0..ndigits as usize {
+            match word(4 + ix) {
+                Some(digit) => digits.push_str(&format!("{:04}", digit)),
+                None => return f64::NAN,
+            }
+        }
+        if digits.is_empty() {
+            return 0.0;
+        }
+        // the value is 0.<digits> * 10000^(weight + 1)
+        format!("{}0.{}e{}", sign, digits, 4 * (weight as i16 as i32 + 1))
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+
+    // The binary form of UUID as hyphenated lower case hexadecimal.
+    fn uuid_to_string(raw: &[u8]) -> String {
+        let hex =
+            raw.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        if hex.len() != 32 {
+            return hex;
+        }
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32],
+        )
+    }
+
+    // Convert a PostgreSQL row to DbValue-s.
+    fn row_values(
+        &self,
+        postgres_row: &tokio_postgres::Row,
+        types: &[DbType],
+    ) -> Result<Vec<DbValue>, Error> {
+        let mut vicocomo_row = Vec::new();
+        for (ix, typ) in types.iter().enumerate() {
+            vicocomo_row.push(match typ {
+                DbType::Blob => DbValue::Blob(
+                    match postgres_row.try_get::<_, Vec<u8>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::Bool => {
+                    DbValue::Bool(match postgres_row.try_get::<_, bool>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    })
+                }
+                DbType::Date => DbValue::Date(
+                    match postgres_row.try_get::<_, NaiveDate>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::DateTime => DbValue::DateTime(
+                    match postgres_row.try_get::<_, NaiveDateTime>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::Float => {
+                    DbValue::Float(match postgres_row.try_get::<_, f64>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    })
+                }
+                DbType::Int => {
+                    DbValue::Int(match postgres_row.try_get::<_, i64>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    })
+                }
+                DbType::Text => DbValue::Text(
+                    match postgres_row.try_get::<_, String>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::Time => DbValue::Time(
+                    match postgres_row.try_get::<_, NaiveTime>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulBlob => DbValue::NulBlob(
+                    match postgres_row.try_get::<_, Option<Vec<u8>>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulBool => DbValue::NulBool(
+                    match postgres_row.try_get::<_, Option<bool>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulDate => DbValue::NulDate(
+                    match postgres_row.try_get::<_, Option<NaiveDate>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulDateTime => DbValue::NulDateTime(
+                    match postgres_row.try_get::<_, Option<NaiveDateTime>>(ix)
+                    {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulFloat => DbValue::NulFloat(
+                    match postgres_row.try_get::<_, Option<f64>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulInt => DbValue::NulInt(
+                    match postgres_row.try_get::<_, Option<i64>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulText => DbValue::NulText(
+                    match postgres_row.try_get::<_, Option<String>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+                DbType::NulTime => DbValue::NulTime(
+                    match postgres_row.try_get::<_, Option<NaiveTime>>(ix) {
+                        Ok(val) => val,
+                        Err(e) => return Err(self.error(&e)),
+                    },
+                ),
+            });
+        }
+        Ok(vicocomo_row)
+    }
+
+    // The type of a column from information_schema.columns.data_type.
+    fn data_type(data_type: &str) -> DbType {
+        match data_type {
+            "boolean" => DbType::Bool,
+            "bytea" => DbType::Blob,
+            "date" => DbType::Date,
+            "bigint" | "integer" | "smallint" => DbType::Int,
+            "double precision" | "numeric" | "real" => DbType::Float,
+            t if t.starts_with("timestamp") => DbType::DateTime,
+            t if t.starts_with("time") => DbType::Time,
+            _ => DbType::Text,
+        }
+    }
+
+    // Split a comma separated list of column names.
+    fn split_columns(columns: &str) -> Vec<String> {
+        columns
+            .split(',')
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string())
+            .collect()
     }
 
+    // The table, column, and constraint are as reported by the server.
     fn error(&self, err: &tokio_postgres::error::Error) -> Error {
+        let db_err = err.as_db_error();
         Error::Database(DatabaseError {
             sqlstate: err.code().map(|c| c.code().to_string()),
             text: err.to_string(),
+            table: db_err.and_then(|e| e.table()).map(|t| t.to_string()),
+            column: db_err.and_then(|e| e.column()).map(|c| c.to_string()),
+            constraint: db_err
+                .and_then(|e| e.constraint())
+                .map(|c| c.to_string()),
         })
     }
 }
 
+// The binary form of a value of any type, see PgConn::infer_value().
+struct RawValue(Vec<u8>);
+
+impl<'a> postgres_types::FromSql<'a> for RawValue {
+    fn from_sql(
+        _ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self(raw.to_vec()))
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
 macro_rules! from_values {
     ($values:expr) => {
         &$values
             .iter()
             .map(|val| match val {
+                DbValue::Blob(v) => v as &(dyn postgres_types::ToSql + Sync),
+                DbValue::Bool(v) => v as &(dyn postgres_types::ToSql + Sync),
+                DbValue::Date(v) => v as &(dyn postgres_types::ToSql + Sync),
+                DbValue::DateTime(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
                 DbValue::Float(v) => v as &(dyn postgres_types::ToSql + Sync),
                 DbValue::Int(v) => v as &(dyn postgres_types::ToSql + Sync),
                 DbValue::Text(v) => v as &(dyn postgres_types::ToSql + Sync),
+                DbValue::Time(v) => v as &(dyn postgres_types::ToSql + Sync),
+                DbValue::NulBlob(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
+                DbValue::NulBool(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
+                DbValue::NulDate(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
+                DbValue::NulDateTime(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
                 DbValue::NulFloat(v) => {
                     v as &(dyn postgres_types::ToSql + Sync)
                 }
@@ -37,6 +449,9 @@ macro_rules! from_values {
                 DbValue::NulText(v) => {
                     v as &(dyn postgres_types::ToSql + Sync)
                 }
+                DbValue::NulTime(v) => {
+                    v as &(dyn postgres_types::ToSql + Sync)
+                }
             })
             .collect::<Vec<_>>()[..]
     };
@@ -44,10 +459,11 @@ macro_rules! from_values {
 
 impl DbConn for PgConn {
     fn exec(&self, sql: &str, vals: &[DbValue]) -> Result<usize, Error> {
-        match block_on(self.0.execute(sql, from_values!(vals))) {
-            Ok(i) => Ok(i as usize),
-            Err(e) => Err(self.error(&e)),
-        }
+        self.with_statement(sql, |stmt| {
+            block_on(self.client.execute(stmt, from_values!(vals)))
+                .map(|i| i as usize)
+                .map_err(|e| self.error(&e))
+        })
     }
 
     fn query(
@@ -56,63 +472,182 @@ impl DbConn for PgConn {
         values: &[DbValue],
         types: &[DbType],
     ) -> Result<Vec<Vec<DbValue>>, Error> {
-        match block_on(self.0.query(sql, from_values!(values))) {
-            Ok(pg_rows) => {
-                let mut vicocomo_rows = Vec::new();
-                for postgres_row in pg_rows {
-                    let mut vicocomo_row = Vec::new();
-                    for (ix, typ) in types.iter().enumerate() {
-                        vicocomo_row.push(match typ {
-                            DbType::Float => DbValue::Float(
-                                match postgres_row.try_get::<_, f64>(ix) {
-                                    Ok(val) => val,
-                                    Err(e) => return Err(self.error(&e)),
-                                },
-                            ),
-                            DbType::Int => DbValue::Int(
-                                match postgres_row.try_get::<_, i64>(ix) {
-                                    Ok(val) => val,
-                                    Err(e) => return Err(self.error(&e)),
-                                },
-                            ),
-                            DbType::Text => DbValue::Text(match postgres_row
-                                .try_get::<_, String>(
-                                ix,
-                            ) {
-                                Ok(val) => val,
-                                Err(e) => return Err(self.error(&e)),
-                            }),
-                            DbType::NulFloat => DbValue::NulFloat(
-                                match postgres_row
-                                    .try_get::<_, Option<f64>>(ix)
-                                {
-                                    Ok(val) => val,
-                                    Err(e) => return Err(self.error(&e)),
-                                },
-                            ),
-                            DbType::NulInt => DbValue::NulInt(
-                                match postgres_row
-                                    .try_get::<_, Option<i64>>(ix)
-                                {
-                                    Ok(val) => val,
-                                    Err(e) => return Err(self.error(&e)),
-                                },
-                            ),
-                            DbType::NulText => DbValue::NulText(
-                                match postgres_row
-                                    .try_get::<_, Option<String>>(ix)
-                                {
-                                    Ok(val) => val,
-                                    Err(e) => return Err(self.error(&e)),
-                                },
-                            ),
-                        });
-                    }
-                    vicocomo_rows.push(vicocomo_row);
+        let mut rows = Vec::new();
+        self.query_each(sql, values, types, &mut |row| {
+            rows.push(row);
+            Ok(())
+        })?;
+        Ok(rows)
+    }
+
+    fn query_each(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+        f: &mut dyn FnMut(Vec<DbValue>) -> Result<(), Error>,
+    ) -> Result<usize, Error> {
+        self.with_statement(sql, |stmt| {
+            let pg_rows = block_on(
+                self.client
+                    .query_raw(stmt, from_values!(values).iter().copied()),
+            )
+            .map_err(|e| self.error(&e))?;
+            pin_mut!(pg_rows);
+            let mut count = 0;
+            while let Some(postgres_row) = block_on(pg_rows.next()) {
+                let postgres_row =
+                    postgres_row.map_err(|e| self.error(&e))?;
+                f(self.row_values(&postgres_row, types)?)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    fn query_rows(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+    ) -> Result<Vec<DbRow>, Error> {
+        self.with_statement(sql, |stmt| {
+            let pg_rows =
+                block_on(self.client.query(stmt, from_values!(values)))
+                    .map_err(|e| self.error(&e))?;
+            let columns = Arc::new(
+                stmt.columns()
+                    .iter()
+                    .map(|col| col.name().to_string())
+                    .collect::<Vec<_>>(),
+            );
+            let mut rows = Vec::new();
+            for postgres_row in pg_rows {
+                let mut vicocomo_row = Vec::new();
+                for (ix, col) in stmt.columns().iter().enumerate() {
+                    vicocomo_row.push(self.infer_value(
+                        &postgres_row,
+                        ix,
+                        col.type_(),
+                    )?);
                 }
-                Ok(vicocomo_rows)
+                rows.push(DbRow::new(columns.clone(), vicocomo_row));
+            }
+            Ok(rows)
+        })
+    }
+
+    fn statement_cache_stats(&self) -> Option<StatementCacheStats> {
+        Some(self.cache.lock().unwrap().stats())
+    }
+
+    // Looks for table in the current schema. Only constraints are reported,
+    // not unique indexes created by CREATE UNIQUE INDEX.
+    //
+    fn table_schema(
+        &self,
+        table: &str,
+    ) -> Result<Option<TableSchema>, Error> {
+        let name = [DbValue::Text(table.to_string())];
+        let cols = self.query(
+            "SELECT column_name::TEXT, data_type::TEXT, is_nullable::TEXT, \
+                column_default::TEXT \
+            FROM information_schema.columns \
+            WHERE table_schema = current_schema() AND table_name = $1 \
+            ORDER BY ordinal_position",
+            &name,
+            &[DbType::Text, DbType::Text, DbType::Text, DbType::NulText],
+        )?;
+        if cols.is_empty() {
+            return Ok(None);
+        }
+        let mut columns = Vec::new();
+        for mut col in cols {
+            let default: Option<String> = col.remove(3).try_into()?;
+            let nullable: String = col.remove(2).try_into()?;
+            let data_type: String = col.remove(1).try_into()?;
+            let name: String = col.remove(0).try_into()?;
+            let auto_increment =
+                default.as_ref().is_some_and(|d| d.starts_with("nextval("));
+            let db_type = Self::data_type(&data_type);
+            columns.push(ColumnSchema {
+                name,
+                db_type: if nullable == "YES" {
+                    db_type.nullable()
+                } else {
+                    db_type
+                },
+                default: if auto_increment { None } else { default },
+                auto_increment,
+            });
+        }
+        let mut primary_key = Vec::new();
+        let mut uniques = Vec::new();
+        let mut foreign_keys = Vec::new();
+        for mut row in self.query(
+            "SELECT con.contype::TEXT, \
+                ( SELECT string_agg(a.attname::TEXT, ',' ORDER BY k.ord) \
+                  FROM unnest(con.conkey) WITH ORDINALITY AS k(num, ord) \
+                  JOIN pg_attribute a \
+                    ON a.attrelid = con.conrelid AND a.attnum = k.num \
+                ), \
+                COALESCE(rem.relname::TEXT, ''), \
+                COALESCE( \
+                  ( SELECT string_agg(a.attname::TEXT, ',' ORDER BY k.ord) \
+                    FROM unnest(con.confkey) WITH ORDINALITY AS k(num, ord) \
+                    JOIN pg_attribute a \
+                      ON a.attrelid = con.confrelid AND a.attnum = k.num \
+                  ), \
+                  '' \
+                ), \
+                con.confdeltype::TEXT \
+            FROM pg_constraint con \
+            JOIN pg_class tab ON tab.oid = con.conrelid \
+            LEFT JOIN pg_class rem ON rem.oid = con.confrelid \
+            WHERE tab.relname::TEXT = $1 \
+                AND tab.relnamespace = current_schema()::regnamespace \
+                AND con.contype IN ('p', 'u', 'f') \
+            ORDER BY con.conname",
+            &name,
+            &[
+                DbType::Text,
+                DbType::Text,
+                DbType::Text,
+                DbType::Text,
+                DbType::Text,
+            ],
+        )? {
+            let on_delete: String = row.remove(4).try_into()?;
+            let remote_columns: String = row.remove(3).try_into()?;
+            let remote_table: String = row.remove(2).try_into()?;
+            let columns: String = row.remove(1).try_into()?;
+            let kind: String = row.remove(0).try_into()?;
+            let columns = Self::split_columns(&columns);
+            match kind.as_str() {
+                "p" => primary_key = columns,
+                "u" => uniques.push(columns),
+                _ => foreign_keys.push(ForeignKeySchema {
+                    table: table.to_string(),
+                    columns,
+                    remote_table,
+                    remote_columns: Self::split_columns(&remote_columns),
+                    on_delete: match on_delete.as_str() {
+                        "c" => ForeignKeyAction::Cascade,
+                        "n" => ForeignKeyAction::SetNull,
+                        _ => ForeignKeyAction::NoAction,
+                    },
+                }),
             }
-            Err(e) => Err(self.error(&e)),
         }
+        Ok(Some(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            uniques,
+            foreign_keys,
+        }))
+    }
+
+    fn transaction_depth(&self) -> Option<&AtomicUsize> {
+        Some(&self.depth)
     }
 }
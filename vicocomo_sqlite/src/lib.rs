@@ -1,32 +1,270 @@
 //! Implement `vicocomo::DbConn` by way of the `rusqlite` crate.
 
-use rusqlite::{Connection, ToSql};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{functions::FunctionFlags, Connection, ToSql};
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{atomic::AtomicUsize, Arc, Mutex};
+use std::time::Duration;
 use vicocomo::{
-    DbConn, DbType, DbValue, Error, SQLSTATE_FOREIGN_KEY_VIOLATION,
+    ColumnSchema, DatabaseError, DbConn, DbRow, DbType, DbValue, Error,
+    ForeignKeyAction, ForeignKeySchema, StatementCache, StatementCacheStats,
+    TableSchema, SQLSTATE_CHECK_VIOLATION, SQLSTATE_FOREIGN_KEY_VIOLATION,
+    SQLSTATE_LOCK_NOT_AVAILABLE, SQLSTATE_NOT_NULL_VIOLATION,
     SQLSTATE_UNIQUE_VIOLATION,
 };
 
+/// The default number of prepared statements cached by a [`SqliteConn`
+/// ](struct.SqliteConn.html).
+///
+pub const STATEMENT_CACHE_CAPACITY: usize = 100;
+
 /// A wrapping of `sqlite::Connection` that implements `vicocomo::DbConn`.
 ///
+/// The statements given to [`exec()`
+/// ](../vicocomo/database/trait.DbConn.html#tymethod.exec) and [`query()`
+/// ](../vicocomo/database/trait.DbConn.html#tymethod.query) are prepared
+/// using `rusqlite`'s [`prepare_cached()`
+/// ](https://docs.rs/rusqlite/latest/rusqlite/struct.Connection.html#method.prepare_cached),
+/// see [`set_statement_cache_capacity()`
+/// ](#method.set_statement_cache_capacity).
+///
 // Connection is not Send, which is needed e.g. when used as managed data by
 // actix-web. Hence Mutex.
 //
-pub struct SqliteConn(Mutex<Connection>);
+// rusqlite does not count cache hits, so cache mirrors the keys in the
+// connection's cache to provide statistics.
+//
+pub struct SqliteConn {
+    conn: Mutex<Connection>,
+    cache: Mutex<StatementCache<()>>,
+    // see DbConn::transaction_depth()
+    depth: AtomicUsize,
+}
 
 impl SqliteConn {
     /// Try to create with [default flags
     /// ](https://docs.rs/rusqlite/latest/rusqlite/struct.Connection.html#flags)
     /// and enabled foreign key support.
     ///
-    pub fn new(path: &Path) -> Result<Self, Error> {
-        Ok(Self(Mutex::new(Self::result(
-            Connection::open(path).and_then(|conn| {
-                conn.execute("PRAGMA foreign_keys = ON", [])?;
-                Ok(conn)
-            }),
-        )?)))
+    /// The SQL function `decode(`*text*`, 'hex')` is defined as in
+    /// PostgreSQL, to support [`DbValue::sql_value()`
+    /// ](../vicocomo/database/enum.DbValue.html#method.sql_value) for binary
+    /// values.
+    ///
+    /// At most [`STATEMENT_CACHE_CAPACITY`
+    /// ](constant.STATEMENT_CACHE_CAPACITY.html) prepared statements are
+    /// cached.
+    ///
+    /// `busy_timeout` is how long to wait for a lock held by another
+    /// connection before failing with an error that [`is_retryable()`
+    /// ](../vicocomo/error/enum.Error.html#method.is_retryable). `None` keeps
+    /// `rusqlite`'s default of 5 seconds.
+    ///
+    pub fn new(
+        path: &Path,
+        busy_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let conn = Self::result(Connection::open(path).and_then(|conn| {
+            if let Some(timeout) = busy_timeout {
+                conn.busy_timeout(timeout)?;
+            }
+            conn.execute("PRAGMA foreign_keys = ON", [])?;
+            conn.create_scalar_function(
+                "decode",
+                2,
+                FunctionFlags::SQLITE_UTF8
+                    | FunctionFlags::SQLITE_DETERMINISTIC,
+                |ctx| {
+                    Self::decode_hex(
+                        &ctx.get::<String>(0)?,
+                        &ctx.get::<String>(1)?,
+                    )
+                    .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+                },
+            )?;
+            conn.set_prepared_statement_cache_capacity(
+                STATEMENT_CACHE_CAPACITY,
+            );
+            Ok(conn)
+        }))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            cache: Mutex::new(StatementCache::new(STATEMENT_CACHE_CAPACITY)),
+            depth: AtomicUsize::new(0),
+        })
+    }
+
+    /// Set the maximum number of cached prepared statements. `0` disables
+    /// caching.
+    ///
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        let conn = self.conn.lock().unwrap();
+        conn.set_prepared_statement_cache_capacity(capacity);
+        self.cache.lock().unwrap().set_capacity(capacity);
+    }
+
+    // The SQL function decode(hex, 'hex').
+    fn decode_hex(hex: &str, format: &str) -> Result<Vec<u8>, String> {
+        if format != "hex" {
+            return Err(format!("decode() cannot handle format {format:?}"));
+        }
+        if hex.len() % 2 == 1 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("decode() got invalid hex {hex:?}"));
+        }
+        Ok((0..hex.len())
+            .step_by(2)
+            .map(|ix| u8::from_str_radix(&hex[ix..(ix + 2)], 16).unwrap())
+            .collect())
+    }
+
+    // The type indicated by a declared column type, following the SQLite
+    // type affinity rules but recognizing the native types used by
+    // vicocomo, or None if there is no declared type or it is NUMERIC.
+    fn declared_type(decl: Option<&str>) -> Option<DbType> {
+        let decl = decl?.to_uppercase();
+        if decl.contains("BOOL") {
+            Some(DbType::Bool)
+        } else if decl.contains("DATETIME") || decl.contains("TIMESTAMP") {
+            Some(DbType::DateTime)
+        } else if decl.contains("DATE") {
+            Some(DbType::Date)
+        } else if decl.contains("TIME") {
+            Some(DbType::Time)
+        } else if decl.contains("INT") {
+            Some(DbType::Int)
+        } else if ["CHAR", "CLOB", "TEXT"].iter().any(|t| decl.contains(t)) {
+            Some(DbType::Text)
+        } else if decl.contains("BLOB") {
+            Some(DbType::Blob)
+        } else if ["REAL", "FLOA", "DOUB"].iter().any(|t| decl.contains(t)) {
+            Some(DbType::Float)
+        } else {
+            None
+        }
+    }
+
+    // Get the value in column ix from the stored value and the declared type.
+    fn infer_value(
+        row: &rusqlite::Row,
+        ix: usize,
+        declared: Option<DbType>,
+    ) -> rusqlite::Result<DbValue> {
+        use rusqlite::types::ValueRef;
+
+        Ok(match row.get_ref(ix)? {
+            ValueRef::Null => match declared {
+                Some(DbType::Blob) => DbValue::NulBlob(None),
+                Some(DbType::Bool) => DbValue::NulBool(None),
+                Some(DbType::Date) => DbValue::NulDate(None),
+                Some(DbType::DateTime) => DbValue::NulDateTime(None),
+                Some(DbType::Float) => DbValue::NulFloat(None),
+                Some(DbType::Int) => DbValue::NulInt(None),
+                Some(DbType::Time) => DbValue::NulTime(None),
+                _ => DbValue::NulText(None),
+            },
+            ValueRef::Integer(i) => match declared {
+                Some(DbType::Bool) => DbValue::Bool(i != 0),
+                _ => DbValue::Int(i),
+            },
+            ValueRef::Real(f) => DbValue::Float(f),
+            ValueRef::Text(_) => match declared {
+                Some(DbType::Date) => row
+                    .get::<_, NaiveDate>(ix)
+                    .map(DbValue::Date)
+                    .or_else(|_| row.get(ix).map(DbValue::Text))?,
+                Some(DbType::DateTime) => row
+                    .get::<_, NaiveDateTime>(ix)
+                    .map(DbValue::DateTime)
+                    .or_else(|_| row.get(ix).map(DbValue::Text))?,
+                Some(DbType::Time) => row
+                    .get::<_, NaiveTime>(ix)
+                    .map(DbValue::Time)
+                    .or_else(|_| row.get(ix).map(DbValue::Text))?,
+                _ => DbValue::Text(row.get(ix)?),
+            },
+            ValueRef::Blob(b) => DbValue::Blob(b.to_vec()),
+        })
+    }
+
+    // Prepare sql using the connection's statement cache, keeping count of
+    // hits and misses.
+    fn prepare_cached<'c>(
+        &self,
+        conn: &'c Connection,
+        sql: &str,
+    ) -> Result<rusqlite::CachedStatement<'c>, Error> {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if cache.get(sql).is_none() {
+                cache.insert(sql, ());
+            }
+        }
+        Self::result(conn.prepare_cached(sql))
+    }
+
+    // Convert an SQLite row to DbValue-s.
+    fn row_values(
+        sqlt_row: &rusqlite::Row,
+        types: &[DbType],
+    ) -> rusqlite::Result<Vec<DbValue>> {
+        let mut vicocomo_row = Vec::new();
+        for (ix, typ) in types.iter().enumerate() {
+            vicocomo_row.push(match typ {
+                DbType::Blob => {
+                    DbValue::Blob(sqlt_row.get::<_, Vec<u8>>(ix)?)
+                }
+                DbType::Bool => DbValue::Bool(sqlt_row.get::<_, bool>(ix)?),
+                DbType::Date => {
+                    DbValue::Date(sqlt_row.get::<_, NaiveDate>(ix)?)
+                }
+                DbType::DateTime => {
+                    DbValue::DateTime(sqlt_row.get::<_, NaiveDateTime>(ix)?)
+                }
+                DbType::Float => DbValue::Float(sqlt_row.get::<_, f64>(ix)?),
+                DbType::Int => DbValue::Int(sqlt_row.get::<_, i64>(ix)?),
+                DbType::Text => DbValue::Text(sqlt_row.get::<_, String>(ix)?),
+                DbType::Time => {
+                    DbValue::Time(sqlt_row.get::<_, NaiveTime>(ix)?)
+                }
+                DbType::NulBlob => {
+                    DbValue::NulBlob(sqlt_row.get::<_, Option<Vec<u8>>>(ix)?)
+                }
+                DbType::NulBool => {
+                    DbValue::NulBool(sqlt_row.get::<_, Option<bool>>(ix)?)
+                }
+                DbType::NulDate => DbValue::NulDate(
+                    sqlt_row.get::<_, Option<NaiveDate>>(ix)?,
+                ),
+                DbType::NulDateTime => DbValue::NulDateTime(
+                    sqlt_row.get::<_, Option<NaiveDateTime>>(ix)?,
+                ),
+                DbType::NulFloat => {
+                    DbValue::NulFloat(sqlt_row.get::<_, Option<f64>>(ix)?)
+                }
+                DbType::NulInt => {
+                    DbValue::NulInt(sqlt_row.get::<_, Option<i64>>(ix)?)
+                }
+                DbType::NulText => {
+                    DbValue::NulText(sqlt_row.get::<_, Option<String>>(ix)?)
+                }
+                DbType::NulTime => DbValue::NulTime(
+                    sqlt_row.get::<_, Option<NaiveTime>>(ix)?,
+                ),
+            });
+        }
+        Ok(vicocomo_row)
+    }
+
+    // The primary key columns of table, in key order.
+    fn primary_key(&self, table: &str) -> Result<Vec<String>, Error> {
+        self.query(
+            "SELECT name FROM pragma_table_info($1) WHERE pk > 0 ORDER BY pk",
+            &[DbValue::Text(table.to_string())],
+            &[DbType::Text],
+        )?
+        .drain(..)
+        .map(|mut row| row.remove(0).try_into())
+        .collect()
     }
 
     /// Replace `$` with `?`.
@@ -38,20 +276,68 @@ impl SqliteConn {
     fn result<T>(res: rusqlite::Result<T>) -> Result<T, Error> {
         res.map_err(|e| {
             let mut sqlstate = None;
-            if let rusqlite::Error::SqliteFailure(err, ref msg) = e {
-                if let rusqlite::ErrorCode::ConstraintViolation = err.code {
-                    if let Some(text) = msg {
-                        if text.starts_with("FOREIGN KEY") {
-                            sqlstate = Some(SQLSTATE_FOREIGN_KEY_VIOLATION);
-                        } else if text.starts_with("UNIQUE") {
-                            sqlstate = Some(SQLSTATE_UNIQUE_VIOLATION);
-                        }
+            if let rusqlite::Error::SqliteFailure(err, _) = e {
+                match err.code {
+                    rusqlite::ErrorCode::ConstraintViolation => {
+                        use rusqlite::ffi;
+                        sqlstate = match err.extended_code {
+                            ffi::SQLITE_CONSTRAINT_CHECK => {
+                                Some(SQLSTATE_CHECK_VIOLATION)
+                            }
+                            ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {
+                                Some(SQLSTATE_FOREIGN_KEY_VIOLATION)
+                            }
+                            ffi::SQLITE_CONSTRAINT_NOTNULL => {
+                                Some(SQLSTATE_NOT_NULL_VIOLATION)
+                            }
+                            ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+                            | ffi::SQLITE_CONSTRAINT_UNIQUE => {
+                                Some(SQLSTATE_UNIQUE_VIOLATION)
+                            }
+                            _ => None,
+                        };
+                    }
+                    rusqlite::ErrorCode::DatabaseBusy
+                    | rusqlite::ErrorCode::DatabaseLocked => {
+                        sqlstate = Some(SQLSTATE_LOCK_NOT_AVAILABLE);
                     }
+                    _ => (),
                 }
             }
-            Error::database(sqlstate, &e)
+            let mut error = Error::database(sqlstate, &e);
+            if let Error::Database(ref mut de) = error {
+                Self::constraint_detail(&e, de);
+            }
+            error
         })
     }
+
+    /// Set the table and column of a NOT NULL violation or the constraint
+    /// of a CHECK violation from the text after "constraint failed: " in the
+    /// SQLite message, e.g. "NOT NULL constraint failed: scores.name".
+    fn constraint_detail(e: &rusqlite::Error, de: &mut DatabaseError) {
+        let detail = match e {
+            rusqlite::Error::SqliteFailure(err, Some(msg)) => {
+                match msg.split_once("constraint failed: ") {
+                    Some((_, detail)) => (err.extended_code, detail),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        match detail {
+            (rusqlite::ffi::SQLITE_CONSTRAINT_NOTNULL, detail) => {
+                if let Some((table, column)) = detail.split_once('.') {
+                    de.table = Some(table.to_string());
+                    de.column = Some(column.to_string());
+                }
+            }
+            (rusqlite::ffi::SQLITE_CONSTRAINT_CHECK, detail) => {
+                de.constraint = Some(detail.to_string());
+            }
+            _ => (),
+        }
+    }
 }
 
 macro_rules! from_values {
@@ -59,12 +345,22 @@ macro_rules! from_values {
         &$values
             .iter()
             .map(|val| match val {
+                DbValue::Blob(v) => v as &dyn ToSql,
+                DbValue::Bool(v) => v as &dyn ToSql,
+                DbValue::Date(v) => v as &dyn ToSql,
+                DbValue::DateTime(v) => v as &dyn ToSql,
                 DbValue::Float(v) => v as &dyn ToSql,
                 DbValue::Int(v) => v as &dyn ToSql,
                 DbValue::Text(v) => v as &dyn ToSql,
+                DbValue::Time(v) => v as &dyn ToSql,
+                DbValue::NulBlob(v) => v as &dyn ToSql,
+                DbValue::NulBool(v) => v as &dyn ToSql,
+                DbValue::NulDate(v) => v as &dyn ToSql,
+                DbValue::NulDateTime(v) => v as &dyn ToSql,
                 DbValue::NulFloat(v) => v as &dyn ToSql,
                 DbValue::NulInt(v) => v as &dyn ToSql,
                 DbValue::NulText(v) => v as &dyn ToSql,
+                DbValue::NulTime(v) => v as &dyn ToSql,
             })
             .collect::<Vec<_>>()[..]
     };
@@ -72,12 +368,10 @@ macro_rules! from_values {
 
 impl DbConn for SqliteConn {
     fn exec(&self, sql: &str, vals: &[DbValue]) -> Result<usize, Error> {
-        Self::result(
-            self.0
-                .lock()
-                .unwrap()
-                .execute(&Self::fix_params(sql), from_values!(vals)),
-        )
+        let sql = Self::fix_params(sql);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = self.prepare_cached(&conn, &sql)?;
+        Self::result(stmt.execute(from_values!(vals)))
     }
 
     fn query(
@@ -86,57 +380,188 @@ impl DbConn for SqliteConn {
         vals: &[DbValue],
         types: &[DbType],
     ) -> Result<Vec<Vec<DbValue>>, Error> {
-        Self::result(
-            self.0
-                .lock()
-                .unwrap()
-                .prepare(&Self::fix_params(sql))
-                .and_then(|mut stmt| {
-                    Ok({
-                        let mut rows = Vec::new();
-                        for row_result in stmt.query_map(
-                            from_values!(vals),
-                            |sqlt_row| -> rusqlite::Result<Vec<DbValue>> {
-                                let mut vicocomo_row = Vec::new();
-                                for (ix, typ) in types.iter().enumerate() {
-                                    vicocomo_row.push(match typ {
-                                        DbType::Float => DbValue::Float(
-                                            sqlt_row.get::<_, f64>(ix)?,
-                                        ),
-                                        DbType::Int => DbValue::Int(
-                                            sqlt_row.get::<_, i64>(ix)?,
-                                        ),
-                                        DbType::Text => DbValue::Text(
-                                            sqlt_row.get::<_, String>(ix)?,
-                                        ),
-                                        DbType::NulFloat => {
-                                            DbValue::NulFloat(
-                                                sqlt_row
-                                                    .get::<_, Option<f64>>(
-                                                        ix,
-                                                    )?,
-                                            )
-                                        }
-                                        DbType::NulInt => DbValue::NulInt(
-                                            sqlt_row
-                                                .get::<_, Option<i64>>(ix)?,
-                                        ),
-                                        DbType::NulText => DbValue::NulText(
-                                            sqlt_row
-                                                .get::<_, Option<String>>(
-                                                    ix,
-                                                )?,
-                                        ),
-                                    })
-                                }
-                                Ok(vicocomo_row)
-                            },
-                        )? {
-                            rows.push(row_result?)
-                        }
-                        rows
-                    })
-                }),
-        )
+        let mut rows = Vec::new();
+        self.query_each(sql, vals, types, &mut |row| {
+            rows.push(row);
+            Ok(())
+        })?;
+        Ok(rows)
+    }
+
+    fn query_each(
+        &self,
+        sql: &str,
+        vals: &[DbValue],
+        types: &[DbType],
+        f: &mut dyn FnMut(Vec<DbValue>) -> Result<(), Error>,
+    ) -> Result<usize, Error> {
+        let sql = Self::fix_params(sql);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = self.prepare_cached(&conn, &sql)?;
+        let mut rows = Self::result(stmt.query(from_values!(vals)))?;
+        let mut count = 0;
+        while let Some(row) = Self::result(rows.next())? {
+            f(Self::result(Self::row_values(row, types))?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn query_rows(
+        &self,
+        sql: &str,
+        vals: &[DbValue],
+    ) -> Result<Vec<DbRow>, Error> {
+        let sql = Self::fix_params(sql);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = self.prepare_cached(&conn, &sql)?;
+        let declared = stmt
+            .columns()
+            .iter()
+            .map(|col| Self::declared_type(col.decl_type()))
+            .collect::<Vec<_>>();
+        let columns = Arc::new(
+            stmt.column_names()
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+        );
+        let mut rows = Self::result(stmt.query(from_values!(vals)))?;
+        let mut result = Vec::new();
+        while let Some(row) = Self::result(rows.next())? {
+            let mut values = Vec::new();
+            for (ix, decl) in declared.iter().enumerate() {
+                values.push(Self::result(Self::infer_value(row, ix, *decl))?);
+            }
+            result.push(DbRow::new(columns.clone(), values));
+        }
+        Ok(result)
+    }
+
+    fn statement_cache_stats(&self) -> Option<StatementCacheStats> {
+        Some(self.cache.lock().unwrap().stats())
+    }
+
+    // A column that is part of the primary key is reported as NOT NULL even
+    // if not declared so, and a single INTEGER primary key as auto
+    // increment, since it is an alias for the rowid.
+    //
+    fn table_schema(
+        &self,
+        table: &str,
+    ) -> Result<Option<TableSchema>, Error> {
+        let name = [DbValue::Text(table.to_string())];
+        let cols = self.query(
+            "SELECT name, type, \"notnull\", dflt_value, pk \
+            FROM pragma_table_info($1) ORDER BY cid",
+            &name,
+            &[
+                DbType::Text,
+                DbType::Text,
+                DbType::Int,
+                DbType::NulText,
+                DbType::Int,
+            ],
+        )?;
+        if cols.is_empty() {
+            return Ok(None);
+        }
+        let primary_key = self.primary_key(table)?;
+        let mut columns = Vec::new();
+        for mut col in cols {
+            let pk: i64 = col.remove(4).try_into()?;
+            let default: Option<String> = col.remove(3).try_into()?;
+            let not_null: i64 = col.remove(2).try_into()?;
+            let decl: String = col.remove(1).try_into()?;
+            let name: String = col.remove(0).try_into()?;
+            let auto_increment = pk > 0
+                && primary_key.len() == 1
+                && decl.eq_ignore_ascii_case("INTEGER");
+            let db_type =
+                Self::declared_type(Some(&decl)).unwrap_or(DbType::Text);
+            columns.push(ColumnSchema {
+                name,
+                db_type: if not_null == 0 && pk == 0 {
+                    db_type.nullable()
+                } else {
+                    db_type
+                },
+                default: if auto_increment { None } else { default },
+                auto_increment,
+            });
+        }
+        let mut uniques: Vec<Vec<String>> = Vec::new();
+        let mut index = String::new();
+        for mut row in self.query(
+            "SELECT il.name, ii.name \
+            FROM pragma_index_list($1) AS il, \
+                pragma_index_info(il.name) AS ii \
+            WHERE il.\"unique\" = 1 AND il.origin <> 'pk' \
+            ORDER BY il.seq, ii.seqno",
+            &name,
+            &[DbType::Text, DbType::Text],
+        )? {
+            let col: String = row.remove(1).try_into()?;
+            let idx: String = row.remove(0).try_into()?;
+            if idx != index || uniques.is_empty() {
+                index = idx;
+                uniques.push(Vec::new());
+            }
+            uniques.last_mut().unwrap().push(col);
+        }
+        let mut foreign_keys: Vec<ForeignKeySchema> = Vec::new();
+        let mut fk_id = -1;
+        for mut row in self.query(
+            "SELECT id, \"table\", \"from\", \"to\", on_delete \
+            FROM pragma_foreign_key_list($1) ORDER BY id, seq",
+            &name,
+            &[
+                DbType::Int,
+                DbType::Text,
+                DbType::Text,
+                DbType::NulText,
+                DbType::Text,
+            ],
+        )? {
+            let on_delete: String = row.remove(4).try_into()?;
+            let to: Option<String> = row.remove(3).try_into()?;
+            let from: String = row.remove(2).try_into()?;
+            let remote_table: String = row.remove(1).try_into()?;
+            let id: i64 = row.remove(0).try_into()?;
+            if id != fk_id {
+                fk_id = id;
+                foreign_keys.push(ForeignKeySchema {
+                    table: table.to_string(),
+                    columns: Vec::new(),
+                    remote_columns: match to {
+                        // refers to the remote primary key
+                        None => self.primary_key(&remote_table)?,
+                        Some(_) => Vec::new(),
+                    },
+                    remote_table,
+                    on_delete: match on_delete.as_str() {
+                        "CASCADE" => ForeignKeyAction::Cascade,
+                        "SET NULL" => ForeignKeyAction::SetNull,
+                        _ => ForeignKeyAction::NoAction,
+                    },
+                });
+            }
+            let fk = foreign_keys.last_mut().unwrap();
+            fk.columns.push(from);
+            if let Some(to) = to {
+                fk.remote_columns.push(to);
+            }
+        }
+        Ok(Some(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            uniques,
+            foreign_keys,
+        }))
+    }
+
+    fn transaction_depth(&self) -> Option<&AtomicUsize> {
+        Some(&self.depth)
     }
 }
@@ -0,0 +1,286 @@
+//! A scriptable mock implementation of `vicocomo::DbConn`
+
+use ::vicocomo::{
+    DbConn, DbType, DbValue, Error, SQLSTATE_FOREIGN_KEY_VIOLATION,
+    SQLSTATE_UNIQUE_VIOLATION,
+};
+use regex::Regex;
+use std::sync::{Arc, Mutex};
+
+/// A `DbConn` that returns programmed results.
+///
+/// Each call to `exec()` or `query()` is recorded and matched against the
+/// expectations in the order they were added. The first expectation of the
+/// right kind with a matching SQL pattern gives the result. It is then used
+/// up unless added by `always_exec()` or `always_query()`.
+///
+/// A call that does not match any expectation panics, showing the SQL and
+/// the values.
+///
+/// `begin()`, `commit()`, `rollback()`, and the savepoint methods are
+/// recorded and succeed without expectations.
+///
+/// ```text
+/// let conn = MockConn::new();
+/// conn.expect_query(
+///     r"^INSERT INTO users",
+///     Err(MockConn::unique_violation()),
+/// );
+/// let db = DatabaseIf::new(Arc::new(conn.clone()));
+/// // ... run a controller method with db ...
+/// conn.verify();
+/// assert!(conn.called(r"^INSERT INTO users"));
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct MockConn(Arc<Mutex<MockState>>);
+
+/// A call recorded by [`MockConn`](struct.MockConn.html).
+///
+#[derive(Clone, Debug)]
+pub struct MockCall {
+    pub sql: String,
+    pub values: Vec<DbValue>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    calls: Vec<MockCall>,
+    expected: Vec<Expectation>,
+}
+
+#[derive(Debug)]
+struct Expectation {
+    pattern: Regex,
+    result: MockResult,
+    always: bool,
+}
+
+#[derive(Clone, Debug)]
+enum MockResult {
+    Exec(Result<usize, Error>),
+    Query(Result<Vec<Vec<DbValue>>, Error>),
+}
+
+impl MockConn {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every matching `exec()` returns `result`.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    ///
+    pub fn always_exec(&self, pattern: &str, result: Result<usize, Error>) {
+        self.expect(pattern, MockResult::Exec(result), true);
+    }
+
+    /// Every matching `query()` returns `result`.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    ///
+    pub fn always_query(
+        &self,
+        pattern: &str,
+        result: Result<Vec<Vec<DbValue>>, Error>,
+    ) {
+        self.expect(pattern, MockResult::Query(result), true);
+    }
+
+    /// Whether any recorded call has SQL matching `pattern`.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    ///
+    pub fn called(&self, pattern: &str) -> bool {
+        let re = Self::regex(pattern);
+        self.0
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .any(|c| re.is_match(&c.sql))
+    }
+
+    /// All recorded calls, oldest first.
+    ///
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.0.lock().unwrap().calls.clone()
+    }
+
+    /// Forget recorded calls and expectations.
+    ///
+    pub fn clear(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.calls.clear();
+        state.expected.clear();
+    }
+
+    /// The next matching `exec()` returns `result`.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    ///
+    pub fn expect_exec(&self, pattern: &str, result: Result<usize, Error>) {
+        self.expect(pattern, MockResult::Exec(result), false);
+    }
+
+    /// The next matching `query()` returns `result`.
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    ///
+    pub fn expect_query(
+        &self,
+        pattern: &str,
+        result: Result<Vec<Vec<DbValue>>, Error>,
+    ) {
+        self.expect(pattern, MockResult::Query(result), false);
+    }
+
+    /// An error like the one returned by a database adapter for a foreign
+    /// key constraint violation.
+    ///
+    pub fn foreign_key_violation() -> Error {
+        Error::database(
+            Some(SQLSTATE_FOREIGN_KEY_VIOLATION),
+            "FOREIGN KEY constraint failed",
+        )
+    }
+
+    /// An error like the one returned by a database adapter for a unique
+    /// constraint violation.
+    ///
+    pub fn unique_violation() -> Error {
+        Error::database(
+            Some(SQLSTATE_UNIQUE_VIOLATION),
+            "UNIQUE constraint failed",
+        )
+    }
+
+    /// Panic if there are any unused expectations added by `expect_exec()`
+    /// or `expect_query()`.
+    ///
+    pub fn verify(&self) {
+        let state = self.0.lock().unwrap();
+        let unused = state
+            .expected
+            .iter()
+            .filter(|e| !e.always)
+            .map(|e| e.pattern.as_str().to_string())
+            .collect::<Vec<_>>();
+        // release the lock before panicking to avoid poisoning it
+        drop(state);
+        if !unused.is_empty() {
+            panic!("MockConn: expected SQL not executed: {:?}", unused);
+        }
+    }
+
+    fn call(&self, sql: &str, values: &[DbValue], query: bool) -> MockResult {
+        let mut state = self.0.lock().unwrap();
+        state.calls.push(MockCall {
+            sql: sql.to_string(),
+            values: values.to_vec(),
+        });
+        let found = state.expected.iter().position(|e| {
+            matches!(
+                (&e.result, query),
+                (MockResult::Exec(_), false) | (MockResult::Query(_), true)
+            ) && e.pattern.is_match(sql)
+        });
+        match found {
+            Some(ix) if state.expected[ix].always => {
+                state.expected[ix].result.clone()
+            }
+            Some(ix) => state.expected.remove(ix).result,
+            None => {
+                // release the lock before panicking to avoid poisoning it
+                drop(state);
+                panic!(
+                    "MockConn: unexpected {} {:?} with values {:?}",
+                    if query { "query" } else { "exec" },
+                    sql,
+                    values,
+                );
+            }
+        }
+    }
+
+    fn expect(&self, pattern: &str, result: MockResult, always: bool) {
+        self.0.lock().unwrap().expected.push(Expectation {
+            pattern: Self::regex(pattern),
+            result,
+            always,
+        });
+    }
+
+    fn record(&self, sql: &str) -> Result<(), Error> {
+        self.0.lock().unwrap().calls.push(MockCall {
+            sql: sql.to_string(),
+            values: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn regex(pattern: &str) -> Regex {
+        Regex::new(pattern)
+            .unwrap_or_else(|e| panic!("MockConn: bad pattern: {}", e))
+    }
+}
+
+impl DbConn for MockConn {
+    fn begin(&self) -> Result<(), Error> {
+        self.record("BEGIN")
+    }
+
+    fn commit(&self) -> Result<(), Error> {
+        self.record("COMMIT")
+    }
+
+    fn exec(&self, sql: &str, values: &[DbValue]) -> Result<usize, Error> {
+        match self.call(sql, values, false) {
+            MockResult::Exec(result) => result,
+            _ => unreachable!(),
+        }
+    }
+
+    fn query(
+        &self,
+        sql: &str,
+        values: &[DbValue],
+        types: &[DbType],
+    ) -> Result<Vec<Vec<DbValue>>, Error> {
+        match self.call(sql, values, true) {
+            MockResult::Query(result) => {
+                if let Ok(rows) = &result {
+                    if let Some(row) =
+                        rows.iter().find(|row| row.len() != types.len())
+                    {
+                        panic!(
+                            "MockConn: {:?} expects {} columns, \
+                            programmed row is {:?}",
+                            sql,
+                            types.len(),
+                            row,
+                        );
+                    }
+                }
+                result
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn release_savepoint(&self, name: &str) -> Result<(), Error> {
+        self.record(&format!("RELEASE SAVEPOINT {}", name))
+    }
+
+    fn rollback(&self) -> Result<(), Error> {
+        self.record("ROLLBACK")
+    }
+
+    fn rollback_to_savepoint(&self, name: &str) -> Result<(), Error> {
+        self.record(&format!("ROLLBACK TO SAVEPOINT {}", name))
+    }
+
+    fn savepoint(&self, name: &str) -> Result<(), Error> {
+        self.record(&format!("SAVEPOINT {}", name))
+    }
+}
@@ -1,4 +1,6 @@
 //! Stub various vicocomo traits for testing
 
+mod db_conn_stub;
 mod http_server_stub;
+pub use db_conn_stub::{MockCall, MockConn};
 pub use http_server_stub::{Request, Server};
@@ -206,6 +206,13 @@ use proc_macro::TokenStream;
 /// ](../vicocomo/http/server/struct.HttpServerIf.html#level-1-app_config)
 /// documentation.
 ///
+/// #### `busy_timeout_ms`
+///
+/// The value should be an integer, the number of milliseconds to wait for a
+/// lock held by another process using the Sqlite database file, see
+/// [`SqliteConn::new()`
+/// ](../vicocomo_sqlite/struct.SqliteConn.html#method.new). Default 5000.
+///
 /// #### `db_file`
 ///
 /// The value should be a string that is the name of the Sqlite database file.
@@ -343,7 +350,7 @@ pub fn config(input: TokenStream) -> TokenStream {
             };
             use ::vicocomo_tauri::{
                 BOUNDARY, fix_body, fix_response, fix_url,
-                get_bool_str_res_path, get_db, tauri_response,
+                get_bool_str_res_path, get_db, get_query_log, tauri_response,
             };
 
             #[cfg(debug_assertions)]
@@ -417,6 +424,12 @@ pub fn config(input: TokenStream) -> TokenStream {
                         .map(|dir| PathBuf::from(&dir).join(#db_file))
                         .unwrap_or_else(|| PathBuf::from(#db_file))
                         .as_path(),
+                    server
+                        .app_config("busy_timeout_ms")
+                        .and_then(|val| val.int())
+                        .map(|ms| {
+                            ::std::time::Duration::from_millis(ms as u64)
+                        }),
                     get_bool_str_res_path(
                         &server,
                         "create_db",
@@ -424,6 +437,14 @@ pub fn config(input: TokenStream) -> TokenStream {
                     )
                     .as_ref()
                     .map(|pb| pb.as_path()),
+                    get_bool_str_res_path(
+                        &server,
+                        "migrations",
+                        "db/migrations",
+                    )
+                    .as_ref()
+                    .map(|pb| pb.as_path()),
+                    get_query_log(&server).expect("cannot-open-query-log"),
                 ).expect("cannot-open-sqlite")
             });
             let mut response: HttpResponse;
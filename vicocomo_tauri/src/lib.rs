@@ -3,11 +3,13 @@
 use regex::Regex;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tauri::api::dialog::blocking::FileDialogBuilder;
 use url::{ParseError, Url};
 use vicocomo::{
-    t, try_exec_sql, AppConfigVal, DatabaseIf, DbType, Error, HttpHeaderVal,
-    HttpParamVals, HttpRespBody, HttpResponse, HttpServer, HttpServerImpl,
+    migration, t, try_exec_sql, AppConfigVal, DatabaseIf, DbType, Error,
+    FileLog, HttpHeaderVal, HttpParamVals, HttpRespBody, HttpResponse,
+    HttpServer, HttpServerImpl, LoggingConn, QueryLogSink, StderrLog,
 };
 use vicocomo_sqlite::SqliteConn;
 pub use vicocomo_tauri_config::config;
@@ -153,20 +155,60 @@ pub fn get_bool_str_res_path(
     })
 }
 
+// Get a query log sink and slow query threshold from server.app_config().
+//
+// If query_log is true, log to stderr.
+// If query_log is a string, log to that file.
+// If slow_query_ms is an integer, use that threshold and default to stderr.
+// Otherwise return None.
+#[doc(hidden)]
+pub fn get_query_log(
+    server: &HttpServerImpl,
+) -> Result<Option<(Arc<dyn QueryLogSink>, Option<Duration>)>, Error> {
+    let slow = server
+        .app_config("slow_query_ms")
+        .and_then(|val| val.int())
+        .map(|ms| Duration::from_millis(ms as u64));
+    let sink: Option<Arc<dyn QueryLogSink>> =
+        match server.app_config("query_log") {
+            Some(AppConfigVal::Bool(flg)) if flg => Some(Arc::new(StderrLog)),
+            Some(AppConfigVal::Str(path)) => {
+                Some(Arc::new(FileLog::new(Path::new(&path))?))
+            }
+            _ => slow.map(|_| Arc::new(StderrLog) as Arc<dyn QueryLogSink>),
+        };
+    Ok(sink.map(|sink| (sink, slow)))
+}
+
 // used by the code generated by config!().
 //
-// Tries to open an Sqlite connection to db_path and, if the database has no
-// tables and schema is Some(_), executes the SQL in schema.
+// Tries to open an Sqlite connection to db_path with busy_timeout, see
+// SqliteConn::new(), wrapped in a LoggingConn if
+// query_log is Some(_), and, if the database has no tables and schema is
+// Some(_), executes the SQL in schema. Then, if migrations is Some(_),
+// applies any pending migrations in that directory.
 //
 // Errors
 //
 #[doc(hidden)]
 pub fn get_db(
     db_path: &Path,
+    busy_timeout: Option<Duration>,
     schema: Option<&Path>,
+    migrations: Option<&Path>,
+    query_log: Option<(Arc<dyn QueryLogSink>, Option<Duration>)>,
 ) -> Result<DatabaseIf, Error> {
-    SqliteConn::new(db_path).and_then(|conn| {
-        let db = DatabaseIf::new(Arc::new(conn));
+    SqliteConn::new(db_path, busy_timeout).and_then(|conn| {
+        let db = match query_log {
+            Some((sink, slow)) => {
+                let mut conn = LoggingConn::new(conn, sink);
+                if let Some(threshold) = slow {
+                    conn = conn.slow_query_threshold(threshold);
+                }
+                DatabaseIf::new(Arc::new(conn))
+            }
+            None => DatabaseIf::new(Arc::new(conn)),
+        };
         if let Some(path) = schema {
             db.clone()
                 .query("SELECT name FROM sqlite_master", &[], &[DbType::Text])
@@ -190,6 +232,10 @@ pub fn get_db(
         } else {
             Ok(())
         }
+        .and_then(|_| match migrations {
+            Some(dir) => migration::migrate(db.clone(), dir).map(|_| ()),
+            None => Ok(()),
+        })
         .map(|_| db)
     })
 }